@@ -0,0 +1,970 @@
+use crate::cmd::statetest::{find_all_json_tests, merkle_trie::state_merkle_trie_root};
+use alloy_consensus::{
+    proofs::{
+        calculate_ommers_root, calculate_transaction_root, calculate_withdrawals_root,
+        ordered_trie_root_with_encoder,
+    },
+    transaction::SignerRecoverable,
+    Block, Eip658Value, Header, Transaction, TxEnvelope, EMPTY_OMMER_ROOT_HASH,
+};
+use alloy_eips::{
+    eip1559::{BaseFeeParams, INITIAL_BASE_FEE},
+    eip7685::Requests,
+    eip7840::BlobParams,
+    Typed2718,
+};
+use alloy_rlp::{Decodable, Encodable};
+use clap::Parser;
+use context::either::Either;
+use database::{CacheState, DatabaseSnapshot, PlainAccount, State, StateTrie};
+use revm::{
+    bytecode::Bytecode,
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    handler::{
+        block::{Ommer, Withdrawal},
+        BlockExecutor, BlockInput, MainnetBlockExecutor, Receipt,
+    },
+    primitives::{
+        alloy_primitives::{Bloom, B64},
+        hardfork::SpecId,
+        keccak256, Address, Bytes, B256, U256,
+    },
+    Context, MainBuilder, MainContext,
+};
+use statetest_types::{AccountInfo, BlockHeader, BlockchainTest, BlockchainTestCase, SpecName};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// `blocktest` subcommand
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Path to folder or file containing the blockchain tests
+    ///
+    /// If multiple paths are specified they will be run in sequence.
+    ///
+    /// Folders will be searched recursively for files with the extension `.json`.
+    #[arg(required = true, num_args = 1..)]
+    paths: Vec<PathBuf>,
+    /// Keep going after a test failure
+    #[arg(long, alias = "no-fail-fast")]
+    keep_going: bool,
+}
+
+impl Cmd {
+    /// Runs `blocktest` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let mut n_errors = 0;
+        let mut n_files = 0;
+        let mut counts = TestCounts::default();
+        for path in &self.paths {
+            if !path.exists() {
+                return Err(Error {
+                    name: "Path validation".to_string(),
+                    path: path.display().to_string(),
+                    kind: ErrorKind::InvalidPath,
+                });
+            }
+
+            println!("\nRunning blockchain tests in {}...", path.display());
+            let test_files = find_all_json_tests(path);
+            if test_files.is_empty() {
+                return Err(Error {
+                    name: "Path validation".to_string(),
+                    path: path.display().to_string(),
+                    kind: ErrorKind::NoJsonFiles,
+                });
+            }
+
+            for test_file in test_files {
+                n_files += 1;
+                match execute_test_suite(&test_file) {
+                    Ok(file_counts) => {
+                        counts.passed += file_counts.passed;
+                        counts.skipped += file_counts.skipped;
+                    }
+                    Err(error) => {
+                        if !self.keep_going {
+                            return Err(error);
+                        }
+                        println!("{error}\n");
+                        n_errors += 1;
+                    }
+                }
+            }
+        }
+
+        println!(
+            "{} test cases passed, {} skipped because of unsupported networks",
+            counts.passed, counts.skipped
+        );
+        if n_errors == 0 {
+            println!("All tests passed!");
+            Ok(())
+        } else {
+            println!("Encountered {n_errors} errors out of {n_files} total tests");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Path: {path}\nName: {name}\nError: {kind}")]
+pub struct Error {
+    pub name: String,
+    pub path: String,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum ErrorKind {
+    #[error("block can not be decoded: {0}")]
+    BlockDecode(String),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("signer of transaction {0} can not be recovered")]
+    SignerRecovery(usize),
+    #[error("block failed to execute: {0}")]
+    BlockExecution(String),
+    #[error("block expected to fail with {0} is valid")]
+    InvalidBlockAccepted(String),
+    #[error("gas used mismatch: got {got}, expected {expected}")]
+    GasUsedMismatch { got: u64, expected: u64 },
+    #[error("blob gas used mismatch: got {got:?}, expected {expected:?}")]
+    BlobGasUsedMismatch {
+        got: Option<u64>,
+        expected: Option<u64>,
+    },
+    #[error("genesis hash mismatch: got {got}, expected {expected}")]
+    GenesisHashMismatch { got: B256, expected: B256 },
+    #[error("ommers hash mismatch: got {got}, expected {expected}")]
+    OmmersHashMismatch { got: B256, expected: B256 },
+    #[error("transactions root mismatch: got {got}, expected {expected}")]
+    TransactionsRootMismatch { got: B256, expected: B256 },
+    #[error("withdrawals root mismatch: got {got:?}, expected {expected:?}")]
+    WithdrawalsRootMismatch {
+        got: Option<B256>,
+        expected: Option<B256>,
+    },
+    #[error("receipts root mismatch: got {got}, expected {expected}")]
+    ReceiptsRootMismatch { got: B256, expected: B256 },
+    #[error("logs bloom mismatch")]
+    LogsBloomMismatch,
+    #[error("requests hash mismatch: got {got:?}, expected {expected:?}")]
+    RequestsHashMismatch {
+        got: Option<B256>,
+        expected: Option<B256>,
+    },
+    #[error("state root mismatch: got {got}, expected {expected}")]
+    StateRootMismatch { got: B256, expected: B256 },
+    #[error("post state root mismatch: got {got}, expected {expected}")]
+    PostStateRootMismatch { got: B256, expected: B256 },
+    #[error("last block hash mismatch: got {got}, expected {expected}")]
+    LastBlockHashMismatch { got: B256, expected: B256 },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeDeserialize(#[from] serde_json::Error),
+    #[error("path does not exist")]
+    InvalidPath,
+    #[error("no JSON test files found in path")]
+    NoJsonFiles,
+}
+
+/// Number of test cases that passed and that were skipped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub skipped: usize,
+}
+
+/// Returns the spec of the block, `None` if the network is not supported.
+///
+/// Transition networks switch to the next fork at block 5.
+fn spec_at_block(network: &SpecName, number: u64) -> Option<SpecId> {
+    let (before, after) = match network {
+        // DAO fork irregular state change is not supported and Osaka is skipped as in the
+        // state tests.
+        SpecName::HomesteadToDaoAt5
+        | SpecName::ByzantiumToConstantinopleAt5
+        | SpecName::Constantinople
+        | SpecName::Osaka
+        | SpecName::Unknown => return None,
+        SpecName::FrontierToHomesteadAt5 => (SpecId::FRONTIER, SpecId::HOMESTEAD),
+        SpecName::HomesteadToEIP150At5 => (SpecId::HOMESTEAD, SpecId::TANGERINE),
+        SpecName::EIP158ToByzantiumAt5 => (SpecId::SPURIOUS_DRAGON, SpecId::BYZANTIUM),
+        SpecName::ByzantiumToConstantinopleFixAt5 => (SpecId::BYZANTIUM, SpecId::PETERSBURG),
+        SpecName::BerlinToLondonAt5 => (SpecId::BERLIN, SpecId::LONDON),
+        spec => return Some(spec.to_spec_id()),
+    };
+    Some(if number < 5 { before } else { after })
+}
+
+fn plain_account(info: AccountInfo) -> PlainAccount {
+    let code_hash = keccak256(&info.code);
+    let bytecode =
+        Bytecode::new_raw_checked(info.code.clone()).unwrap_or(Bytecode::new_legacy(info.code));
+    let acc_info = revm::state::AccountInfo {
+        balance: info.balance,
+        code_hash,
+        code: Some(bytecode),
+        nonce: info.nonce,
+    };
+    PlainAccount {
+        info: acc_info,
+        storage: info.storage,
+    }
+}
+
+/// Converts the JSON header of the genesis block.
+fn header(header: &BlockHeader) -> Header {
+    Header {
+        parent_hash: header.parent_hash,
+        ommers_hash: header.uncle_hash,
+        beneficiary: header.coinbase,
+        state_root: header.state_root,
+        transactions_root: header.transactions_trie,
+        receipts_root: header.receipt_trie,
+        logs_bloom: Bloom::from(header.bloom.0),
+        difficulty: header.difficulty,
+        number: header.number.saturating_to(),
+        gas_limit: header.gas_limit.saturating_to(),
+        gas_used: header.gas_used.saturating_to(),
+        timestamp: header.timestamp.saturating_to(),
+        extra_data: header.extra_data.clone(),
+        mix_hash: header.mix_hash,
+        nonce: header.nonce,
+        base_fee_per_gas: header.base_fee_per_gas.map(|fee| fee.saturating_to()),
+        withdrawals_root: header.withdrawals_root,
+        blob_gas_used: header.blob_gas_used.map(|gas| gas.saturating_to()),
+        excess_blob_gas: header.excess_blob_gas.map(|gas| gas.saturating_to()),
+        parent_beacon_block_root: header.parent_beacon_block_root,
+        requests_hash: header.requests_hash,
+    }
+}
+
+/// Returns the blob parameters of the spec.
+fn blob_params(spec: SpecId) -> BlobParams {
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        BlobParams::prague()
+    } else {
+        BlobParams::cancun()
+    }
+}
+
+/// Returns the proof of work difficulty of the block.
+///
+/// The difficulty bomb is delayed by Byzantium and the Glacier forks.
+fn calc_difficulty(header: &Header, parent: &Header, spec: SpecId) -> U256 {
+    const MINIMUM_DIFFICULTY: u64 = 131_072;
+
+    let elapsed = header.timestamp.saturating_sub(parent.timestamp).min(1_000);
+    let factor = if spec.is_enabled_in(SpecId::BYZANTIUM) {
+        let uncles = if parent.ommers_hash == EMPTY_OMMER_ROOT_HASH {
+            1
+        } else {
+            2
+        };
+        (uncles - (elapsed / 9) as i64).max(-99)
+    } else if spec.is_enabled_in(SpecId::HOMESTEAD) {
+        (1 - (elapsed / 10) as i64).max(-99)
+    } else if elapsed < 13 {
+        1
+    } else {
+        -1
+    };
+    let adjustment = parent.difficulty / U256::from(2048) * U256::from(factor.unsigned_abs());
+    let difficulty = if factor >= 0 {
+        parent.difficulty.saturating_add(adjustment)
+    } else {
+        parent.difficulty.saturating_sub(adjustment)
+    };
+    let difficulty = difficulty.max(U256::from(MINIMUM_DIFFICULTY));
+
+    let bomb_delay = [
+        (SpecId::GRAY_GLACIER, 11_400_000),
+        (SpecId::ARROW_GLACIER, 10_700_000),
+        (SpecId::LONDON, 9_700_000),
+        (SpecId::MUIR_GLACIER, 9_000_000),
+        (SpecId::PETERSBURG, 5_000_000),
+        (SpecId::BYZANTIUM, 3_000_000),
+    ]
+    .into_iter()
+    .find_map(|(fork, delay)| spec.is_enabled_in(fork).then_some(delay))
+    .unwrap_or_default();
+    let period = header.number.saturating_sub(bomb_delay) / 100_000;
+    if period > 1 {
+        difficulty.saturating_add(U256::from(1) << (period - 2) as usize)
+    } else {
+        difficulty
+    }
+}
+
+/// Validates the header against its parent.
+///
+/// Proof of work seals and ommer headers are not validated.
+fn validate_header(header: &Header, parent: &Header, spec: SpecId) -> Result<(), String> {
+    if header.number != parent.number + 1 {
+        return Err(format!(
+            "number {} does not follow parent number {}",
+            header.number, parent.number
+        ));
+    }
+    if header.timestamp <= parent.timestamp {
+        return Err(format!(
+            "timestamp {} is not after parent timestamp {}",
+            header.timestamp, parent.timestamp
+        ));
+    }
+    if header.extra_data.len() > 32 {
+        return Err(format!("extra data has {} bytes", header.extra_data.len()));
+    }
+    if header.gas_used > header.gas_limit {
+        return Err(format!(
+            "gas used {} is above gas limit {}",
+            header.gas_used, header.gas_limit
+        ));
+    }
+
+    for (field, present, fork) in [
+        (
+            "base fee",
+            header.base_fee_per_gas.is_some(),
+            SpecId::LONDON,
+        ),
+        (
+            "withdrawals root",
+            header.withdrawals_root.is_some(),
+            SpecId::SHANGHAI,
+        ),
+        (
+            "blob gas used",
+            header.blob_gas_used.is_some(),
+            SpecId::CANCUN,
+        ),
+        (
+            "excess blob gas",
+            header.excess_blob_gas.is_some(),
+            SpecId::CANCUN,
+        ),
+        (
+            "parent beacon block root",
+            header.parent_beacon_block_root.is_some(),
+            SpecId::CANCUN,
+        ),
+        (
+            "requests hash",
+            header.requests_hash.is_some(),
+            SpecId::PRAGUE,
+        ),
+    ] {
+        if present != spec.is_enabled_in(fork) {
+            return Err(format!("{field} is present only from {fork:?}"));
+        }
+    }
+
+    // Gas target of the first London block is the gas limit of its parent.
+    let is_london_fork_block =
+        spec.is_enabled_in(SpecId::LONDON) && parent.base_fee_per_gas.is_none();
+    let parent_gas_limit = if is_london_fork_block {
+        parent.gas_limit * BaseFeeParams::ethereum().elasticity_multiplier as u64
+    } else {
+        parent.gas_limit
+    };
+    if header.gas_limit.abs_diff(parent_gas_limit) >= parent_gas_limit / 1024
+        || header.gas_limit < 5000
+    {
+        return Err(format!(
+            "gas limit {} is invalid for parent gas limit {parent_gas_limit}",
+            header.gas_limit
+        ));
+    }
+    if spec.is_enabled_in(SpecId::LONDON) {
+        let expected = if is_london_fork_block {
+            INITIAL_BASE_FEE
+        } else {
+            parent
+                .next_block_base_fee(BaseFeeParams::ethereum())
+                .unwrap_or_default()
+        };
+        if header.base_fee_per_gas != Some(expected) {
+            return Err(format!(
+                "base fee {:?} is not {expected}",
+                header.base_fee_per_gas
+            ));
+        }
+    }
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        // Parent of the first Cancun block has no blob gas fields.
+        let expected = parent
+            .next_block_excess_blob_gas(blob_params(spec))
+            .unwrap_or_default();
+        if header.excess_blob_gas != Some(expected) {
+            return Err(format!(
+                "excess blob gas {:?} is not {expected}",
+                header.excess_blob_gas
+            ));
+        }
+    }
+
+    if spec.is_enabled_in(SpecId::MERGE) {
+        if !header.difficulty.is_zero() || header.nonce != B64::ZERO {
+            return Err("difficulty and nonce are not zero after the merge".to_string());
+        }
+    } else {
+        let expected = calc_difficulty(header, parent, spec);
+        if header.difficulty != expected {
+            return Err(format!(
+                "difficulty {} is not {expected}",
+                header.difficulty
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn block_env(header: &Header, spec: SpecId) -> BlockEnv {
+    let mut block = BlockEnv {
+        number: header.number,
+        beneficiary: header.beneficiary,
+        timestamp: header.timestamp,
+        gas_limit: header.gas_limit,
+        basefee: header.base_fee_per_gas.unwrap_or_default(),
+        difficulty: header.difficulty,
+        prevrandao: None,
+        blob_excess_gas_and_price: None,
+    };
+    if spec.is_enabled_in(SpecId::MERGE) {
+        block.prevrandao = Some(header.mix_hash);
+    }
+    if let Some(excess_blob_gas) = header.excess_blob_gas {
+        block.set_blob_excess_gas_and_price(excess_blob_gas, spec.is_enabled_in(SpecId::PRAGUE));
+    }
+    block
+}
+
+fn tx_env(tx: &TxEnvelope, caller: Address) -> TxEnv {
+    TxEnv {
+        tx_type: tx.ty(),
+        caller,
+        gas_limit: tx.gas_limit(),
+        gas_price: tx.max_fee_per_gas(),
+        kind: tx.kind(),
+        value: tx.value(),
+        data: tx.input().clone(),
+        nonce: tx.nonce(),
+        chain_id: tx.chain_id(),
+        access_list: tx.access_list().cloned().unwrap_or_default(),
+        gas_priority_fee: tx.max_priority_fee_per_gas(),
+        blob_hashes: tx
+            .blob_versioned_hashes()
+            .map(<[B256]>::to_vec)
+            .unwrap_or_default(),
+        max_fee_per_blob_gas: tx.max_fee_per_blob_gas().unwrap_or_default(),
+        authorization_list: tx
+            .authorization_list()
+            .unwrap_or_default()
+            .iter()
+            .map(|auth| Either::Left(auth.clone()))
+            .collect(),
+    }
+}
+
+/// Returns the receipts root and the logs bloom of the block.
+///
+/// Receipts contain the state root after every transaction before Byzantium, which is not
+/// calculated, so the receipts root is `None` for these blocks.
+fn receipts_root(receipts: &[Receipt], spec: SpecId) -> (Option<B256>, Bloom) {
+    let receipts: Vec<_> = receipts
+        .iter()
+        .map(|receipt| {
+            let encoded = alloy_consensus::Receipt {
+                status: Eip658Value::Eip658(receipt.success),
+                cumulative_gas_used: receipt.cumulative_gas_used,
+                logs: receipt.logs.clone(),
+            }
+            .with_bloom();
+            (receipt.tx_type, encoded)
+        })
+        .collect();
+    let mut logs_bloom = Bloom::ZERO;
+    for (_, receipt) in &receipts {
+        logs_bloom.accrue_bloom(&receipt.logs_bloom);
+    }
+    let root = spec.is_enabled_in(SpecId::BYZANTIUM).then(|| {
+        ordered_trie_root_with_encoder(&receipts, |(tx_type, receipt), buf| {
+            if *tx_type != 0 {
+                buf.push(*tx_type);
+            }
+            receipt.encode(buf);
+        })
+    });
+    (root, logs_bloom)
+}
+
+/// Decodes, validates and executes the block on top of its parent.
+///
+/// Returns the header of the block, which is valid if its roots match the execution.
+fn execute_block(
+    network: &SpecName,
+    state: &mut State<database::EmptyDB>,
+    state_trie: &mut StateTrie,
+    rlp: &Bytes,
+    parent: &Header,
+) -> Result<Header, ErrorKind> {
+    let mut buf = rlp.as_ref();
+    let block =
+        Block::<TxEnvelope>::decode(&mut buf).map_err(|e| ErrorKind::BlockDecode(e.to_string()))?;
+    if !buf.is_empty() {
+        return Err(ErrorKind::BlockDecode(format!(
+            "{} trailing bytes",
+            buf.len()
+        )));
+    }
+    let Block { header, body } = block;
+    let spec = spec_at_block(network, header.number).expect("network is supported");
+
+    validate_header(&header, parent, spec).map_err(ErrorKind::InvalidHeader)?;
+    if header.parent_hash != parent.hash_slow() {
+        return Err(ErrorKind::InvalidHeader(format!(
+            "parent hash {} is not the hash of the previous block",
+            header.parent_hash
+        )));
+    }
+    let ommers_hash = calculate_ommers_root(&body.ommers);
+    if header.ommers_hash != ommers_hash {
+        return Err(ErrorKind::OmmersHashMismatch {
+            got: ommers_hash,
+            expected: header.ommers_hash,
+        });
+    }
+    let max_ommers = if spec.is_enabled_in(SpecId::MERGE) {
+        0
+    } else {
+        2
+    };
+    if body.ommers.len() > max_ommers {
+        return Err(ErrorKind::InvalidHeader(format!(
+            "block has {} ommers",
+            body.ommers.len()
+        )));
+    }
+    let transactions_root = calculate_transaction_root(&body.transactions);
+    if header.transactions_root != transactions_root {
+        return Err(ErrorKind::TransactionsRootMismatch {
+            got: transactions_root,
+            expected: header.transactions_root,
+        });
+    }
+    let withdrawals_root = body
+        .withdrawals
+        .as_ref()
+        .map(|withdrawals| calculate_withdrawals_root(withdrawals));
+    if header.withdrawals_root != withdrawals_root {
+        return Err(ErrorKind::WithdrawalsRootMismatch {
+            got: withdrawals_root,
+            expected: header.withdrawals_root,
+        });
+    }
+
+    let transactions = body
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            // Signatures with a high `s` value are valid only in Frontier.
+            let caller = if spec.is_enabled_in(SpecId::HOMESTEAD) {
+                tx.recover_signer()
+            } else {
+                tx.recover_signer_unchecked()
+            };
+            caller
+                .map(|caller| tx_env(tx, caller))
+                .map_err(|_| ErrorKind::SignerRecovery(index))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut cfg = CfgEnv::new_with_spec(spec);
+    cfg.set_blob_max_count(blob_params(spec).max_blob_count);
+    state
+        .cache
+        .set_state_clear_flag(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+    let mut input = BlockInput::new(block_env(&header, spec), transactions)
+        .with_parent_hash(header.parent_hash)
+        .with_ommers(
+            body.ommers
+                .iter()
+                .map(|ommer| Ommer {
+                    beneficiary: ommer.beneficiary,
+                    number: ommer.number,
+                })
+                .collect(),
+        );
+    if let Some(root) = header.parent_beacon_block_root {
+        input = input.with_parent_beacon_block_root(root);
+    }
+    if let Some(withdrawals) = body.withdrawals {
+        input = input.with_withdrawals(
+            withdrawals
+                .into_iter()
+                .map(|withdrawal| Withdrawal {
+                    index: withdrawal.index,
+                    validator_index: withdrawal.validator_index,
+                    address: withdrawal.address,
+                    amount: withdrawal.amount,
+                })
+                .collect(),
+        );
+    }
+
+    let mut evm = Context::mainnet()
+        .with_cfg(cfg)
+        .with_db(&mut *state)
+        .build_mainnet();
+    let output = MainnetBlockExecutor::default()
+        .execute_block(&mut evm, input)
+        .map_err(|e| ErrorKind::BlockExecution(e.to_string()))?;
+
+    if output.gas_used != header.gas_used {
+        return Err(ErrorKind::GasUsedMismatch {
+            got: output.gas_used,
+            expected: header.gas_used,
+        });
+    }
+    let blob_gas_used = spec
+        .is_enabled_in(SpecId::CANCUN)
+        .then_some(output.blob_gas_used);
+    if header.blob_gas_used != blob_gas_used {
+        return Err(ErrorKind::BlobGasUsedMismatch {
+            got: blob_gas_used,
+            expected: header.blob_gas_used,
+        });
+    }
+    let (receipts_root, logs_bloom) = receipts_root(&output.receipts, spec);
+    if let Some(receipts_root) = receipts_root {
+        if header.receipts_root != receipts_root {
+            return Err(ErrorKind::ReceiptsRootMismatch {
+                got: receipts_root,
+                expected: header.receipts_root,
+            });
+        }
+    }
+    if header.logs_bloom != logs_bloom {
+        return Err(ErrorKind::LogsBloomMismatch);
+    }
+    let requests_hash = spec
+        .is_enabled_in(SpecId::PRAGUE)
+        .then(|| Requests::new(output.requests).requests_hash());
+    if header.requests_hash != requests_hash {
+        return Err(ErrorKind::RequestsHashMismatch {
+            got: requests_hash,
+            expected: header.requests_hash,
+        });
+    }
+
+    state_trie.apply_bundle(&output.bundle_state);
+    let state_root = state_trie.root();
+    if state_root != header.state_root {
+        return Err(ErrorKind::StateRootMismatch {
+            got: state_root,
+            expected: header.state_root,
+        });
+    }
+    Ok(header)
+}
+
+/// Executes all blocks of the test case and checks the post state and the last block hash.
+///
+/// Blocks that are expected to be invalid have to be rejected, the chain continues from the
+/// last valid block. Returns `false` if the network is not supported and the case is skipped.
+fn execute_test_case(name: &str, path: &str, case: BlockchainTestCase) -> Result<bool, Error> {
+    let error = |block: Option<usize>, kind| Error {
+        name: match block {
+            Some(index) => format!("{name} (block {index})"),
+            None => name.to_string(),
+        },
+        path: path.to_string(),
+        kind,
+    };
+
+    let Some(genesis_spec) = spec_at_block(&case.network, 0) else {
+        return Ok(false);
+    };
+    let mut parent = header(&case.genesis_block_header);
+    let genesis_hash = parent.hash_slow();
+    if genesis_hash != case.genesis_block_header.hash {
+        return Err(error(
+            None,
+            ErrorKind::GenesisHashMismatch {
+                got: genesis_hash,
+                expected: case.genesis_block_header.hash,
+            },
+        ));
+    }
+
+    let mut cache_state = CacheState::new(genesis_spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+    for (address, info) in case.pre {
+        let account = plain_account(info);
+        cache_state.insert_account_with_storage(address, account.info, account.storage);
+    }
+    // State root is updated incrementally from the bundle of every block.
+    let mut state_trie = StateTrie::from_plain_accounts(cache_state.trie_account());
+    let mut state = State::builder()
+        .with_cached_prestate(cache_state)
+        .with_bundle_update()
+        .build();
+
+    for (index, block) in case.blocks.into_iter().enumerate() {
+        let snapshot = state.snapshot();
+        // State trie of an invalid block is restored after its state root is checked.
+        let state_trie_before = block.expect_exception.is_some().then(|| state_trie.clone());
+        let result = execute_block(
+            &case.network,
+            &mut state,
+            &mut state_trie,
+            &block.rlp,
+            &parent,
+        );
+        match (result, block.expect_exception) {
+            (Ok(header), None) => {
+                state.release_snapshot(snapshot);
+                parent = header;
+            }
+            (Ok(_), Some(exception)) => {
+                return Err(error(
+                    Some(index),
+                    ErrorKind::InvalidBlockAccepted(exception),
+                ))
+            }
+            (Err(kind), None) => return Err(error(Some(index), kind)),
+            (Err(_), Some(_)) => {
+                state.revert_to(snapshot);
+                state_trie = state_trie_before.expect("saved for invalid blocks");
+            }
+        }
+    }
+
+    let last_block_hash = parent.hash_slow();
+    if last_block_hash != case.last_block_hash {
+        return Err(error(
+            None,
+            ErrorKind::LastBlockHashMismatch {
+                got: last_block_hash,
+                expected: case.last_block_hash,
+            },
+        ));
+    }
+
+    let state_root = state_trie.root();
+    let expected_root = match (case.post_state, case.post_state_hash) {
+        (Some(post_state), _) => {
+            let accounts: Vec<(Address, PlainAccount)> = post_state
+                .into_iter()
+                .map(|(address, info)| (address, plain_account(info)))
+                .collect();
+            state_merkle_trie_root(accounts.iter().map(|(address, acc)| (*address, acc)))
+        }
+        (None, Some(hash)) => hash,
+        (None, None) => return Ok(true),
+    };
+    if state_root != expected_root {
+        return Err(error(
+            None,
+            ErrorKind::PostStateRootMismatch {
+                got: state_root,
+                expected: expected_root,
+            },
+        ));
+    }
+    Ok(true)
+}
+
+/// Executes all test cases in the blockchain test file.
+pub fn execute_test_suite(path: &Path) -> Result<TestCounts, Error> {
+    let error = |kind| Error {
+        name: "Unknown".to_string(),
+        path: path.to_string_lossy().into_owned(),
+        kind,
+    };
+    let s = std::fs::read_to_string(path).map_err(|e| error(e.into()))?;
+    let suite: BlockchainTest = serde_json::from_str(&s).map_err(|e| error(e.into()))?;
+    let path = path.to_string_lossy().into_owned();
+
+    let mut counts = TestCounts::default();
+    for (name, case) in suite.0 {
+        if execute_test_case(&name, &path, case)? {
+            counts.passed += 1;
+        } else {
+            counts.skipped += 1;
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{proofs::calculate_withdrawals_root, BlockBody, EMPTY_ROOT_HASH};
+    use alloy_eips::eip4895::{Withdrawal, Withdrawals};
+    use revm::primitives::{address, HashMap};
+
+    const SENDER: Address = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+    const VALIDATOR: Address = address!("0000000000000000000000000000000000001000");
+
+    fn account(balance: u64) -> AccountInfo {
+        AccountInfo {
+            balance: U256::from(balance),
+            code: Bytes::new(),
+            nonce: 0,
+            storage: HashMap::default(),
+        }
+    }
+
+    fn state_root(accounts: &HashMap<Address, AccountInfo>) -> B256 {
+        let accounts: Vec<(Address, PlainAccount)> = accounts
+            .iter()
+            .map(|(address, info)| (*address, plain_account(info.clone())))
+            .collect();
+        state_merkle_trie_root(accounts.iter().map(|(address, acc)| (*address, acc)))
+    }
+
+    fn block_header(header: &Header) -> BlockHeader {
+        BlockHeader {
+            parent_hash: header.parent_hash,
+            uncle_hash: header.ommers_hash,
+            coinbase: header.beneficiary,
+            state_root: header.state_root,
+            transactions_trie: header.transactions_root,
+            receipt_trie: header.receipts_root,
+            bloom: header.logs_bloom.0,
+            difficulty: header.difficulty,
+            number: U256::from(header.number),
+            gas_limit: U256::from(header.gas_limit),
+            gas_used: U256::from(header.gas_used),
+            timestamp: U256::from(header.timestamp),
+            extra_data: header.extra_data.clone(),
+            mix_hash: header.mix_hash,
+            nonce: header.nonce,
+            base_fee_per_gas: header.base_fee_per_gas.map(U256::from),
+            withdrawals_root: header.withdrawals_root,
+            blob_gas_used: header.blob_gas_used.map(U256::from),
+            excess_blob_gas: header.excess_blob_gas.map(U256::from),
+            parent_beacon_block_root: header.parent_beacon_block_root,
+            requests_hash: header.requests_hash,
+            hash: header.hash_slow(),
+        }
+    }
+
+    /// Returns a Shanghai block on top of the parent that withdraws `amount` gwei to the
+    /// validator.
+    fn withdrawal_block(parent: &Header, amount: u64, state_root: B256) -> (Header, Bytes) {
+        let withdrawals = Withdrawals::new(vec![Withdrawal {
+            index: parent.number,
+            validator_index: 0,
+            address: VALIDATOR,
+            amount,
+        }]);
+        let header = Header {
+            parent_hash: parent.hash_slow(),
+            number: parent.number + 1,
+            timestamp: parent.timestamp + 12,
+            gas_limit: parent.gas_limit,
+            base_fee_per_gas: parent.next_block_base_fee(BaseFeeParams::ethereum()),
+            withdrawals_root: Some(calculate_withdrawals_root(&withdrawals)),
+            state_root,
+            ..parent.clone()
+        };
+        let block = Block::<TxEnvelope> {
+            header: header.clone(),
+            body: BlockBody {
+                transactions: Vec::new(),
+                ommers: Vec::new(),
+                withdrawals: Some(withdrawals),
+            },
+        };
+        let mut rlp = Vec::new();
+        block.encode(&mut rlp);
+        (header, rlp.into())
+    }
+
+    /// Returns a case with a valid withdrawal block followed by a block with a wrong state
+    /// root.
+    fn test_case(network: SpecName) -> BlockchainTestCase {
+        let pre = HashMap::from_iter([(SENDER, account(1_000_000))]);
+        let genesis = Header {
+            state_root: state_root(&pre),
+            transactions_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(INITIAL_BASE_FEE),
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            ..Default::default()
+        };
+
+        let mut post = pre.clone();
+        post.insert(VALIDATOR, account(2_000_000_000));
+        let (valid, valid_rlp) = withdrawal_block(&genesis, 2, state_root(&post));
+        // The state root of the parent misses the withdrawal of the block.
+        let (_, invalid_rlp) = withdrawal_block(&valid, 3, valid.state_root);
+
+        BlockchainTestCase {
+            info: None,
+            network,
+            genesis_block_header: block_header(&genesis),
+            genesis_rlp: None,
+            pre,
+            post_state: Some(post),
+            post_state_hash: None,
+            last_block_hash: valid.hash_slow(),
+            blocks: vec![
+                statetest_types::Block {
+                    rlp: valid_rlp,
+                    block_header: None,
+                    transactions: Vec::new(),
+                    uncle_headers: Vec::new(),
+                    withdrawals: None,
+                    expect_exception: None,
+                },
+                statetest_types::Block {
+                    rlp: invalid_rlp,
+                    block_header: None,
+                    transactions: Vec::new(),
+                    uncle_headers: Vec::new(),
+                    withdrawals: None,
+                    expect_exception: Some("InvalidStateRoot".to_string()),
+                },
+            ],
+            seal_engine: None,
+        }
+    }
+
+    #[test]
+    fn valid_and_invalid_blocks() {
+        assert!(execute_test_case("case", "path", test_case(SpecName::Shanghai)).unwrap());
+    }
+
+    #[test]
+    fn invalid_block_is_rejected() {
+        let mut case = test_case(SpecName::Shanghai);
+        case.blocks[1].expect_exception = None;
+
+        let error = execute_test_case("case", "path", case).unwrap_err();
+        assert_eq!(error.name, "case (block 1)");
+        assert!(matches!(error.kind, ErrorKind::StateRootMismatch { .. }));
+    }
+
+    #[test]
+    fn valid_block_expected_to_fail() {
+        let mut case = test_case(SpecName::Shanghai);
+        case.blocks[0].expect_exception = Some("InvalidStateRoot".to_string());
+
+        let error = execute_test_case("case", "path", case).unwrap_err();
+        assert_eq!(error.name, "case (block 0)");
+        assert!(matches!(error.kind, ErrorKind::InvalidBlockAccepted(_)));
+    }
+
+    #[test]
+    fn osaka_is_skipped() {
+        assert!(!execute_test_case("case", "path", test_case(SpecName::Osaka)).unwrap());
+    }
+}
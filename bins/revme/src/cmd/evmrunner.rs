@@ -1,6 +1,10 @@
 use clap::Parser;
 use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
-use inspector::{inspectors::TracerEip3155, InspectEvm};
+use inspector::{
+    inspectors::{Profiler, TracerEip3155},
+    profiler::ProfileMetric,
+    InspectEvm,
+};
 use revm::{
     bytecode::{Bytecode, BytecodeDecodeError},
     primitives::{hex, TxKind},
@@ -51,6 +55,12 @@ pub struct Cmd {
     /// Whether to print the trace
     #[arg(long)]
     trace: bool,
+    /// Whether to profile the execution and print the gas and time spent per opcode
+    #[arg(long)]
+    profile: bool,
+    /// Path to write the collapsed stacks of the profile to, for flamegraphs
+    #[arg(long, requires = "profile")]
+    flamegraph: Option<PathBuf>,
 }
 
 impl Cmd {
@@ -82,15 +92,33 @@ impl Cmd {
 
         // BenchmarkDB is dummy state that implements Database trait.
         // The bytecode is deployed at zero address.
-        let mut evm = Context::mainnet()
-            .with_db(db)
-            .modify_tx_chained(|tx| {
-                tx.caller = BENCH_CALLER;
-                tx.kind = TxKind::Call(BENCH_TARGET);
-                tx.data = input;
-                tx.nonce = nonce;
-            })
-            .build_mainnet_with_inspector(TracerEip3155::new(Box::new(std::io::stdout())));
+        let ctx = Context::mainnet().with_db(db).modify_tx_chained(|tx| {
+            tx.caller = BENCH_CALLER;
+            tx.kind = TxKind::Call(BENCH_TARGET);
+            tx.data = input;
+            tx.nonce = nonce;
+        });
+
+        if self.profile {
+            let mut evm = ctx.build_mainnet_with_inspector(Profiler::new());
+            let time = Instant::now();
+            let out = evm.inspect_replay().map_err(|_| Errors::EVMError)?;
+            let time = time.elapsed();
+            println!("Result: {:#?}", out.result);
+            if self.state {
+                println!("State: {:#?}", out.state);
+            }
+            evm.inspector.write_opcode_histogram(std::io::stdout())?;
+            if let Some(path) = &self.flamegraph {
+                evm.inspector
+                    .write_collapsed(fs::File::create(path)?, ProfileMetric::Gas)?;
+            }
+            println!("Elapsed: {:?}", time);
+            return Ok(());
+        }
+
+        let mut evm =
+            ctx.build_mainnet_with_inspector(TracerEip3155::new(Box::new(std::io::stdout())));
 
         if self.bench {
             let mut criterion = criterion::Criterion::default()
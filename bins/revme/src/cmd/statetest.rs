@@ -2,11 +2,16 @@ pub mod merkle_trie;
 mod runner;
 pub mod utils;
 
-pub use runner::{TestError as Error, TestErrorKind};
+pub use runner::{find_all_json_tests, TestError as Error, TestErrorKind};
 
 use clap::Parser;
-use runner::{find_all_json_tests, run, TestError};
-use std::path::PathBuf;
+use inspector::{inspectors::Profiler, profiler::ProfileMetric};
+use runner::{run, TestError};
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 /// `statetest` subcommand
 #[derive(Parser, Debug)]
@@ -36,11 +41,18 @@ pub struct Cmd {
     /// Keep going after a test failure
     #[arg(long, alias = "no-fail-fast")]
     keep_going: bool,
+    /// Profile the execution and print the gas and time spent per opcode and precompile
+    #[arg(long)]
+    profile: bool,
+    /// Path to write the collapsed stacks of the profile to, for flamegraphs
+    #[arg(long, requires = "profile")]
+    flamegraph: Option<PathBuf>,
 }
 
 impl Cmd {
     /// Runs `statetest` command.
     pub fn run(&self) -> Result<(), TestError> {
+        let profiler = self.profile.then(|| Arc::new(Mutex::new(Profiler::new())));
         for path in &self.paths {
             if !path.exists() {
                 return Err(TestError {
@@ -67,8 +79,28 @@ impl Cmd {
                 self.json,
                 self.json_outcome,
                 self.keep_going,
+                profiler.clone(),
             )?
         }
+
+        if let Some(profiler) = profiler {
+            let profiler = profiler.lock().unwrap();
+            let to_error = |error: std::io::Error| TestError {
+                name: "Profile".to_string(),
+                path: String::new(),
+                kind: error.into(),
+            };
+            println!();
+            profiler
+                .write_opcode_histogram(std::io::stdout())
+                .map_err(to_error)?;
+            if let Some(path) = &self.flamegraph {
+                let file = File::create(path).map_err(to_error)?;
+                profiler
+                    .write_collapsed(file, ProfileMetric::Gas)
+                    .map_err(to_error)?;
+            }
+        }
         Ok(())
     }
 }
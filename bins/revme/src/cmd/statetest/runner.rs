@@ -5,7 +5,10 @@ use super::{
 use context::either::Either;
 use database::State;
 use indicatif::{ProgressBar, ProgressDrawTarget};
-use inspector::{inspectors::TracerEip3155, InspectCommitEvm};
+use inspector::{
+    inspectors::{Profiler, TracerEip3155},
+    InspectCommitEvm,
+};
 use primitives::U256;
 use revm::{
     bytecode::Bytecode,
@@ -72,6 +75,8 @@ pub enum TestErrorKind {
     InvalidPath,
     #[error("no JSON test files found in path")]
     NoJsonFiles,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
@@ -224,6 +229,7 @@ pub fn execute_test_suite(
     elapsed: &Arc<Mutex<Duration>>,
     trace: bool,
     print_json_outcome: bool,
+    profiler: Option<&Mutex<Profiler>>,
 ) -> Result<(), TestError> {
     if skip_test(path) {
         return Ok(());
@@ -426,6 +432,12 @@ pub fn execute_test_suite(
                     let res = evm.inspect_replay_commit();
                     let db = evm.ctx.journaled_state.database;
                     (db, res)
+                } else if let Some(profiler) = profiler {
+                    let mut evm = evm_context.build_mainnet_with_inspector(Profiler::new());
+                    let res = evm.inspect_replay_commit();
+                    profiler.lock().unwrap().merge(&evm.inspector);
+                    let db = evm.ctx.journaled_state.database;
+                    (db, res)
                 } else {
                     let mut evm = evm_context.build_mainnet();
                     let res = evm.replay_commit();
@@ -510,6 +522,7 @@ pub fn run(
     trace: bool,
     mut print_outcome: bool,
     keep_going: bool,
+    profiler: Option<Arc<Mutex<Profiler>>>,
 ) -> Result<(), TestError> {
     // Trace implies print_outcome
     if trace {
@@ -540,6 +553,7 @@ pub fn run(
         let n_errors = n_errors.clone();
         let console_bar = console_bar.clone();
         let elapsed = elapsed.clone();
+        let profiler = profiler.clone();
 
         let thread = std::thread::Builder::new().name(format!("runner-{i}"));
 
@@ -558,7 +572,13 @@ pub fn run(
                 (prev_idx, test_path)
             };
 
-            let result = execute_test_suite(&test_path, &elapsed, trace, print_outcome);
+            let result = execute_test_suite(
+                &test_path,
+                &elapsed,
+                trace,
+                print_outcome,
+                profiler.as_deref(),
+            );
 
             // Increment after the test is done.
             console_bar.inc(1);
@@ -29,6 +29,14 @@ pub trait Cfg {
     fn is_nonce_check_disabled(&self) -> bool;
 
     fn is_base_fee_check_disabled(&self) -> bool;
+
+    /// Returns `true` if value transfers emit synthetic
+    /// [ERC-7528](https://eips.ethereum.org/EIPS/eip-7528) logs.
+    ///
+    /// Disabled by default.
+    fn is_transfer_log_enabled(&self) -> bool {
+        false
+    }
 }
 
 /// What bytecode analysis to perform
@@ -2,11 +2,12 @@ use crate::context::{SStoreResult, SelfDestructResult};
 use core::ops::{Deref, DerefMut};
 use database_interface::Database;
 use primitives::{
-    hardfork::SpecId, Address, Bytes, HashSet, Log, StorageKey, StorageValue, B256, U256,
+    address, b256, hardfork::SpecId, Address, Bytes, HashSet, Log, LogData, StorageKey,
+    StorageValue, B256, U256,
 };
 use state::{
     bytecode::{EOF_MAGIC_BYTES, EOF_MAGIC_HASH},
-    Account, Bytecode,
+    Account, BlockAccessList, Bytecode,
 };
 
 /// Trait that contains database and journal of all changes that were made to the state.
@@ -77,6 +78,32 @@ pub trait JournalTr {
     /// Sets the spec id.
     fn set_spec_id(&mut self, spec_id: SpecId);
 
+    /// Enables or disables the [ERC-7528](https://eips.ethereum.org/EIPS/eip-7528) logs of
+    /// the value transfers, see [`transfer_log`].
+    ///
+    /// The logs are added to the journal directly and are not passed to the inspector log
+    /// hook. Journals without transfer logs can keep the default no-op.
+    fn set_transfer_logs(&mut self, _enabled: bool) {}
+
+    /// Sets the [EIP-7928](https://eips.ethereum.org/EIPS/eip-7928) block access list, `None`
+    /// stops the recording.
+    ///
+    /// Accesses and changes of a transaction are recorded into the list when the journal is
+    /// finalized. Journals without block access lists can keep the default no-op.
+    fn set_block_access_list(&mut self, _block_access_list: Option<BlockAccessList>) {}
+
+    /// Takes the block access list and stops the recording.
+    fn take_block_access_list(&mut self) -> Option<BlockAccessList> {
+        None
+    }
+
+    /// Sets the block access index at which the following changes are recorded.
+    fn set_block_access_index(&mut self, _index: u64) {}
+
+    /// Records a balance change made outside of the journal, like a withdrawal, at the block
+    /// access index. Only the access is recorded if the balance is unchanged.
+    fn record_balance_change(&mut self, _address: Address, _original: U256, _balance: U256) {}
+
     /// Touches the account.
     fn touch_account(&mut self, address: Address);
 
@@ -200,6 +227,28 @@ pub trait JournalTr {
     fn finalize(&mut self) -> Self::FinalOutput;
 }
 
+/// Address of the synthetic ETH transfer logs, as defined in
+/// [ERC-7528](https://eips.ethereum.org/EIPS/eip-7528).
+pub const TRANSFER_LOG_ADDRESS: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// Topic of the ERC-20 `Transfer(address,address,uint256)` event.
+pub const TRANSFER_EVENT_TOPIC: B256 =
+    b256!("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Returns the synthetic log of the ETH transfer.
+///
+/// It has the layout of the ERC-20 `Transfer` event emitted by [`TRANSFER_LOG_ADDRESS`], so
+/// native ETH flows can be tracked the same way as ERC-20 transfers.
+pub fn transfer_log(from: Address, to: Address, value: U256) -> Log {
+    Log {
+        address: TRANSFER_LOG_ADDRESS,
+        data: LogData::new_unchecked(
+            [TRANSFER_EVENT_TOPIC, from.into_word(), to.into_word()].into(),
+            value.to_be_bytes_vec().into(),
+        ),
+    }
+}
+
 /// Transfer and creation result
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TransferError {
@@ -372,6 +372,10 @@ pub enum InvalidTransaction {
     Eip7702NotSupported,
     /// EIP-7873 is not supported.
     Eip7873NotSupported,
+    /// Transaction type is not supported by the chain.
+    TxTypeNotSupported {
+        tx_type: u8,
+    },
     // TODO (EOF)
     // /// EIP-7873 needs to have at least one initcode.
     // Eip7873EmptyInitcodeList,
@@ -475,6 +479,9 @@ impl fmt::Display for InvalidTransaction {
             Self::Eip4844NotSupported => write!(f, "Eip4844 is not supported"),
             Self::Eip7702NotSupported => write!(f, "Eip7702 is not supported"),
             Self::Eip7873NotSupported => write!(f, "Eip7873 is not supported"),
+            Self::TxTypeNotSupported { tx_type } => {
+                write!(f, "transaction type {tx_type} is not supported")
+            }
             // TODO(EOF)
             // Self::Eip7873EmptyInitcodeList => {
             //     write!(f, "Eip7873 initcode list should have at least one initcode")
@@ -1,6 +1,6 @@
 //! This module contains [`BlockEnv`] and it implements [`Block`] trait.
 use context_interface::block::{BlobExcessGasAndPrice, Block};
-use primitives::{Address, B256, U256};
+use primitives::{Address, HashMap, B256, U256};
 
 /// The block environment
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -109,3 +109,250 @@ impl Default for BlockEnv {
         }
     }
 }
+
+/// Overrides of the block fields used for simulation, as in `eth_call` and `eth_simulateV1`.
+///
+/// Fields that are `None` are not overridden. It is applied to the block of a context with
+/// `context.modify_block(|block| overrides.apply(block))`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase", default)
+)]
+pub struct BlockOverride {
+    /// Block number.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "quantity", skip_serializing_if = "Option::is_none")
+    )]
+    pub number: Option<u64>,
+    /// Difficulty of the block.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub difficulty: Option<U256>,
+    /// Timestamp of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "time",
+            with = "quantity",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub timestamp: Option<u64>,
+    /// Gas limit of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "quantity", skip_serializing_if = "Option::is_none")
+    )]
+    pub gas_limit: Option<u64>,
+    /// Beneficiary of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "feeRecipient", skip_serializing_if = "Option::is_none")
+    )]
+    pub beneficiary: Option<Address>,
+    /// Randomness of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "prevRandao", skip_serializing_if = "Option::is_none")
+    )]
+    pub prevrandao: Option<B256>,
+    /// Base fee per gas of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "baseFeePerGas",
+            with = "quantity",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub basefee: Option<u64>,
+    /// Blob gas price of the block.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "quantity", skip_serializing_if = "Option::is_none")
+    )]
+    pub blob_base_fee: Option<u128>,
+    /// Hashes of the previous blocks by their number.
+    ///
+    /// They are not part of [`BlockEnv`] and are served by the database.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "blockHash", skip_serializing_if = "Option::is_none")
+    )]
+    pub block_hashes: Option<HashMap<u64, B256>>,
+}
+
+impl BlockOverride {
+    /// Sets the block number.
+    pub fn with_number(mut self, number: u64) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    /// Sets the difficulty.
+    pub fn with_difficulty(mut self, difficulty: U256) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// Sets the timestamp.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the gas limit.
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Sets the beneficiary.
+    pub fn with_beneficiary(mut self, beneficiary: Address) -> Self {
+        self.beneficiary = Some(beneficiary);
+        self
+    }
+
+    /// Sets the randomness.
+    pub fn with_prevrandao(mut self, prevrandao: B256) -> Self {
+        self.prevrandao = Some(prevrandao);
+        self
+    }
+
+    /// Sets the base fee.
+    pub fn with_basefee(mut self, basefee: u64) -> Self {
+        self.basefee = Some(basefee);
+        self
+    }
+
+    /// Sets the blob gas price.
+    pub fn with_blob_base_fee(mut self, blob_base_fee: u128) -> Self {
+        self.blob_base_fee = Some(blob_base_fee);
+        self
+    }
+
+    /// Sets the hashes of the previous blocks.
+    pub fn with_block_hashes(mut self, block_hashes: HashMap<u64, B256>) -> Self {
+        self.block_hashes = Some(block_hashes);
+        self
+    }
+
+    /// Applies the overrides to the block.
+    ///
+    /// The blob gas price replaces the one calculated from the excess blob gas, which is kept.
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(difficulty) = self.difficulty {
+            block.difficulty = difficulty;
+        }
+        if let Some(timestamp) = self.timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(beneficiary) = self.beneficiary {
+            block.beneficiary = beneficiary;
+        }
+        if let Some(prevrandao) = self.prevrandao {
+            block.prevrandao = Some(prevrandao);
+        }
+        if let Some(basefee) = self.basefee {
+            block.basefee = basefee;
+        }
+        if let Some(blob_gasprice) = self.blob_base_fee {
+            let excess_blob_gas = block
+                .blob_excess_gas_and_price
+                .map(|blob| blob.excess_blob_gas)
+                .unwrap_or_default();
+            block.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                excess_blob_gas,
+                blob_gasprice,
+            });
+        }
+    }
+}
+
+/// (De)serializes optional integers as hex quantities, as in the JSON-RPC API.
+#[cfg(feature = "serde")]
+mod quantity {
+    use primitives::U128;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u128>,
+        S: Serializer,
+    {
+        value
+            .map(|value| U128::from(value.into()))
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: TryFrom<u128>,
+        D: Deserializer<'de>,
+    {
+        Option::<U128>::deserialize(deserializer)?
+            .map(|value| {
+                T::try_from(value.to::<u128>()).map_err(|_| D::Error::custom("quantity overflows"))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_block_override() {
+        let mut block = BlockEnv::default();
+        block.set_blob_excess_gas_and_price(10, false);
+        let overrides = BlockOverride::default()
+            .with_number(7)
+            .with_timestamp(100)
+            .with_beneficiary(Address::with_last_byte(1))
+            .with_blob_base_fee(5);
+        overrides.apply(&mut block);
+
+        assert_eq!(
+            block,
+            BlockEnv {
+                number: 7,
+                timestamp: 100,
+                beneficiary: Address::with_last_byte(1),
+                blob_excess_gas_and_price: Some(BlobExcessGasAndPrice {
+                    excess_blob_gas: 10,
+                    blob_gasprice: 5,
+                }),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn block_override_serde() {
+        let json = r#"{"number":"0x7","time":"0x64","gasLimit":"0x1c9c380","baseFeePerGas":"0x3b9aca00","blobBaseFee":"0x1"}"#;
+        let overrides: BlockOverride = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            overrides,
+            BlockOverride::default()
+                .with_number(7)
+                .with_timestamp(100)
+                .with_gas_limit(30_000_000)
+                .with_basefee(1_000_000_000)
+                .with_blob_base_fee(1)
+        );
+        assert_eq!(serde_json::to_string(&overrides).unwrap(), json);
+        assert!(
+            serde_json::from_str::<BlockOverride>(r#"{"number":"0x10000000000000000"}"#).is_err()
+        );
+    }
+}
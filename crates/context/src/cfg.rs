@@ -24,6 +24,15 @@ pub struct CfgEnv<SPEC = SpecId> {
     pub limit_contract_code_size: Option<usize>,
     /// Skips the nonce validation against the account's nonce
     pub disable_nonce_check: bool,
+    /// Emits a synthetic [ERC-7528](https://eips.ethereum.org/EIPS/eip-7528) `Transfer` log
+    /// for every non-zero value transfer, including selfdestruct balance moves.
+    ///
+    /// The logs are part of the ordinary log stream, at the position of the transfer. They are
+    /// added by the journal and not by a `LOG` instruction, so inspectors do not see them in
+    /// their `log` hook.
+    ///
+    /// By default, it is set to `false`.
+    pub transfer_logs: bool,
     /// Blob max count. EIP-7840 Add blob schedule to EL config files.
     ///
     /// If this config is not set, the check for max blobs will be skipped.
@@ -82,6 +91,7 @@ impl<SPEC> CfgEnv<SPEC> {
             limit_contract_code_size: None,
             spec,
             disable_nonce_check: false,
+            transfer_logs: false,
             blob_max_count: None, //vec![(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)],
             #[cfg(feature = "memory_limit")]
             memory_limit: (1 << 32) - 1,
@@ -109,6 +119,7 @@ impl<SPEC> CfgEnv<SPEC> {
             limit_contract_code_size: self.limit_contract_code_size,
             spec,
             disable_nonce_check: self.disable_nonce_check,
+            transfer_logs: self.transfer_logs,
             blob_max_count: self.blob_max_count,
             #[cfg(feature = "memory_limit")]
             memory_limit: self.memory_limit,
@@ -123,6 +134,12 @@ impl<SPEC> CfgEnv<SPEC> {
         }
     }
 
+    /// Consumes `self` and returns a new `CfgEnv` with the transfer logs enabled or disabled.
+    pub fn with_transfer_logs(mut self, transfer_logs: bool) -> Self {
+        self.transfer_logs = transfer_logs;
+        self
+    }
+
     /// Sets the blob target
     pub fn with_blob_max_count(mut self, blob_max_count: u64) -> Self {
         self.set_blob_max_count(blob_max_count);
@@ -195,6 +212,10 @@ impl<SPEC: Into<SpecId> + Copy> Cfg for CfgEnv<SPEC> {
         self.disable_nonce_check
     }
 
+    fn is_transfer_log_enabled(&self) -> bool {
+        self.transfer_logs
+    }
+
     fn is_base_fee_check_disabled(&self) -> bool {
         cfg_if::cfg_if! {
             if #[cfg(feature = "optional_no_base_fee")] {
@@ -16,7 +16,7 @@ use context_interface::{
 use core::ops::{Deref, DerefMut};
 use database_interface::Database;
 use primitives::{hardfork::SpecId, Address, HashSet, Log, StorageKey, StorageValue, B256, U256};
-use state::{Account, EvmState};
+use state::{Account, BlockAccessList, EvmState};
 use std::vec::Vec;
 
 /// A journal of state changes internal to the EVM
@@ -154,6 +154,27 @@ impl<DB: Database, ENTRY: JournalEntryTr> JournalTr for Journal<DB, ENTRY> {
         self.inner.warm_preloaded_addresses.insert(address);
     }
 
+    fn set_transfer_logs(&mut self, enabled: bool) {
+        self.inner.transfer_logs = enabled;
+    }
+
+    fn set_block_access_list(&mut self, block_access_list: Option<BlockAccessList>) {
+        self.inner.block_access_list = block_access_list;
+    }
+
+    fn take_block_access_list(&mut self) -> Option<BlockAccessList> {
+        self.inner.block_access_list.take()
+    }
+
+    fn set_block_access_index(&mut self, index: u64) {
+        self.inner.block_access_index = index;
+    }
+
+    fn record_balance_change(&mut self, address: Address, original: U256, balance: U256) {
+        self.inner
+            .record_block_access_balance(address, original, balance);
+    }
+
     fn warm_precompiles(&mut self, address: HashSet<Address>) {
         self.inner.precompiles = address;
         self.inner.warm_preloaded_addresses = self.inner.precompiles.clone();
@@ -273,6 +294,7 @@ impl<DB: Database, ENTRY: JournalEntryTr> JournalTr for Journal<DB, ENTRY> {
 
     #[inline]
     fn finalize(&mut self) -> Self::FinalOutput {
+        self.inner.record_block_access_list();
         self.inner.clear_and_take_output()
     }
 }
@@ -3,7 +3,7 @@ use super::{JournalEntryTr, JournalOutput};
 use bytecode::Bytecode;
 use context_interface::{
     context::{SStoreResult, SelfDestructResult, StateLoad},
-    journaled_state::{AccountLoad, JournalCheckpoint, TransferError},
+    journaled_state::{transfer_log, AccountLoad, JournalCheckpoint, TransferError},
 };
 use core::mem;
 use database_interface::Database;
@@ -12,7 +12,7 @@ use primitives::{
     hash_map::Entry,
     Address, HashMap, HashSet, Log, StorageKey, StorageValue, B256, KECCAK_EMPTY, U256,
 };
-use state::{Account, EvmState, EvmStorageSlot, TransientStorage};
+use state::{Account, AccountInfo, BlockAccessList, EvmState, EvmStorageSlot, TransientStorage};
 use std::vec::Vec;
 
 /// Inner journal state that contains journal and state changes.
@@ -55,6 +55,22 @@ pub struct JournalInner<ENTRY> {
     pub warm_preloaded_addresses: HashSet<Address>,
     /// Precompile addresses
     pub precompiles: HashSet<Address>,
+    /// Whether non-zero value transfers emit synthetic
+    /// [ERC-7528](https://eips.ethereum.org/EIPS/eip-7528) logs.
+    ///
+    /// The logs are emitted by [`JournalInner::transfer`], [`JournalInner::create_account_checkpoint`]
+    /// and [`JournalInner::selfdestruct`] and are reverted together with the other logs of the call.
+    pub transfer_logs: bool,
+    /// [EIP-7928](https://eips.ethereum.org/EIPS/eip-7928) block access list built across the
+    /// transactions of the block, nothing is recorded if it is not set.
+    ///
+    /// Accesses and changes of a transaction are recorded when the journal is finalized, see
+    /// [`JournalInner::record_block_access_list`].
+    pub block_access_list: Option<BlockAccessList>,
+    /// Block access index of the current transaction, it is not changed by the journal.
+    pub block_access_index: u64,
+    /// Account infos before the transaction, only tracked when the block access list is set.
+    pub original_infos: HashMap<Address, AccountInfo>,
 }
 
 impl<ENTRY: JournalEntryTr> Default for JournalInner<ENTRY> {
@@ -78,6 +94,10 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
             spec: SpecId::default(),
             warm_preloaded_addresses: HashSet::default(),
             precompiles: HashSet::default(),
+            transfer_logs: false,
+            block_access_list: None,
+            block_access_index: 0,
+            original_infos: HashMap::default(),
         }
     }
 
@@ -99,9 +119,18 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
             spec,
             warm_preloaded_addresses,
             precompiles,
+            transfer_logs,
+            block_access_list,
+            block_access_index,
+            original_infos,
         } = self;
-        // Spec is not changed. It is always set again execution.
+        // Spec and transfer logs are not changed. They are always set again execution.
         let _ = spec;
+        let _ = transfer_logs;
+        // Block access list is built across transactions.
+        let _ = block_access_list;
+        let _ = block_access_index;
+        original_infos.clear();
         // Load precompiles into warm_preloaded_addresses.
         warm_preloaded_addresses.clone_from(precompiles);
 
@@ -114,6 +143,35 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
         JournalOutput { state, logs }
     }
 
+    /// Records the accesses and changes of the current transaction into the block access list
+    /// at the block access index, does nothing if the block access list is not set.
+    ///
+    /// Accounts and storage slots accessed in reverted calls stay in the state, so they are
+    /// recorded as accesses.
+    pub fn record_block_access_list(&mut self) {
+        let Some(block_access_list) = &mut self.block_access_list else {
+            return;
+        };
+        let not_existing = AccountInfo::default();
+        for (address, account) in &self.state {
+            let original = self.original_infos.get(address).unwrap_or(&not_existing);
+            block_access_list.record_account(self.block_access_index, *address, original, account);
+        }
+    }
+
+    /// Records a balance change made outside of the journal into the block access list at the
+    /// block access index, only the access is recorded if the balance is unchanged.
+    pub fn record_block_access_balance(&mut self, address: Address, original: U256, balance: U256) {
+        let Some(block_access_list) = &mut self.block_access_list else {
+            return;
+        };
+        if balance == original {
+            block_access_list.account_mut(address);
+        } else {
+            block_access_list.record_balance_change(self.block_access_index, address, balance);
+        }
+    }
+
     /// Return reference to state.
     #[inline]
     pub fn state(&mut self) -> &mut EvmState {
@@ -252,6 +310,9 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
 
         self.journal
             .push(ENTRY::balance_transfer(from, to, balance));
+        if self.transfer_logs {
+            self.logs.push(transfer_log(from, to, balance));
+        }
 
         Ok(None)
     }
@@ -331,6 +392,10 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
 
         // add journal entry of transferred balance
         last_journal.push(ENTRY::balance_transfer(caller, target_address, balance));
+        if self.transfer_logs && !balance.is_zero() {
+            self.logs
+                .push(transfer_log(caller, target_address, balance));
+        }
 
         Ok(checkpoint)
     }
@@ -432,6 +497,9 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
         if let Some(entry) = journal_entry {
             self.journal.push(entry);
         };
+        if self.transfer_logs && address != target && !balance.is_zero() {
+            self.logs.push(transfer_log(address, target, balance));
+        }
 
         Ok(StateLoad {
             data: SelfDestructResult {
@@ -551,6 +619,9 @@ impl<ENTRY: JournalEntryTr> JournalInner<ENTRY> {
                 } else {
                     Account::new_not_existing()
                 };
+                if self.block_access_list.is_some() {
+                    self.original_infos.insert(address, account.info.clone());
+                }
 
                 // Precompiles among some other account are warm loaded so we need to take that into account
                 let is_cold = !self.warm_preloaded_addresses.contains(&address);
@@ -738,3 +809,104 @@ pub fn sload_with_account<DB: Database, ENTRY: JournalEntryTr>(
 
     Ok(StateLoad::new(value, is_cold))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalEntry;
+    use context_interface::journaled_state::TRANSFER_LOG_ADDRESS;
+    use database::{CacheDB, EmptyDB};
+    use primitives::address;
+    use state::AccountInfo;
+
+    const ALICE: Address = address!("0x1000000000000000000000000000000000000001");
+    const BOB: Address = address!("0x1000000000000000000000000000000000000002");
+    const CAROL: Address = address!("0x1000000000000000000000000000000000000003");
+
+    #[test]
+    fn transfer_logs() {
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(
+            ALICE,
+            AccountInfo {
+                balance: U256::from(100),
+                ..Default::default()
+            },
+        );
+        let mut journal = JournalInner::<JournalEntry>::new();
+        journal.set_spec_id(SpecId::PRAGUE);
+        journal.transfer_logs = true;
+
+        // Zero value transfers do not emit a log.
+        journal.transfer(&mut db, ALICE, BOB, U256::ZERO).unwrap();
+        assert!(journal.logs.is_empty());
+
+        journal
+            .transfer(&mut db, ALICE, BOB, U256::from(10))
+            .unwrap();
+        assert_eq!(journal.logs, vec![transfer_log(ALICE, BOB, U256::from(10))]);
+        assert_eq!(journal.logs[0].address, TRANSFER_LOG_ADDRESS);
+
+        // Logs of the reverted frame are discarded together with the transfer.
+        let checkpoint = journal.checkpoint();
+        journal
+            .transfer(&mut db, BOB, CAROL, U256::from(5))
+            .unwrap();
+        assert_eq!(journal.logs.len(), 2);
+        journal.checkpoint_revert(checkpoint);
+        assert_eq!(journal.logs.len(), 1);
+
+        // Selfdestruct moves the whole balance to the target.
+        journal.selfdestruct(&mut db, BOB, CAROL).unwrap();
+        assert_eq!(journal.logs[1], transfer_log(BOB, CAROL, U256::from(10)));
+
+        // Disabled by default.
+        journal.transfer_logs = false;
+        journal
+            .transfer(&mut db, ALICE, BOB, U256::from(1))
+            .unwrap();
+        assert_eq!(journal.logs.len(), 2);
+    }
+
+    #[test]
+    fn block_access_list() {
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(ALICE, AccountInfo::from_balance(U256::from(100)));
+        let mut journal = JournalInner::<JournalEntry>::new();
+        journal.set_spec_id(SpecId::PRAGUE);
+        journal.block_access_list = Some(BlockAccessList::new());
+
+        journal.block_access_index = 1;
+        journal
+            .transfer(&mut db, ALICE, BOB, U256::from(10))
+            .unwrap();
+        journal.load_account(&mut db, CAROL).unwrap();
+        journal.sload(&mut db, CAROL, U256::from(1)).unwrap();
+        journal.record_block_access_list();
+        let _ = journal.clear_and_take_output();
+
+        // Changes of the reverted call are not recorded, the accesses are.
+        journal.block_access_index = 2;
+        journal
+            .transfer(&mut db, ALICE, BOB, U256::from(10))
+            .unwrap();
+        let checkpoint = journal.checkpoint();
+        journal
+            .transfer(&mut db, ALICE, CAROL, U256::from(5))
+            .unwrap();
+        journal.checkpoint_revert(checkpoint);
+        journal.record_block_access_list();
+
+        let list = journal.block_access_list.unwrap();
+        assert_eq!(
+            list.accounts[&ALICE].balance_changes,
+            [(1, U256::from(90)), (2, U256::from(90))]
+        );
+        assert_eq!(
+            list.accounts[&BOB].balance_changes,
+            [(1, U256::from(10)), (2, U256::from(10))]
+        );
+        assert!(list.accounts[&CAROL].balance_changes.is_empty());
+        assert!(list.accounts[&CAROL].storage_reads.contains(&U256::from(1)));
+    }
+}
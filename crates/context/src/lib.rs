@@ -13,12 +13,14 @@ pub mod context;
 pub mod evm;
 pub mod journal;
 pub mod local;
+pub mod snapshot;
 pub mod tx;
 
-pub use block::BlockEnv;
+pub use block::{BlockEnv, BlockOverride};
 pub use cfg::{Cfg, CfgEnv};
 pub use context::*;
 pub use evm::Evm;
 pub use journal::*;
 pub use local::LocalContext;
+pub use snapshot::ContextSnapshots;
 pub use tx::TxEnv;
@@ -0,0 +1,176 @@
+//! Snapshots of the [`Context`] that persist across transactions.
+use crate::Context;
+use context_interface::{JournalTr, LocalContextTr};
+use database_interface::{Database, DatabaseSnapshot, SnapshotId};
+use std::vec::Vec;
+
+/// Environment of the context captured by a snapshot.
+#[derive(Clone, Debug)]
+struct ContextSnapshot<BLOCK, TX, CFG> {
+    block: BLOCK,
+    tx: TX,
+    cfg: CFG,
+    /// Id of the database snapshot.
+    db: SnapshotId,
+}
+
+/// Stack of [`Context`] snapshots with `evm_snapshot`/`evm_revert` semantics.
+///
+/// A snapshot captures the block, transaction and configuration of the context together with a
+/// [`DatabaseSnapshot`] of its database, so balances, nonces, code and storage committed after
+/// the snapshot are restored on revert.
+///
+/// Snapshots are meant to be taken and reverted between transactions, within a transaction the
+/// journal checkpoints are used. The journal is cleared on revert.
+///
+/// What is restored in the database is up to its [`DatabaseSnapshot`] implementation, for
+/// `State` block hashes and loaded contracts are not part of the snapshot.
+#[derive(Clone, Debug)]
+pub struct ContextSnapshots<BLOCK, TX, CFG> {
+    snapshots: Vec<ContextSnapshot<BLOCK, TX, CFG>>,
+}
+
+impl<BLOCK, TX, CFG> Default for ContextSnapshots<BLOCK, TX, CFG> {
+    fn default() -> Self {
+        Self {
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+impl<BLOCK: Clone, TX: Clone, CFG: Clone> ContextSnapshots<BLOCK, TX, CFG> {
+    /// Creates an empty snapshot stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of the active snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if there are no active snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Captures the context and its database, returns the id of the snapshot.
+    pub fn snapshot<DB, JOURNAL, CHAIN, LOCAL>(
+        &mut self,
+        context: &mut Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN, LOCAL>,
+    ) -> SnapshotId
+    where
+        DB: Database + DatabaseSnapshot,
+        JOURNAL: JournalTr<Database = DB>,
+        LOCAL: LocalContextTr,
+    {
+        let db = context.journaled_state.db().snapshot();
+        self.snapshots.push(ContextSnapshot {
+            block: context.block.clone(),
+            tx: context.tx.clone(),
+            cfg: context.cfg.clone(),
+            db,
+        });
+        self.snapshots.len() - 1
+    }
+
+    /// Restores the context and its database captured by the snapshot.
+    ///
+    /// The snapshot and all snapshots taken after it are discarded.
+    ///
+    /// Returns `false` if the snapshot or its database snapshot does not exist, in which case
+    /// neither the context nor the snapshots are changed.
+    pub fn revert_to<DB, JOURNAL, CHAIN, LOCAL>(
+        &mut self,
+        context: &mut Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN, LOCAL>,
+        id: SnapshotId,
+    ) -> bool
+    where
+        DB: Database + DatabaseSnapshot,
+        JOURNAL: JournalTr<Database = DB>,
+        LOCAL: LocalContextTr,
+    {
+        let Some(db) = self.snapshots.get(id).map(|snapshot| snapshot.db) else {
+            return false;
+        };
+        if !context.journaled_state.db().revert_to(db) {
+            return false;
+        }
+        let snapshot = self.snapshots.split_off(id).swap_remove(0);
+        context.journaled_state.clear();
+        context.block = snapshot.block;
+        context.tx = snapshot.tx;
+        context.cfg = snapshot.cfg;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockEnv, CfgEnv, Journal, TxEnv};
+    use database::InMemoryDB;
+    use primitives::{hardfork::SpecId, Address};
+    use state::AccountInfo;
+
+    #[test]
+    fn snapshot_revert() {
+        let address = Address::with_last_byte(1);
+        let mut context = Context::<BlockEnv, TxEnv, CfgEnv, InMemoryDB, Journal<InMemoryDB>>::new(
+            InMemoryDB::default(),
+            SpecId::PRAGUE,
+        );
+        let mut snapshots = ContextSnapshots::new();
+
+        let first = snapshots.snapshot(&mut context);
+        context.block.number = 1;
+        context.tx.nonce = 1;
+        context.journaled_state.db().insert_account_info(
+            address,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        let second = snapshots.snapshot(&mut context);
+        context.block.number = 2;
+        assert_eq!((first, second, snapshots.len()), (0, 1, 2));
+
+        assert!(snapshots.revert_to(&mut context, second));
+        assert_eq!(context.block.number, 1);
+        assert_eq!(
+            context
+                .journaled_state
+                .db()
+                .basic(address)
+                .unwrap()
+                .unwrap()
+                .nonce,
+            1
+        );
+
+        assert!(snapshots.revert_to(&mut context, first));
+        assert_eq!((context.block.number, context.tx.nonce), (0, 0));
+        assert_eq!(context.journaled_state.db().basic(address).unwrap(), None);
+        assert!(snapshots.is_empty());
+        assert!(!snapshots.revert_to(&mut context, first));
+    }
+
+    #[test]
+    fn failed_revert_keeps_context() {
+        let mut context = Context::<BlockEnv, TxEnv, CfgEnv, InMemoryDB, Journal<InMemoryDB>>::new(
+            InMemoryDB::default(),
+            SpecId::PRAGUE,
+        );
+        let mut snapshots = ContextSnapshots::new();
+
+        let id = snapshots.snapshot(&mut context);
+        context.block.number = 1;
+        // The database snapshot is gone, so the context can not be restored.
+        assert!(context.journaled_state.db().release_snapshot(0));
+
+        assert!(!snapshots.revert_to(&mut context, id));
+        assert_eq!(context.block.number, 1);
+        assert_eq!(snapshots.len(), 1);
+    }
+}
@@ -0,0 +1,56 @@
+//! Database that aggregates the changes of a whole block.
+use crate::{Database, DatabaseCommit};
+use primitives::{Address, B256};
+
+/// Database that aggregates transaction changes into a block level bundle.
+///
+/// It is implemented for mutable references, so the EVM can be built both with an owned
+/// database and with a reference to it.
+pub trait BlockStateDb: Database + DatabaseCommit {
+    /// Aggregated changes of the block together with their reverts.
+    type Bundle;
+
+    /// Increments balances of the given accounts outside of transaction execution.
+    ///
+    /// Used for withdrawals and block rewards.
+    fn increment_balances(
+        &mut self,
+        balances: impl IntoIterator<Item = (Address, u128)>,
+    ) -> Result<(), Self::Error>;
+
+    /// Makes the hash of the given block available to `BLOCKHASH`.
+    fn insert_block_hash(&mut self, number: u64, hash: B256);
+
+    /// Merges all pending transitions into the bundle, keeping their reverts.
+    fn merge_transitions(&mut self);
+
+    /// Takes the aggregated bundle.
+    fn take_bundle(&mut self) -> Self::Bundle;
+}
+
+impl<T: BlockStateDb> BlockStateDb for &mut T {
+    type Bundle = T::Bundle;
+
+    #[inline]
+    fn increment_balances(
+        &mut self,
+        balances: impl IntoIterator<Item = (Address, u128)>,
+    ) -> Result<(), Self::Error> {
+        (**self).increment_balances(balances)
+    }
+
+    #[inline]
+    fn insert_block_hash(&mut self, number: u64, hash: B256) {
+        (**self).insert_block_hash(number, hash)
+    }
+
+    #[inline]
+    fn merge_transitions(&mut self) {
+        (**self).merge_transitions()
+    }
+
+    #[inline]
+    fn take_bundle(&mut self) -> Self::Bundle {
+        (**self).take_bundle()
+    }
+}
@@ -15,12 +15,18 @@ use std::string::String;
 
 #[cfg(feature = "asyncdb")]
 pub mod async_db;
+pub mod block_state;
 pub mod empty_db;
+pub mod snapshot;
+pub mod state_override;
 pub mod try_commit;
 
 #[cfg(feature = "asyncdb")]
 pub use async_db::{DatabaseAsync, WrapDatabaseAsync};
+pub use block_state::BlockStateDb;
 pub use empty_db::{EmptyDB, EmptyDBTyped};
+pub use snapshot::{DatabaseSnapshot, SnapshotId};
+pub use state_override::{AccountOverride, OverrideError, StateOverride};
 pub use try_commit::{ArcUpgradeError, TryDatabaseCommit};
 
 /// Database error marker is needed to implement From conversion for Error type.
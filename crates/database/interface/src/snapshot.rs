@@ -0,0 +1,34 @@
+//! Snapshots of the database state that can be restored later.
+use auto_impl::auto_impl;
+
+/// Identifier of a database snapshot.
+///
+/// It is the position of the snapshot in the snapshot stack, so ids of reverted snapshots are
+/// reused by the snapshots that are taken after the revert.
+pub type SnapshotId = usize;
+
+/// Database that can capture its state and restore it later, across transactions.
+///
+/// Snapshots form a stack: reverting to a snapshot discards it and all snapshots taken after it.
+#[auto_impl(&mut, Box)]
+pub trait DatabaseSnapshot {
+    /// Captures the current state and returns the id of the snapshot.
+    fn snapshot(&mut self) -> SnapshotId;
+
+    /// Restores the state captured by the snapshot.
+    ///
+    /// The snapshot and all snapshots taken after it are discarded.
+    ///
+    /// Returns `false` if the snapshot does not exist, in which case the state is not changed.
+    fn revert_to(&mut self, id: SnapshotId) -> bool;
+
+    /// Discards the snapshot and all snapshots taken after it, keeping the current state.
+    ///
+    /// Earlier snapshots still restore the state they captured.
+    ///
+    /// Returns `false` if the snapshot does not exist.
+    fn release_snapshot(&mut self, id: SnapshotId) -> bool;
+
+    /// Returns the number of the active snapshots.
+    fn snapshot_count(&self) -> usize;
+}
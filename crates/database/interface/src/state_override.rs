@@ -0,0 +1,150 @@
+//! State overrides for simulation, as used by `eth_call` and `eth_simulateV1`.
+use core::fmt;
+use primitives::{Address, Bytes, HashMap, B256, U256};
+use state::bytecode::BytecodeDecodeError;
+
+/// Overrides of the accounts by their address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of a single account.
+///
+/// Fields that are `None` are not overridden.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase", default)
+)]
+pub struct AccountOverride {
+    /// Balance of the account.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "quantity", skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub code: Option<Bytes>,
+    /// Storage of the account, slots that are not set are zero.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub state: Option<HashMap<B256, B256>>,
+    /// Storage slots that are changed, other slots keep their values.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub state_diff: Option<HashMap<B256, B256>>,
+    /// Address the precompile at this address is moved to.
+    ///
+    /// It is not applied to the database but by the precompile provider of the EVM.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "movePrecompileToAddress",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub move_precompile_to: Option<Address>,
+}
+
+impl AccountOverride {
+    /// Sets the balance.
+    pub fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Sets the nonce.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the code.
+    pub fn with_code(mut self, code: impl Into<Bytes>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Replaces the whole storage.
+    pub fn with_state(mut self, state: HashMap<B256, B256>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Changes the storage slots.
+    pub fn with_state_diff(mut self, state_diff: HashMap<B256, B256>) -> Self {
+        self.state_diff = Some(state_diff);
+        self
+    }
+
+    /// Moves the precompile at this address to another address.
+    pub fn with_move_precompile_to(mut self, address: Address) -> Self {
+        self.move_precompile_to = Some(address);
+        self
+    }
+}
+
+/// Invalid [`StateOverride`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverrideError {
+    /// Both `state` and `state_diff` are set for the account.
+    StateAndStateDiff(Address),
+    /// Code of the account can not be decoded.
+    InvalidCode(Address, BytecodeDecodeError),
+    /// Account that is moved with `move_precompile_to` is not a precompile.
+    NotPrecompile(Address),
+    /// Precompile is moved to an address that is a precompile.
+    MoveToPrecompile(Address),
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StateAndStateDiff(address) => {
+                write!(
+                    f,
+                    "account {address} has both state and state diff overrides"
+                )
+            }
+            Self::InvalidCode(address, error) => {
+                write!(f, "invalid code override of account {address}: {error:?}")
+            }
+            Self::NotPrecompile(address) => write!(f, "account {address} is not a precompile"),
+            Self::MoveToPrecompile(address) => {
+                write!(f, "precompile can not be moved to precompile {address}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for OverrideError {}
+
+/// (De)serializes optional integers as hex quantities, as in the JSON-RPC API.
+#[cfg(feature = "serde")]
+mod quantity {
+    use primitives::U128;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u128>,
+        S: Serializer,
+    {
+        value
+            .map(|value| U128::from(value.into()))
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: TryFrom<u128>,
+        D: Deserializer<'de>,
+    {
+        Option::<U128>::deserialize(deserializer)?
+            .map(|value| {
+                T::try_from(value.to::<u128>()).map_err(|_| D::Error::custom("quantity overflows"))
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,508 @@
+//! Persistent on-disk database backed by the embedded [LMDB](http://www.lmdb.tech) store.
+use crate::states::{PlainStateReverts, StateChangeset};
+use bytecode::BytecodeDecodeError;
+use core::{error::Error, fmt, ops::Bound};
+use database_interface::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use heed::{types::Bytes, Database as Table, Env, EnvOpenOptions, RwTxn};
+use primitives::{Address, HashMap, StorageKey, StorageValue, B256, KECCAK_EMPTY, U256};
+use state::{Account, AccountInfo, Bytecode};
+use std::{fs, path::Path};
+
+/// Default maximum size of the database, 1 TiB.
+///
+/// It only reserves the address space, the file grows with the data.
+pub const DEFAULT_MAP_SIZE: usize = 1 << 40;
+
+/// Length of the encoded [`AccountInfo`]: balance, nonce and code hash.
+const ACCOUNT_LEN: usize = 32 + 8 + 32;
+
+/// Error of the [`DiskDB`].
+#[derive(Debug)]
+pub enum DiskDBError {
+    /// Error of the underlying store.
+    Store(heed::Error),
+    /// Stored bytecode can not be decoded.
+    Bytecode(BytecodeDecodeError),
+    /// Stored value has an invalid length.
+    InvalidLength {
+        /// Name of the table.
+        table: &'static str,
+        /// Length of the value.
+        len: usize,
+    },
+}
+
+impl DBErrorMarker for DiskDBError {}
+
+impl fmt::Display for DiskDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(error) => write!(f, "disk database error: {error}"),
+            Self::Bytecode(error) => write!(f, "invalid stored bytecode: {error:?}"),
+            Self::InvalidLength { table, len } => {
+                write!(f, "invalid value length {len} in table {table}")
+            }
+        }
+    }
+}
+
+impl Error for DiskDBError {}
+
+impl From<heed::Error> for DiskDBError {
+    fn from(error: heed::Error) -> Self {
+        Self::Store(error)
+    }
+}
+
+/// Persistent [`Database`] stored on disk.
+///
+/// State is kept in four tables:
+/// * `accounts`: address to balance, nonce and code hash,
+/// * `storage`: address and slot to the non-zero slot value,
+/// * `contracts`: code hash to the original bytecode,
+/// * `block_hashes`: block number to block hash.
+///
+/// Every write method runs in a single write transaction, so it is applied atomically.
+#[derive(Debug, Clone)]
+pub struct DiskDB {
+    env: Env,
+    accounts: Table<Bytes, Bytes>,
+    storage: Table<Bytes, Bytes>,
+    contracts: Table<Bytes, Bytes>,
+    block_hashes: Table<Bytes, Bytes>,
+}
+
+impl DiskDB {
+    /// Opens the database in the directory, creating it if it does not exist.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`DiskDB::open_with_map_size`].
+    pub unsafe fn open(path: impl AsRef<Path>) -> Result<Self, DiskDBError> {
+        Self::open_with_map_size(path, DEFAULT_MAP_SIZE)
+    }
+
+    /// Opens the database with the given maximum size in bytes.
+    ///
+    /// # Safety
+    ///
+    /// The store is memory mapped, see [`EnvOpenOptions::open`]. The directory must not be
+    /// opened again in this process while the database is open, and the files must not be
+    /// modified by other processes or programs other than LMDB while it is open.
+    pub unsafe fn open_with_map_size(
+        path: impl AsRef<Path>,
+        map_size: usize,
+    ) -> Result<Self, DiskDBError> {
+        let path = path.as_ref();
+        fs::create_dir_all(path).map_err(|error| DiskDBError::Store(heed::Error::Io(error)))?;
+        // SAFETY: The caller guarantees that the store is opened once in the process and not
+        // modified by other programs while it is open.
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(map_size)
+                .max_dbs(4)
+                .open(path)?
+        };
+        let mut txn = env.write_txn()?;
+        let accounts = env.create_database(&mut txn, Some("accounts"))?;
+        let storage = env.create_database(&mut txn, Some("storage"))?;
+        let contracts = env.create_database(&mut txn, Some("contracts"))?;
+        let block_hashes = env.create_database(&mut txn, Some("block_hashes"))?;
+        txn.commit()?;
+        Ok(Self {
+            env,
+            accounts,
+            storage,
+            contracts,
+            block_hashes,
+        })
+    }
+
+    /// Inserts the account info and its bytecode.
+    pub fn insert_account_info(
+        &self,
+        address: Address,
+        info: &AccountInfo,
+    ) -> Result<(), DiskDBError> {
+        self.write(|txn| {
+            self.put_account(txn, address, Some(info))?;
+            if let Some(code) = &info.code {
+                self.put_contract(txn, info.code_hash, code)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Inserts the account storage slot.
+    pub fn insert_account_storage(
+        &self,
+        address: Address,
+        slot: StorageKey,
+        value: StorageValue,
+    ) -> Result<(), DiskDBError> {
+        self.write(|txn| self.put_storage(txn, address, slot, value))
+    }
+
+    /// Inserts the hash of the block.
+    pub fn insert_block_hash(&self, number: u64, hash: B256) -> Result<(), DiskDBError> {
+        self.write(|txn| {
+            self.block_hashes
+                .put(txn, &number.to_be_bytes(), hash.as_slice())?;
+            Ok(())
+        })
+    }
+
+    /// Commits the changes of the EVM state, see [`DatabaseCommit::commit`].
+    pub fn commit_changes(&self, changes: HashMap<Address, Account>) -> Result<(), DiskDBError> {
+        self.write(|txn| {
+            for (address, account) in changes {
+                if !account.is_touched() {
+                    continue;
+                }
+                if account.is_selfdestructed() {
+                    self.put_account(txn, address, None)?;
+                    self.wipe_storage(txn, address)?;
+                    continue;
+                }
+                if account.is_created() {
+                    self.wipe_storage(txn, address)?;
+                }
+                self.put_account(txn, address, Some(&account.info))?;
+                if let Some(code) = &account.info.code {
+                    self.put_contract(txn, account.info.code_hash, code)?;
+                }
+                for (slot, value) in account.changed_storage_slots() {
+                    self.put_storage(txn, address, *slot, value.present_value())?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Commits the changeset of a [`BundleState`](crate::BundleState), see
+    /// [`BundleState::into_plain_state`](crate::BundleState::into_plain_state).
+    pub fn commit_changeset(&self, changeset: StateChangeset) -> Result<(), DiskDBError> {
+        self.write(|txn| {
+            for (address, info) in changeset.accounts {
+                self.put_account(txn, address, info.as_ref())?;
+            }
+            for storage in changeset.storage {
+                if storage.wipe_storage {
+                    self.wipe_storage(txn, storage.address)?;
+                }
+                for (slot, value) in storage.storage {
+                    self.put_storage(txn, storage.address, slot, value)?;
+                }
+            }
+            for (code_hash, code) in changeset.contracts {
+                self.put_contract(txn, code_hash, &code)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Unwinds the blocks by applying their reverts, from the newest block to the oldest.
+    ///
+    /// Wiped storage is cleared and the slots of the revert are restored.
+    ///
+    /// Reverts are created by [`Reverts::to_plain_state_reverts`](crate::states::reverts::Reverts::to_plain_state_reverts).
+    pub fn apply_reverts(&self, reverts: PlainStateReverts) -> Result<(), DiskDBError> {
+        self.write(|txn| {
+            for (accounts, storage) in reverts.accounts.into_iter().zip(reverts.storage).rev() {
+                for (address, info) in accounts {
+                    self.put_account(txn, address, info.as_ref())?;
+                }
+                for storage in storage {
+                    if storage.wiped {
+                        self.wipe_storage(txn, storage.address)?;
+                    }
+                    for (slot, value) in storage.storage_revert {
+                        self.put_storage(txn, storage.address, slot, value.to_previous_value())?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs the function in a write transaction and commits it if the function succeeds.
+    fn write(
+        &self,
+        f: impl FnOnce(&mut RwTxn<'_>) -> Result<(), DiskDBError>,
+    ) -> Result<(), DiskDBError> {
+        let mut txn = self.env.write_txn()?;
+        f(&mut txn)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn put_account(
+        &self,
+        txn: &mut RwTxn<'_>,
+        address: Address,
+        info: Option<&AccountInfo>,
+    ) -> Result<(), DiskDBError> {
+        match info {
+            Some(info) => self
+                .accounts
+                .put(txn, address.as_slice(), &encode_account(info))?,
+            None => {
+                self.accounts.delete(txn, address.as_slice())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn put_storage(
+        &self,
+        txn: &mut RwTxn<'_>,
+        address: Address,
+        slot: StorageKey,
+        value: StorageValue,
+    ) -> Result<(), DiskDBError> {
+        let key = storage_key(address, slot);
+        if value.is_zero() {
+            self.storage.delete(txn, &key)?;
+        } else {
+            self.storage.put(txn, &key, &value.to_be_bytes::<32>())?;
+        }
+        Ok(())
+    }
+
+    fn put_contract(
+        &self,
+        txn: &mut RwTxn<'_>,
+        code_hash: B256,
+        code: &Bytecode,
+    ) -> Result<(), DiskDBError> {
+        if code_hash != KECCAK_EMPTY && !code.is_empty() {
+            self.contracts
+                .put(txn, code_hash.as_slice(), &code.original_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn wipe_storage(&self, txn: &mut RwTxn<'_>, address: Address) -> Result<(), DiskDBError> {
+        let start = storage_key(address, StorageKey::ZERO);
+        let end = storage_key(address, StorageKey::MAX);
+        let range = (Bound::Included(&start[..]), Bound::Included(&end[..]));
+        self.storage.delete_range(txn, &range)?;
+        Ok(())
+    }
+}
+
+/// Key of the storage slot: address followed by the big endian slot.
+fn storage_key(address: Address, slot: StorageKey) -> [u8; 52] {
+    let mut key = [0; 52];
+    key[..20].copy_from_slice(address.as_slice());
+    key[20..].copy_from_slice(&slot.to_be_bytes::<32>());
+    key
+}
+
+fn encode_account(info: &AccountInfo) -> [u8; ACCOUNT_LEN] {
+    let mut value = [0; ACCOUNT_LEN];
+    value[..32].copy_from_slice(&info.balance.to_be_bytes::<32>());
+    value[32..40].copy_from_slice(&info.nonce.to_be_bytes());
+    value[40..].copy_from_slice(info.code_hash.as_slice());
+    value
+}
+
+fn decode_account(value: &[u8]) -> Result<AccountInfo, DiskDBError> {
+    if value.len() != ACCOUNT_LEN {
+        return Err(DiskDBError::InvalidLength {
+            table: "accounts",
+            len: value.len(),
+        });
+    }
+    Ok(AccountInfo {
+        balance: U256::from_be_slice(&value[..32]),
+        nonce: u64::from_be_bytes(value[32..40].try_into().unwrap()),
+        code_hash: B256::from_slice(&value[40..]),
+        code: None,
+    })
+}
+
+impl DatabaseCommit for DiskDB {
+    /// Commits the changes, see [`DiskDB::commit_changes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write transaction fails.
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.commit_changes(changes)
+            .expect("failed to commit changes to the disk database");
+    }
+}
+
+impl Database for DiskDB {
+    type Error = DiskDBError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseRef for DiskDB {
+    type Error = DiskDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let txn = self.env.read_txn()?;
+        self.accounts
+            .get(&txn, address.as_slice())?
+            .map(decode_account)
+            .transpose()
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let txn = self.env.read_txn()?;
+        match self.contracts.get(&txn, code_hash.as_slice())? {
+            Some(code) => {
+                Bytecode::new_raw_checked(code.to_vec().into()).map_err(DiskDBError::Bytecode)
+            }
+            None => Ok(Bytecode::default()),
+        }
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        let txn = self.env.read_txn()?;
+        match self.storage.get(&txn, &storage_key(address, index))? {
+            Some(value) if value.len() == 32 => Ok(StorageValue::from_be_slice(value)),
+            Some(value) => Err(DiskDBError::InvalidLength {
+                table: "storage",
+                len: value.len(),
+            }),
+            None => Ok(StorageValue::ZERO),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let txn = self.env.read_txn()?;
+        match self.block_hashes.get(&txn, &number.to_be_bytes())? {
+            Some(hash) if hash.len() == 32 => Ok(B256::from_slice(hash)),
+            Some(hash) => Err(DiskDBError::InvalidLength {
+                table: "block_hashes",
+                len: hash.len(),
+            }),
+            None => Ok(B256::ZERO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{PlainStorageChangeset, PlainStorageRevert, RevertToSlot};
+    use std::{env, path::PathBuf, process, vec};
+
+    /// Opens the database in a fresh temporary directory.
+    fn open(name: &str) -> (DiskDB, PathBuf) {
+        let path = env::temp_dir().join(format!("revm-diskdb-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&path);
+        // SAFETY: Every test opens its own directory once.
+        (unsafe { DiskDB::open(&path) }.unwrap(), path)
+    }
+
+    #[test]
+    fn commit_changeset_and_revert() {
+        let (db, path) = open("changeset");
+        let address = Address::with_last_byte(1);
+        let slot = StorageKey::from(1);
+        let code = Bytecode::new_raw([0x60, 0x00].into());
+        let old = AccountInfo {
+            balance: U256::from(1),
+            ..Default::default()
+        };
+        db.insert_account_info(address, &old).unwrap();
+        db.insert_account_storage(address, slot, U256::from(2))
+            .unwrap();
+
+        let info = AccountInfo {
+            balance: U256::from(3),
+            nonce: 1,
+            code_hash: code.hash_slow(),
+            code: Some(code.clone()),
+        };
+        db.commit_changeset(StateChangeset {
+            accounts: vec![(address, Some(info.clone()))],
+            storage: vec![PlainStorageChangeset {
+                address,
+                wipe_storage: false,
+                storage: vec![(slot, U256::ZERO)],
+            }],
+            contracts: vec![(info.code_hash, code.clone())],
+        })
+        .unwrap();
+        assert_eq!(
+            db.basic_ref(address).unwrap(),
+            Some(AccountInfo { code: None, ..info })
+        );
+        assert_eq!(db.storage_ref(address, slot).unwrap(), U256::ZERO);
+        assert_eq!(
+            db.code_by_hash_ref(code.hash_slow())
+                .unwrap()
+                .original_bytes(),
+            code.original_bytes()
+        );
+
+        db.apply_reverts(PlainStateReverts {
+            accounts: vec![vec![(address, Some(old.clone()))]],
+            storage: vec![vec![PlainStorageRevert {
+                address,
+                wiped: false,
+                storage_revert: vec![(slot, RevertToSlot::Some(U256::from(2)))],
+            }]],
+        })
+        .unwrap();
+        assert_eq!(db.basic_ref(address).unwrap(), Some(old));
+        assert_eq!(db.storage_ref(address, slot).unwrap(), U256::from(2));
+
+        drop(db);
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn selfdestruct_wipes_storage() {
+        let (mut db, path) = open("selfdestruct");
+        let address = Address::with_last_byte(1);
+        db.insert_account_info(address, &AccountInfo::default())
+            .unwrap();
+        db.insert_account_storage(address, StorageKey::from(1), U256::from(1))
+            .unwrap();
+        db.insert_block_hash(1, B256::with_last_byte(1)).unwrap();
+
+        let mut account = Account::default();
+        account.mark_touch();
+        account.mark_selfdestruct();
+        db.commit(HashMap::from_iter([(address, account)]));
+
+        assert_eq!(db.basic_ref(address).unwrap(), None);
+        assert_eq!(
+            db.storage_ref(address, StorageKey::from(1)).unwrap(),
+            U256::ZERO
+        );
+        assert_eq!(db.block_hash_ref(1).unwrap(), B256::with_last_byte(1));
+
+        drop(db);
+        let _ = fs::remove_dir_all(path);
+    }
+}
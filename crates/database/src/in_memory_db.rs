@@ -1,5 +1,7 @@
 use core::convert::Infallible;
-use database_interface::{Database, DatabaseCommit, DatabaseRef, EmptyDB};
+use database_interface::{
+    Database, DatabaseCommit, DatabaseRef, DatabaseSnapshot, EmptyDB, SnapshotId,
+};
 use primitives::{
     address, hash_map::Entry, Address, HashMap, Log, StorageKey, StorageValue, B256, KECCAK_EMPTY,
     U256,
@@ -56,6 +58,21 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: This is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// Stack of the active snapshots, see [DatabaseSnapshot].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshots: Vec<CacheSnapshot>,
+}
+
+/// Changes made to the [Cache] after a snapshot was taken.
+///
+/// Accounts are copied the first time they are changed after the snapshot, so taking a snapshot
+/// is cheap and reverting only touches the changed accounts.
+#[derive(Debug, Clone, Default)]
+struct CacheSnapshot {
+    /// Accounts before their first change, `None` if they were not in the cache.
+    accounts: HashMap<Address, Option<DbAccount>>,
+    /// Number of logs when the snapshot was taken.
+    logs: usize,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -82,6 +99,7 @@ impl<ExtDb> CacheDB<CacheDB<ExtDb>> {
                     block_hashes,
                 },
             db: mut inner,
+            ..
         } = self;
 
         inner.cache.accounts.extend(accounts);
@@ -103,6 +121,7 @@ impl<ExtDB> CacheDB<ExtDB> {
         Self {
             cache: Cache::default(),
             db,
+            snapshots: Vec::new(),
         }
     }
 
@@ -131,6 +150,7 @@ impl<ExtDB> CacheDB<ExtDB> {
     /// Inserts account info but not override storage
     pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
         self.insert_contract(&mut info);
+        self.snapshot_account(address);
         self.cache.accounts.entry(address).or_default().info = info;
     }
 
@@ -138,6 +158,16 @@ impl<ExtDB> CacheDB<ExtDB> {
     pub fn nest(self) -> CacheDB<Self> {
         CacheDB::new(self)
     }
+
+    /// Saves the account to the last snapshot if this is its first change after the snapshot.
+    fn snapshot_account(&mut self, address: Address) {
+        if let Some(snapshot) = self.snapshots.last_mut() {
+            snapshot
+                .accounts
+                .entry(address)
+                .or_insert_with(|| self.cache.accounts.get(&address).cloned());
+        }
+    }
 }
 
 impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
@@ -145,6 +175,7 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     ///
     /// If the account was not found in the cache, it will be loaded from the underlying database.
     pub fn load_account(&mut self, address: Address) -> Result<&mut DbAccount, ExtDB::Error> {
+        self.snapshot_account(address);
         let db = &self.db;
         match self.cache.accounts.entry(address) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
@@ -190,6 +221,7 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            self.snapshot_account(address);
             if account.is_selfdestructed() {
                 let db_account = self.cache.accounts.entry(address).or_default();
                 db_account.storage.clear();
@@ -222,6 +254,54 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
     }
 }
 
+/// Changes made through [DatabaseCommit::commit], [CacheDB::insert_account_info] and
+/// [CacheDB::load_account] are reverted. Changes made directly to the [Cache] are not tracked.
+impl<ExtDB> DatabaseSnapshot for CacheDB<ExtDB> {
+    fn snapshot(&mut self) -> SnapshotId {
+        self.snapshots.push(CacheSnapshot {
+            accounts: HashMap::default(),
+            logs: self.cache.logs.len(),
+        });
+        self.snapshots.len() - 1
+    }
+
+    fn revert_to(&mut self, id: SnapshotId) -> bool {
+        if id >= self.snapshots.len() {
+            return false;
+        }
+        for snapshot in self.snapshots.split_off(id).into_iter().rev() {
+            for (address, account) in snapshot.accounts {
+                match account {
+                    Some(account) => self.cache.accounts.insert(address, account),
+                    None => self.cache.accounts.remove(&address),
+                };
+            }
+            self.cache.logs.truncate(snapshot.logs);
+        }
+        true
+    }
+
+    fn release_snapshot(&mut self, id: SnapshotId) -> bool {
+        if id >= self.snapshots.len() {
+            return false;
+        }
+        let released = self.snapshots.split_off(id);
+        if let Some(previous) = self.snapshots.last_mut() {
+            // The oldest copy of the account is the one the previous snapshot needs.
+            for snapshot in released {
+                for (address, account) in snapshot.accounts {
+                    previous.accounts.entry(address).or_insert(account);
+                }
+            }
+        }
+        true
+    }
+
+    fn snapshot_count(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
 impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
@@ -489,7 +569,7 @@ impl Database for BenchmarkDB {
 #[cfg(test)]
 mod tests {
     use super::{CacheDB, EmptyDB};
-    use database_interface::Database;
+    use database_interface::{Database, DatabaseSnapshot};
     use primitives::{Address, HashMap, StorageKey, StorageValue};
     use state::AccountInfo;
 
@@ -545,6 +625,64 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_snapshot_revert() {
+        let account = Address::with_last_byte(42);
+        let other = Address::with_last_byte(43);
+        let (key, value) = (StorageKey::from(123), StorageValue::from(456));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+
+        let first = db.snapshot();
+        db.insert_account_storage(account, key, value).unwrap();
+        let second = db.snapshot();
+        db.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+        db.insert_account_info(other, AccountInfo::default());
+        assert_eq!((first, second, db.snapshot_count()), (0, 1, 2));
+
+        assert!(db.revert_to(second));
+        assert_eq!(db.basic(account).unwrap().unwrap().nonce, 1);
+        assert_eq!(db.storage(account, key), Ok(value));
+        assert!(!db.cache.accounts.contains_key(&other));
+
+        assert!(db.revert_to(first));
+        assert_eq!(db.storage(account, key), Ok(StorageValue::ZERO));
+        assert_eq!(db.snapshot_count(), 0);
+        assert!(!db.revert_to(first));
+    }
+
+    #[test]
+    fn test_snapshot_release() {
+        let account = Address::with_last_byte(42);
+        let info = |nonce| AccountInfo {
+            nonce,
+            ..Default::default()
+        };
+        let mut db = CacheDB::new(EmptyDB::default());
+
+        let first = db.snapshot();
+        let second = db.snapshot();
+        db.insert_account_info(account, info(1));
+        assert!(db.release_snapshot(second));
+        assert_eq!(db.basic(account).unwrap().unwrap().nonce, 1);
+
+        // The change made after the released snapshot is reverted by the first one.
+        assert!(db.revert_to(first));
+        assert_eq!(db.basic(account).unwrap(), None);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
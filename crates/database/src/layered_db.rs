@@ -0,0 +1,438 @@
+//! Persistent in-memory database with copy-on-write layers.
+//!
+//! [`LayeredDB`] keeps the changes made on top of a read-only database in a stack of
+//! [`DbLayer`]s. Frozen layers are shared between forks, so forking and cloning are O(1) and
+//! unchanged accounts and storage are never copied.
+use core::{iter, mem};
+use database_interface::{Database, DatabaseCommit, DatabaseRef};
+use primitives::{hash_map::Entry, Address, HashMap, StorageKey, StorageValue, B256};
+use state::{Account, AccountInfo, Bytecode};
+use std::{sync::Arc, vec::Vec};
+
+/// Changes of the accounts and contracts in one layer of the [`LayeredDB`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DbLayer {
+    /// Changed accounts.
+    pub accounts: HashMap<Address, LayerAccount>,
+    /// Contracts deployed or inserted in this layer, by their code hash.
+    pub contracts: HashMap<B256, Bytecode>,
+}
+
+/// Account changed in a [`DbLayer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerAccount {
+    /// Account info, `None` if the account does not exist.
+    pub info: Option<AccountInfo>,
+    /// If set, the storage of the lower layers is not used, slots that are not in `storage`
+    /// are zero.
+    ///
+    /// It is set for created and selfdestructed accounts.
+    pub storage_cleared: bool,
+    /// Changed storage slots.
+    pub storage: HashMap<StorageKey, StorageValue>,
+}
+
+impl LayerAccount {
+    /// Returns `true` if the account replaces the account of the lower layers.
+    fn is_replacing(&self) -> bool {
+        self.storage_cleared || self.info.is_none()
+    }
+}
+
+impl DbLayer {
+    /// Returns `true` if the layer has no changes.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.contracts.is_empty()
+    }
+
+    /// Applies the changes of a newer layer on top of this layer.
+    pub fn extend(&mut self, newer: DbLayer) {
+        self.contracts.extend(newer.contracts);
+        for (address, account) in newer.accounts {
+            match self.accounts.entry(address) {
+                Entry::Occupied(mut entry) if !account.is_replacing() => {
+                    let entry = entry.get_mut();
+                    entry.info = account.info;
+                    entry.storage.extend(account.storage);
+                }
+                Entry::Occupied(mut entry) => {
+                    entry.insert(account);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(account);
+                }
+            }
+        }
+    }
+}
+
+/// Layer that is no longer changed and is shared between the forks.
+#[derive(Debug)]
+struct FrozenLayer {
+    layer: DbLayer,
+    parent: Option<Arc<FrozenLayer>>,
+}
+
+impl Drop for FrozenLayer {
+    fn drop(&mut self) {
+        // Drops the chain iteratively, as deep chains would overflow the stack.
+        let mut parent = self.parent.take();
+        while let Some(layer) = parent {
+            parent = Arc::into_inner(layer).and_then(|mut layer| layer.parent.take());
+        }
+    }
+}
+
+/// Persistent in-memory database with copy-on-write layers.
+///
+/// Changes are written to the top layer, [`LayeredDB::fork`] freezes it and returns a new
+/// database that shares all layers with this one. Reads walk the layers from the top to the
+/// bottom and fall back to the underlying database, use [`LayeredDB::squash`] to merge the
+/// layers when the stack gets deep.
+///
+/// Reads of the underlying database are not cached.
+#[derive(Debug)]
+pub struct LayeredDB<ExtDB> {
+    /// Layer that receives the changes.
+    layer: Arc<DbLayer>,
+    /// Frozen layers below the top layer.
+    parent: Option<Arc<FrozenLayer>>,
+    /// Number of the frozen layers.
+    depth: usize,
+    /// The underlying read-only database.
+    db: Arc<ExtDB>,
+}
+
+impl<ExtDB> Clone for LayeredDB<ExtDB> {
+    /// Clones the database in O(1).
+    ///
+    /// The top layer is shared and copied on the first write, use [`LayeredDB::fork`] to avoid
+    /// the copy.
+    fn clone(&self) -> Self {
+        Self {
+            layer: self.layer.clone(),
+            parent: self.parent.clone(),
+            depth: self.depth,
+            db: self.db.clone(),
+        }
+    }
+}
+
+impl<ExtDB: Default> Default for LayeredDB<ExtDB> {
+    fn default() -> Self {
+        Self::new(ExtDB::default())
+    }
+}
+
+impl<ExtDB> LayeredDB<ExtDB> {
+    /// Creates a new database on top of the given database.
+    pub fn new(db: ExtDB) -> Self {
+        Self {
+            layer: Arc::default(),
+            parent: None,
+            depth: 0,
+            db: Arc::new(db),
+        }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &ExtDB {
+        &self.db
+    }
+
+    /// Returns the changes made since the last fork.
+    pub fn layer(&self) -> &DbLayer {
+        &self.layer
+    }
+
+    /// Returns the number of the frozen layers.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Freezes the top layer and returns a fork that shares all layers with this database.
+    ///
+    /// Changes of the fork and of this database are written to their own new top layers.
+    pub fn fork(&mut self) -> Self {
+        self.freeze();
+        self.clone()
+    }
+
+    /// Returns all changes on top of the underlying database merged into one layer.
+    pub fn changes(&self) -> DbLayer {
+        let layers: Vec<_> = self.layers().collect();
+        let mut changes = DbLayer::default();
+        for layer in layers.into_iter().rev() {
+            changes.extend(layer.clone());
+        }
+        changes
+    }
+
+    /// Merges all layers into a single top layer.
+    ///
+    /// Other forks are not affected, they keep sharing the old layers.
+    pub fn squash(&mut self) {
+        self.layer = Arc::new(self.changes());
+        self.parent = None;
+        self.depth = 0;
+    }
+
+    /// Applies the changes of a layer on top of the top layer.
+    ///
+    /// It can be used to merge the [`LayeredDB::layer`] of a fork back into its parent.
+    pub fn apply(&mut self, layer: DbLayer) {
+        Arc::make_mut(&mut self.layer).extend(layer);
+    }
+
+    /// Inserts the account info, storage of the account is not changed.
+    pub fn insert_account_info(&mut self, address: Address, info: AccountInfo) {
+        let layer = Arc::make_mut(&mut self.layer);
+        insert_code(layer, &info);
+        layer.accounts.entry(address).or_default().info = Some(info);
+    }
+
+    /// Moves the top layer to the frozen layers.
+    fn freeze(&mut self) {
+        if self.layer.is_empty() {
+            return;
+        }
+        let layer = mem::take(&mut self.layer);
+        self.parent = Some(Arc::new(FrozenLayer {
+            layer: Arc::try_unwrap(layer).unwrap_or_else(|layer| (*layer).clone()),
+            parent: self.parent.take(),
+        }));
+        self.depth += 1;
+    }
+
+    /// Iterates over the layers from the top to the bottom.
+    fn layers(&self) -> impl Iterator<Item = &DbLayer> {
+        let frozen = iter::successors(self.parent.as_deref(), |layer| layer.parent.as_deref());
+        iter::once(&*self.layer).chain(frozen.map(|frozen| &frozen.layer))
+    }
+
+    /// Returns the account from the highest layer that changed it.
+    fn account(&self, address: &Address) -> Option<&LayerAccount> {
+        self.layers().find_map(|layer| layer.accounts.get(address))
+    }
+}
+
+impl<ExtDB: DatabaseRef> LayeredDB<ExtDB> {
+    /// Inserts the account storage slot, the account info is not changed.
+    pub fn insert_account_storage(
+        &mut self,
+        address: Address,
+        slot: StorageKey,
+        value: StorageValue,
+    ) -> Result<(), ExtDB::Error> {
+        if !self.layer.accounts.contains_key(&address) {
+            let info = self.basic_ref(address)?;
+            Arc::make_mut(&mut self.layer).accounts.insert(
+                address,
+                LayerAccount {
+                    info,
+                    ..Default::default()
+                },
+            );
+        }
+        Arc::make_mut(&mut self.layer)
+            .accounts
+            .get_mut(&address)
+            .expect("account is inserted")
+            .storage
+            .insert(slot, value);
+        Ok(())
+    }
+}
+
+/// Inserts the code of the account into the layer contracts.
+fn insert_code(layer: &mut DbLayer, info: &AccountInfo) {
+    if let Some(code) = info.code.as_ref().filter(|code| !code.is_empty()) {
+        layer
+            .contracts
+            .entry(info.code_hash)
+            .or_insert_with(|| code.clone());
+    }
+}
+
+impl<ExtDB> DatabaseCommit for LayeredDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        let layer = Arc::make_mut(&mut self.layer);
+        for (address, account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                layer.accounts.insert(
+                    address,
+                    LayerAccount {
+                        info: None,
+                        storage_cleared: true,
+                        storage: HashMap::default(),
+                    },
+                );
+                continue;
+            }
+            insert_code(layer, &account.info);
+            let is_newly_created = account.is_created();
+            let layer_account = layer.accounts.entry(address).or_default();
+            layer_account.info = Some(account.info);
+            if is_newly_created {
+                layer_account.storage.clear();
+                layer_account.storage_cleared = true;
+            }
+            layer_account.storage.extend(
+                account
+                    .storage
+                    .into_iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(key, slot)| (key, slot.present_value())),
+            );
+        }
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for LayeredDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for LayeredDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.account(&address) {
+            Some(account) => Ok(account.info.clone()),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self
+            .layers()
+            .find_map(|layer| layer.contracts.get(&code_hash))
+        {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        for layer in self.layers() {
+            let Some(account) = layer.accounts.get(&address) else {
+                continue;
+            };
+            if let Some(value) = account.storage.get(&index) {
+                return Ok(*value);
+            }
+            if account.is_replacing() {
+                return Ok(StorageValue::ZERO);
+            }
+        }
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use primitives::U256;
+    use state::{AccountStatus, EvmStorageSlot};
+
+    fn info(nonce: u64) -> AccountInfo {
+        AccountInfo {
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    fn changes(address: Address, account: Account) -> HashMap<Address, Account> {
+        HashMap::from_iter([(address, account)])
+    }
+
+    #[test]
+    fn fork_shares_layers() {
+        let address = Address::with_last_byte(1);
+        let (key, value) = (StorageKey::from(1), StorageValue::from(2));
+        let mut base = InMemoryDB::default();
+        base.insert_account_info(address, info(1));
+        base.insert_account_storage(address, key, value).unwrap();
+
+        let mut db = LayeredDB::new(base);
+        db.insert_account_info(address, info(2));
+        let mut fork = db.fork();
+        fork.insert_account_storage(address, key, StorageValue::from(3))
+            .unwrap();
+        db.insert_account_info(address, info(4));
+
+        assert_eq!((db.depth(), fork.depth()), (1, 1));
+        assert_eq!(db.basic_ref(address).unwrap().unwrap().nonce, 4);
+        assert_eq!(db.storage_ref(address, key), Ok(value));
+        assert_eq!(fork.basic_ref(address).unwrap().unwrap().nonce, 2);
+        assert_eq!(fork.storage_ref(address, key), Ok(StorageValue::from(3)));
+
+        // The changes of the fork are merged back into the parent.
+        db.apply(fork.layer().clone());
+        assert_eq!(db.basic_ref(address).unwrap().unwrap().nonce, 2);
+        assert_eq!(db.storage_ref(address, key), Ok(StorageValue::from(3)));
+    }
+
+    #[test]
+    fn commit_and_squash() {
+        let address = Address::with_last_byte(1);
+        let key = StorageKey::from(1);
+        let mut db = LayeredDB::new(InMemoryDB::default());
+
+        let mut account = Account::from(info(1));
+        account.status = AccountStatus::Touched | AccountStatus::Created;
+        account.storage.insert(
+            key,
+            EvmStorageSlot::new_changed(U256::ZERO, StorageValue::from(5)),
+        );
+        db.commit(changes(address, account));
+        let mut fork = db.fork();
+
+        let mut account = Account::from(info(1));
+        account.status = AccountStatus::Touched | AccountStatus::SelfDestructed;
+        fork.commit(changes(address, account));
+        assert_eq!(fork.basic_ref(address), Ok(None));
+        assert_eq!(fork.storage_ref(address, key), Ok(StorageValue::ZERO));
+        assert_eq!(db.storage_ref(address, key), Ok(StorageValue::from(5)));
+
+        let merged = fork.changes();
+        fork.squash();
+        assert_eq!(fork.depth(), 0);
+        assert_eq!(fork.layer(), &merged);
+        assert_eq!(fork.basic_ref(address), Ok(None));
+        assert_eq!(db.storage_ref(address, key), Ok(StorageValue::from(5)));
+    }
+}
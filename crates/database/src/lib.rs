@@ -6,18 +6,32 @@ extern crate alloc as std;
 
 #[cfg(feature = "alloydb")]
 mod alloydb;
+#[cfg(feature = "diskdb")]
+mod diskdb;
 
 pub use database_interface::*;
 
 pub mod in_memory_db;
+pub mod layered_db;
+pub mod override_db;
+pub mod stateless;
 pub mod states;
+pub mod trie;
+pub mod witness;
 
 #[cfg(feature = "alloydb")]
 pub use alloydb::{AlloyDB, BlockId, DBTransportError};
+#[cfg(feature = "diskdb")]
+pub use diskdb::{DiskDB, DiskDBError, DEFAULT_MAP_SIZE};
 
 pub use in_memory_db::*;
+pub use layered_db::{DbLayer, LayerAccount, LayeredDB};
+pub use override_db::OverrideDB;
+pub use stateless::{StatelessDB, StatelessError};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+pub use trie::{MerklePatriciaTrie, ProofNodes, StateTrie, TrieError, EMPTY_ROOT_HASH};
+pub use witness::{ExecutionWitness, RecordingDB, WitnessDB, WitnessError};
@@ -0,0 +1,358 @@
+//! State overrides for simulation, as used by `eth_call` and `eth_simulateV1`.
+//!
+//! [`OverrideDB`] wraps a database and serves the overridden balance, nonce, code and storage
+//! of the accounts in a [`StateOverride`] without modifying the wrapped database.
+use database_interface::{Database, DatabaseRef, OverrideError, StateOverride};
+use primitives::{Address, HashMap, StorageKey, StorageValue, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+
+/// Overridden storage of an account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum StorageOverride {
+    /// Whole storage is replaced.
+    Replace(HashMap<StorageKey, StorageValue>),
+    /// Only the slots are changed.
+    Diff(HashMap<StorageKey, StorageValue>),
+}
+
+/// Overrides of an account prepared for lookups.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct OverriddenAccount {
+    balance: Option<U256>,
+    nonce: Option<u64>,
+    code: Option<(B256, Option<Bytecode>)>,
+    storage: Option<StorageOverride>,
+}
+
+/// Database wrapper that applies a [`StateOverride`] on top of the wrapped database.
+///
+/// Accounts that are not in the wrapped database are created by the overrides of their
+/// balance, nonce or code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OverrideDB<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    /// Overridden accounts.
+    accounts: HashMap<Address, OverriddenAccount>,
+    /// Overridden codes by their hash.
+    contracts: HashMap<B256, Bytecode>,
+    /// Overridden block hashes by block number.
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl<DB> OverrideDB<DB> {
+    /// Creates a new database without overrides.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: HashMap::default(),
+            contracts: HashMap::default(),
+            block_hashes: HashMap::default(),
+        }
+    }
+
+    /// Applies the state overrides on top of the previous ones.
+    pub fn with_state_override(mut self, overrides: &StateOverride) -> Result<Self, OverrideError> {
+        self.apply_state_override(overrides)?;
+        Ok(self)
+    }
+
+    /// Overrides the block hashes.
+    pub fn with_block_hashes(
+        mut self,
+        block_hashes: impl IntoIterator<Item = (u64, B256)>,
+    ) -> Self {
+        self.block_hashes.extend(block_hashes);
+        self
+    }
+
+    /// Applies the state overrides on top of the previous ones.
+    ///
+    /// No override is applied if any of them is invalid.
+    pub fn apply_state_override(&mut self, overrides: &StateOverride) -> Result<(), OverrideError> {
+        let mut prepared = HashMap::<Address, OverriddenAccount>::default();
+        for (address, account) in overrides {
+            let storage = match (&account.state, &account.state_diff) {
+                (Some(_), Some(_)) => return Err(OverrideError::StateAndStateDiff(*address)),
+                (Some(state), None) => Some(StorageOverride::Replace(storage_map(state))),
+                (None, Some(diff)) => Some(StorageOverride::Diff(storage_map(diff))),
+                (None, None) => None,
+            };
+            let code = match &account.code {
+                Some(code) if code.is_empty() => Some((KECCAK_EMPTY, None)),
+                Some(code) => {
+                    let bytecode = Bytecode::new_raw_checked(code.clone())
+                        .map_err(|error| OverrideError::InvalidCode(*address, error))?;
+                    Some((bytecode.hash_slow(), Some(bytecode)))
+                }
+                None => None,
+            };
+            prepared.insert(
+                *address,
+                OverriddenAccount {
+                    balance: account.balance,
+                    nonce: account.nonce,
+                    code,
+                    storage,
+                },
+            );
+        }
+
+        for (address, account) in prepared {
+            if let Some((hash, Some(bytecode))) = &account.code {
+                self.contracts.insert(*hash, bytecode.clone());
+            }
+            let previous = self.accounts.entry(address).or_default();
+            previous.balance = account.balance.or(previous.balance);
+            previous.nonce = account.nonce.or(previous.nonce);
+            previous.code = account.code.or(previous.code.take());
+            previous.storage = match (previous.storage.take(), account.storage) {
+                (
+                    Some(StorageOverride::Replace(mut storage)),
+                    Some(StorageOverride::Diff(diff)),
+                ) => {
+                    storage.extend(diff);
+                    Some(StorageOverride::Replace(storage))
+                }
+                (Some(StorageOverride::Diff(mut storage)), Some(StorageOverride::Diff(diff))) => {
+                    storage.extend(diff);
+                    Some(StorageOverride::Diff(storage))
+                }
+                (previous, storage) => storage.or(previous),
+            };
+        }
+        Ok(())
+    }
+
+    /// Applies the overrides to the account loaded from the wrapped database.
+    fn override_account(&self, address: Address, info: Option<AccountInfo>) -> Option<AccountInfo> {
+        let Some(account) = self.accounts.get(&address) else {
+            return info;
+        };
+        if account.balance.is_none() && account.nonce.is_none() && account.code.is_none() {
+            return info;
+        }
+        let mut info = info.unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some((hash, code)) = &account.code {
+            info.code_hash = *hash;
+            info.code = code.clone();
+        }
+        Some(info)
+    }
+
+    /// Returns the overridden storage value, `None` if the slot is not overridden.
+    fn override_storage(&self, address: Address, index: StorageKey) -> Option<StorageValue> {
+        match self.accounts.get(&address)?.storage.as_ref()? {
+            StorageOverride::Replace(storage) => {
+                Some(storage.get(&index).copied().unwrap_or_default())
+            }
+            StorageOverride::Diff(storage) => storage.get(&index).copied(),
+        }
+    }
+}
+
+/// Converts the storage slots to storage keys and values.
+fn storage_map(storage: &HashMap<B256, B256>) -> HashMap<StorageKey, StorageValue> {
+    storage
+        .iter()
+        .map(|(key, value)| ((*key).into(), (*value).into()))
+        .collect()
+}
+
+impl<DB: Database> Database for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        Ok(self.override_account(address, info))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash(code_hash),
+        }
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        match self.override_storage(address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.db.block_hash(number),
+        }
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        Ok(self.override_account(address, info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        match self.override_storage(address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.db.block_hash_ref(number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccountOverride, InMemoryDB};
+    use primitives::{address, bytes};
+
+    const A: Address = address!("0x1000000000000000000000000000000000000001");
+    const B: Address = address!("0x2000000000000000000000000000000000000002");
+    const NEW: Address = address!("0x3000000000000000000000000000000000000003");
+
+    fn slot(value: u64) -> B256 {
+        B256::from(U256::from(value))
+    }
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            A,
+            AccountInfo {
+                balance: U256::from(1),
+                nonce: 2,
+                ..Default::default()
+            },
+        );
+        for (address, value) in [(A, 10), (B, 20)] {
+            db.insert_account_storage(address, U256::from(1), U256::from(value))
+                .unwrap();
+            db.insert_account_storage(address, U256::from(2), U256::from(value + 1))
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn account_and_storage_overrides() {
+        let code = bytes!("0x6001600055");
+        let overrides = StateOverride::from_iter([
+            (
+                A,
+                AccountOverride::default()
+                    .with_balance(U256::from(100))
+                    .with_state(HashMap::from_iter([(slot(1), slot(7))])),
+            ),
+            (
+                B,
+                AccountOverride::default()
+                    .with_state_diff(HashMap::from_iter([(slot(1), slot(8))])),
+            ),
+            (NEW, AccountOverride::default().with_code(code.clone())),
+        ]);
+        let mut db = OverrideDB::new(db())
+            .with_state_override(&overrides)
+            .unwrap()
+            .with_block_hashes([(5, slot(5))]);
+
+        let a = db.basic(A).unwrap().unwrap();
+        assert_eq!((a.balance, a.nonce), (U256::from(100), 2));
+        // Storage is replaced.
+        assert_eq!(db.storage(A, U256::from(1)).unwrap(), U256::from(7));
+        assert_eq!(db.storage(A, U256::from(2)).unwrap(), U256::ZERO);
+        // Only the slot is changed.
+        assert_eq!(db.storage(B, U256::from(1)).unwrap(), U256::from(8));
+        assert_eq!(db.storage_ref(B, U256::from(2)).unwrap(), U256::from(21));
+
+        let new = db.basic_ref(NEW).unwrap().unwrap();
+        assert_eq!(new.code_hash, primitives::keccak256(&code));
+        assert_eq!(
+            db.code_by_hash(new.code_hash).unwrap().original_bytes(),
+            code
+        );
+        assert_eq!(db.block_hash(5).unwrap(), slot(5));
+
+        // Later overrides are applied on top of the previous ones.
+        let overrides = StateOverride::from_iter([(
+            A,
+            AccountOverride::default()
+                .with_nonce(9)
+                .with_state_diff(HashMap::from_iter([(slot(2), slot(3))])),
+        )]);
+        db.apply_state_override(&overrides).unwrap();
+        let a = db.basic(A).unwrap().unwrap();
+        assert_eq!((a.balance, a.nonce), (U256::from(100), 9));
+        assert_eq!(db.storage(A, U256::from(1)).unwrap(), U256::from(7));
+        assert_eq!(db.storage(A, U256::from(2)).unwrap(), U256::from(3));
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        let overrides = StateOverride::from_iter([(
+            A,
+            AccountOverride::default()
+                .with_state(HashMap::default())
+                .with_state_diff(HashMap::default()),
+        )]);
+        assert_eq!(
+            OverrideDB::new(db()).with_state_override(&overrides).err(),
+            Some(OverrideError::StateAndStateDiff(A))
+        );
+
+        let overrides =
+            StateOverride::from_iter([(A, AccountOverride::default().with_code(bytes!("0xef00")))]);
+        assert!(matches!(
+            OverrideDB::new(db()).with_state_override(&overrides).err(),
+            Some(OverrideError::InvalidCode(A, _))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn account_override_serde() {
+        let json = r#"{"balance":"0x10","nonce":"0x1","stateDiff":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"},"movePrecompileToAddress":"0x0000000000000000000000000000000000000100"}"#;
+        let account: AccountOverride = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            account,
+            AccountOverride::default()
+                .with_balance(U256::from(16))
+                .with_nonce(1)
+                .with_state_diff(HashMap::from_iter([(slot(1), slot(2))]))
+                .with_move_precompile_to(address!("0x0000000000000000000000000000000000000100"))
+        );
+        assert_eq!(serde_json::to_string(&account).unwrap(), json);
+    }
+}
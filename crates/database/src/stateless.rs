@@ -0,0 +1,389 @@
+//! Stateless database verified against the state root.
+//!
+//! [`StatelessDB`] is created from the pre-state root and the trie nodes of an
+//! `eth_getProof`-style multiproof. Every account and storage read is proven against the root
+//! before it is returned, so the provider of the proof does not have to be trusted. After
+//! execution the changes are applied to the revealed tries to calculate the new state root.
+use crate::{
+    states::{BundleState, OriginalValuesKnown},
+    trie::{
+        state_trie::{encode_account, encode_slot, TrieAccountInfo},
+        MerklePatriciaTrie, ProofNodes, TrieError, EMPTY_ROOT_HASH,
+    },
+};
+use alloy_rlp::{Decodable, Header};
+use core::fmt;
+use database_interface::{DBErrorMarker, Database};
+use primitives::{
+    hash_map, keccak256, Address, Bytes, HashMap, HashSet, StorageKey, StorageValue, B256,
+    KECCAK_EMPTY, U256,
+};
+use state::{AccountInfo, Bytecode};
+
+/// Account leaf of the account trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TrieAccount {
+    info: TrieAccountInfo,
+    storage_root: B256,
+}
+
+impl TrieAccount {
+    /// Decodes the RLP encoded `[nonce, balance, storage_root, code_hash]` list.
+    fn decode(mut encoded: &[u8]) -> Option<Self> {
+        let header = Header::decode(&mut encoded).ok()?;
+        if !header.list || header.payload_length != encoded.len() {
+            return None;
+        }
+        let nonce = u64::decode(&mut encoded).ok()?;
+        let balance = U256::decode(&mut encoded).ok()?;
+        let storage_root = B256::decode(&mut encoded).ok()?;
+        let code_hash = B256::decode(&mut encoded).ok()?;
+        encoded.is_empty().then_some(Self {
+            info: TrieAccountInfo {
+                nonce,
+                balance,
+                code_hash,
+            },
+            storage_root,
+        })
+    }
+}
+
+/// Error returned when the read can't be proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatelessError {
+    /// Trie node needed for the read is missing or invalid.
+    Trie(TrieError),
+    /// Account leaf is not a valid account.
+    InvalidAccount(Address),
+    /// Storage leaf is not a valid storage value.
+    InvalidStorage(Address, StorageKey),
+    /// Bytecode was not provided.
+    MissingCode(B256),
+    /// Block hash was not provided.
+    MissingBlockHash(u64),
+}
+
+impl From<TrieError> for StatelessError {
+    fn from(error: TrieError) -> Self {
+        Self::Trie(error)
+    }
+}
+
+impl DBErrorMarker for StatelessError {}
+
+impl fmt::Display for StatelessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trie(error) => error.fmt(f),
+            Self::InvalidAccount(address) => write!(f, "account {address} has invalid encoding"),
+            Self::InvalidStorage(address, index) => {
+                write!(f, "storage {index} of {address} has invalid encoding")
+            }
+            Self::MissingCode(hash) => write!(f, "bytecode {hash} is missing"),
+            Self::MissingBlockHash(number) => write!(f, "block hash {number} is missing"),
+        }
+    }
+}
+
+impl core::error::Error for StatelessError {}
+
+/// Database that proves every read against the state root.
+///
+/// Bytecodes are keyed by their hash so they are verified on insertion. Block hashes can't
+/// be proven against the state root and have to be checked by the caller against the chain
+/// of ancestor headers.
+#[derive(Clone, Debug, Default)]
+pub struct StatelessDB {
+    /// Nodes of the account and storage tries.
+    nodes: ProofNodes,
+    /// Partially revealed account trie.
+    accounts: MerklePatriciaTrie,
+    /// Partially revealed storage tries of the accessed accounts.
+    storages: HashMap<Address, MerklePatriciaTrie>,
+    /// Bytecodes by their hash.
+    contracts: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl StatelessDB {
+    /// Creates the database from the state root and the trie nodes.
+    pub fn new(state_root: B256, nodes: ProofNodes) -> Self {
+        Self {
+            nodes,
+            accounts: MerklePatriciaTrie::from_root(state_root),
+            ..Default::default()
+        }
+    }
+
+    /// Adds bytecodes of the accessed contracts.
+    pub fn with_contracts(mut self, contracts: impl IntoIterator<Item = Bytecode>) -> Self {
+        self.contracts.extend(
+            contracts
+                .into_iter()
+                .map(|bytecode| (bytecode.hash_slow(), bytecode)),
+        );
+        self
+    }
+
+    /// Adds block hashes used by the `BLOCKHASH` opcode.
+    pub fn with_block_hashes(
+        mut self,
+        block_hashes: impl IntoIterator<Item = (u64, B256)>,
+    ) -> Self {
+        self.block_hashes.extend(block_hashes);
+        self
+    }
+
+    /// Adds more trie nodes, for example the siblings needed to apply removals.
+    pub fn extend_nodes(&mut self, nodes: impl IntoIterator<Item = Bytes>) {
+        self.nodes.extend(nodes);
+    }
+
+    /// Returns the current state root.
+    pub fn state_root(&mut self) -> B256 {
+        self.accounts.root()
+    }
+
+    /// Applies all changes of the bundle to the revealed tries.
+    ///
+    /// Fails if a node needed for the update is not in the proof. Afterwards
+    /// [`StatelessDB::state_root`] returns the root of the post state.
+    pub fn apply_bundle(&mut self, bundle: &BundleState) -> Result<(), StatelessError> {
+        let changeset = bundle.to_plain_state(OriginalValuesKnown::Yes);
+
+        let mut storage_changed: HashSet<Address> = HashSet::default();
+        for storage in &changeset.storage {
+            if storage.wipe_storage {
+                self.storages
+                    .insert(storage.address, MerklePatriciaTrie::new());
+            } else {
+                self.load_storage_trie(storage.address)?;
+            }
+            let trie = self.storages.get_mut(&storage.address).expect("loaded");
+            for (key, value) in &storage.storage {
+                let key = keccak256(key.to_be_bytes::<32>());
+                trie.insert_proven(key.as_slice(), encode_slot(*value), &self.nodes)?;
+            }
+            storage_changed.insert(storage.address);
+        }
+
+        let mut accounts: HashMap<Address, Option<TrieAccountInfo>> = changeset
+            .accounts
+            .iter()
+            .map(|(address, info)| (*address, info.as_ref().map(Into::into)))
+            .collect();
+        for address in storage_changed {
+            if let hash_map::Entry::Vacant(entry) = accounts.entry(address) {
+                entry.insert(self.account(address)?.map(|account| account.info));
+            }
+        }
+
+        for (address, info) in accounts {
+            let key = keccak256(address);
+            let Some(info) = info else {
+                self.storages.remove(&address);
+                self.accounts.remove_proven(key.as_slice(), &self.nodes)?;
+                continue;
+            };
+            self.load_storage_trie(address)?;
+            let storage_root = self.storages.get_mut(&address).expect("loaded").root();
+            self.accounts.insert_proven(
+                key.as_slice(),
+                encode_account(&info, storage_root),
+                &self.nodes,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reads the account leaf.
+    fn account(&mut self, address: Address) -> Result<Option<TrieAccount>, StatelessError> {
+        let key = keccak256(address);
+        let Some(encoded) = self.accounts.get_proven(key.as_slice(), &self.nodes)? else {
+            return Ok(None);
+        };
+        TrieAccount::decode(encoded)
+            .map(Some)
+            .ok_or(StatelessError::InvalidAccount(address))
+    }
+
+    /// Creates the blinded storage trie of the account if it is not loaded yet.
+    fn load_storage_trie(&mut self, address: Address) -> Result<(), StatelessError> {
+        if !self.storages.contains_key(&address) {
+            let storage_root = self
+                .account(address)?
+                .map_or(EMPTY_ROOT_HASH, |account| account.storage_root);
+            self.storages
+                .insert(address, MerklePatriciaTrie::from_root(storage_root));
+        }
+        Ok(())
+    }
+}
+
+impl Database for StatelessDB {
+    type Error = StatelessError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self.account(address)?.map(|account| AccountInfo {
+            balance: account.info.balance,
+            nonce: account.info.nonce,
+            code_hash: account.info.code_hash,
+            code: self.contracts.get(&account.info.code_hash).cloned(),
+        }))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(StatelessError::MissingCode(code_hash))
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.load_storage_trie(address)?;
+        let trie = self.storages.get_mut(&address).expect("loaded");
+        let key = keccak256(index.to_be_bytes::<32>());
+        match trie.get_proven(key.as_slice(), &self.nodes)? {
+            None => Ok(StorageValue::ZERO),
+            Some(mut encoded) => StorageValue::decode(&mut encoded)
+                .ok()
+                .filter(|_| encoded.is_empty())
+                .ok_or(StatelessError::InvalidStorage(address, index)),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(StatelessError::MissingBlockHash(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{states::bundle_state::BundleRetention, PlainAccount, State, StateTrie};
+    use database_interface::DatabaseCommit;
+    use primitives::{address, bytes};
+    use state::{Account, AccountStatus, EvmStorageSlot};
+
+    #[test]
+    fn proven_reads_and_state_root() {
+        let a = address!("0x1000000000000000000000000000000000000001");
+        let b = address!("0x2000000000000000000000000000000000000002");
+        let created = address!("0x3000000000000000000000000000000000000003");
+        let code = Bytecode::new_legacy(bytes!("0x6001600055"));
+
+        let mut accounts: HashMap<Address, PlainAccount> = (0..32u64)
+            .map(|i| {
+                let address = Address::from_word(keccak256(i.to_be_bytes()));
+                let info = AccountInfo::from_balance(U256::from(i + 1));
+                (address, PlainAccount::from(info))
+            })
+            .collect();
+        accounts.insert(
+            a,
+            PlainAccount {
+                info: AccountInfo::from_balance(U256::from(10)).with_code(code.clone()),
+                storage: (1..=3u64).map(|i| (U256::from(i), U256::from(i))).collect(),
+            },
+        );
+        accounts.insert(
+            b,
+            PlainAccount::from(AccountInfo::from_balance(U256::from(20))),
+        );
+        let mut full = StateTrie::from_plain_accounts(accounts.iter().map(|(k, v)| (*k, v)));
+        let pre_root = full.root();
+
+        let mut nodes = ProofNodes::default();
+        for address in [a, b, created] {
+            nodes.extend(full.account_proof(address));
+        }
+        for slot in 1..=4u64 {
+            nodes.extend(full.storage_proof(a, U256::from(slot)));
+        }
+        let mut db = StatelessDB::new(pre_root, nodes).with_contracts([code.clone()]);
+
+        let a_info = db.basic(a).unwrap().unwrap();
+        assert_eq!(a_info.balance, U256::from(10));
+        assert_eq!(a_info.code, Some(code.clone()));
+        assert_eq!(db.basic(created).unwrap(), None);
+        assert_eq!(db.storage(a, U256::from(2)).unwrap(), U256::from(2));
+        assert_eq!(db.storage(a, U256::from(4)).unwrap(), U256::ZERO);
+        assert_eq!(db.storage(created, U256::from(1)).unwrap(), U256::ZERO);
+        assert_eq!(db.code_by_hash(a_info.code_hash).unwrap(), code);
+
+        // Accounts outside of the proof can't be read.
+        let unknown = address!("0x4000000000000000000000000000000000000004");
+        assert!(matches!(
+            db.basic(unknown),
+            Err(StatelessError::Trie(TrieError::MissingNode(_)))
+        ));
+        assert_eq!(db.block_hash(1), Err(StatelessError::MissingBlockHash(1)));
+
+        // Change the storage of `a`, move balance from `b` and create an account.
+        let mut state = State::builder()
+            .with_database(&mut db)
+            .with_bundle_update()
+            .build();
+        let a_info = state.basic(a).unwrap().unwrap();
+        let b_info = state.basic(b).unwrap().unwrap();
+        state.basic(created).unwrap();
+        let mut changes: HashMap<Address, Account> = HashMap::default();
+        changes.insert(
+            a,
+            Account {
+                info: a_info,
+                storage: [
+                    (
+                        U256::from(1),
+                        EvmStorageSlot::new_changed(U256::from(1), U256::ZERO),
+                    ),
+                    (
+                        U256::from(4),
+                        EvmStorageSlot::new_changed(U256::ZERO, U256::from(4)),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                status: AccountStatus::Touched,
+            },
+        );
+        changes.insert(
+            b,
+            Account {
+                info: AccountInfo::from_balance(U256::from(15)).with_nonce(b_info.nonce + 1),
+                storage: Default::default(),
+                status: AccountStatus::Touched,
+            },
+        );
+        changes.insert(
+            created,
+            Account {
+                info: AccountInfo::from_balance(U256::from(5)),
+                storage: Default::default(),
+                status: AccountStatus::Touched,
+            },
+        );
+        state.commit(changes);
+        state.merge_transitions(BundleRetention::PlainState);
+        let bundle = state.take_bundle();
+        drop(state);
+
+        db.apply_bundle(&bundle).unwrap();
+        full.apply_bundle(&bundle);
+        assert_eq!(db.state_root(), full.root());
+        assert_ne!(db.state_root(), pre_root);
+        assert_eq!(db.storage(a, U256::from(4)).unwrap(), U256::from(4));
+    }
+}
@@ -3,7 +3,9 @@ use super::{
     CacheAccount, StateBuilder, TransitionAccount, TransitionState,
 };
 use bytecode::Bytecode;
-use database_interface::{Database, DatabaseCommit, EmptyDB};
+use database_interface::{
+    BlockStateDb, Database, DatabaseCommit, DatabaseSnapshot, EmptyDB, SnapshotId,
+};
 use primitives::{hash_map, Address, HashMap, StorageKey, StorageValue, B256, BLOCK_HASH_HISTORY};
 use state::{Account, AccountInfo};
 use std::{
@@ -63,6 +65,22 @@ pub struct State<DB> {
     ///
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// Stack of the active snapshots, see [DatabaseSnapshot].
+    pub(crate) snapshots: Vec<StateSnapshot>,
+}
+
+/// Changes made to the [State] after a snapshot was taken.
+///
+/// Accounts and transitions are copied the first time they are changed after the snapshot,
+/// the bundle is copied the first time transitions are merged into it.
+#[derive(Debug, Default)]
+pub(crate) struct StateSnapshot {
+    /// Cached accounts before their first change, `None` if they were not in the cache.
+    accounts: HashMap<Address, Option<CacheAccount>>,
+    /// Transitions before their first change, `None` if there was no transition.
+    transitions: HashMap<Address, Option<TransitionAccount>>,
+    /// Transition state and bundle before the first merge.
+    bundle: Option<(Option<TransitionState>, BundleState)>,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -98,6 +116,7 @@ impl<DB: Database> State<DB> {
             if balance == 0 {
                 continue;
             }
+            self.snapshot_account(address);
             let original_account = self.load_cache_account(address)?;
             transitions.push((
                 address,
@@ -106,10 +125,7 @@ impl<DB: Database> State<DB> {
                     .expect("Balance is not zero"),
             ))
         }
-        // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(())
     }
 
@@ -124,15 +140,13 @@ impl<DB: Database> State<DB> {
         let mut transitions = Vec::new();
         let mut balances = Vec::new();
         for address in addresses {
+            self.snapshot_account(address);
             let original_account = self.load_cache_account(address)?;
             let (balance, transition) = original_account.drain_balance();
             balances.push(balance);
             transitions.push((address, transition))
         }
-        // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(balances)
     }
 
@@ -142,10 +156,12 @@ impl<DB: Database> State<DB> {
     }
 
     pub fn insert_not_existing(&mut self, address: Address) {
+        self.snapshot_account(address);
         self.cache.insert_not_existing(address)
     }
 
     pub fn insert_account(&mut self, address: Address, info: AccountInfo) {
+        self.snapshot_account(address);
         self.cache.insert_account(address, info)
     }
 
@@ -155,6 +171,7 @@ impl<DB: Database> State<DB> {
         info: AccountInfo,
         storage: PlainStorage,
     ) {
+        self.snapshot_account(address);
         self.cache
             .insert_account_with_storage(address, info, storage)
     }
@@ -163,6 +180,18 @@ impl<DB: Database> State<DB> {
     pub fn apply_transition(&mut self, transitions: Vec<(Address, TransitionAccount)>) {
         // Add transition to transition state.
         if let Some(s) = self.transition_state.as_mut() {
+            if let Some(snapshot) = self
+                .snapshots
+                .last_mut()
+                .filter(|snapshot| snapshot.bundle.is_none())
+            {
+                for (address, _) in &transitions {
+                    snapshot
+                        .transitions
+                        .entry(*address)
+                        .or_insert_with(|| s.transitions.get(address).cloned());
+                }
+            }
             s.add_transitions(transitions)
         }
     }
@@ -173,6 +202,7 @@ impl<DB: Database> State<DB> {
     /// we at any time revert state of bundle to the state before transition
     /// is applied.
     pub fn merge_transitions(&mut self, retention: BundleRetention) {
+        self.snapshot_bundle();
         if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
             self.bundle_state
                 .apply_transitions_and_create_reverts(transition_state, retention);
@@ -209,6 +239,26 @@ impl<DB: Database> State<DB> {
         }
     }
 
+    /// Saves the cached account to the last snapshot if this is its first change after the
+    /// snapshot.
+    fn snapshot_account(&mut self, address: Address) {
+        if let Some(snapshot) = self.snapshots.last_mut() {
+            snapshot
+                .accounts
+                .entry(address)
+                .or_insert_with(|| self.cache.accounts.get(&address).cloned());
+        }
+    }
+
+    /// Saves the transition state and the bundle to the last snapshot if they were not saved yet.
+    fn snapshot_bundle(&mut self) {
+        if let Some(snapshot) = self.snapshots.last_mut() {
+            snapshot
+                .bundle
+                .get_or_insert_with(|| (self.transition_state.clone(), self.bundle_state.clone()));
+        }
+    }
+
     // TODO : Make cache aware of transitions dropping by having global transition counter.
     /// Takess the [`BundleState`] changeset from the [`State`], replacing it
     /// with an empty one.
@@ -221,6 +271,7 @@ impl<DB: Database> State<DB> {
     /// [`StateBuilder::with_bundle_prestate`] option, the pre-state will be
     /// taken along with any changes made by [`State::merge_transitions`].
     pub fn take_bundle(&mut self) -> BundleState {
+        self.snapshot_bundle();
         core::mem::take(&mut self.bundle_state)
     }
 }
@@ -309,11 +360,114 @@ impl<DB: Database> Database for State<DB> {
 
 impl<DB: Database> DatabaseCommit for State<DB> {
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
+        for (address, account) in &evm_state {
+            if account.is_touched() {
+                self.snapshot_account(*address);
+            }
+        }
         let transitions = self.cache.apply_evm_state(evm_state);
         self.apply_transition(transitions);
     }
 }
 
+/// Bundle is collected only if the state is built with [`StateBuilder::with_bundle_update`].
+impl<DB: Database> BlockStateDb for State<DB> {
+    type Bundle = BundleState;
+
+    #[inline]
+    fn increment_balances(
+        &mut self,
+        balances: impl IntoIterator<Item = (Address, u128)>,
+    ) -> Result<(), Self::Error> {
+        State::increment_balances(self, balances)
+    }
+
+    #[inline]
+    fn insert_block_hash(&mut self, number: u64, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+
+    #[inline]
+    fn merge_transitions(&mut self) {
+        State::merge_transitions(self, BundleRetention::Reverts)
+    }
+
+    #[inline]
+    fn take_bundle(&mut self) -> BundleState {
+        State::take_bundle(self)
+    }
+}
+
+/// Changes made through [DatabaseCommit::commit], the balance changes, the account insertions
+/// and [State::merge_transitions] are reverted. Changes made directly to the [CacheState] are
+/// not tracked.
+///
+/// Block hashes and contract code are not part of the snapshot: [State::block_hashes] and the
+/// cached contracts keep the entries added after the snapshot. Contracts are keyed by their code
+/// hash, so the kept entries do not change the state.
+impl<DB: Database> DatabaseSnapshot for State<DB> {
+    fn snapshot(&mut self) -> SnapshotId {
+        self.snapshots.push(StateSnapshot::default());
+        self.snapshots.len() - 1
+    }
+
+    fn revert_to(&mut self, id: SnapshotId) -> bool {
+        if id >= self.snapshots.len() {
+            return false;
+        }
+        for snapshot in self.snapshots.split_off(id).into_iter().rev() {
+            for (address, account) in snapshot.accounts {
+                match account {
+                    Some(account) => self.cache.accounts.insert(address, account),
+                    None => self.cache.accounts.remove(&address),
+                };
+            }
+            if let Some((transition_state, bundle_state)) = snapshot.bundle {
+                self.transition_state = transition_state;
+                self.bundle_state = bundle_state;
+            }
+            if let Some(transition_state) = self.transition_state.as_mut() {
+                for (address, transition) in snapshot.transitions {
+                    match transition {
+                        Some(transition) => {
+                            transition_state.transitions.insert(address, transition)
+                        }
+                        None => transition_state.transitions.remove(&address),
+                    };
+                }
+            }
+        }
+        true
+    }
+
+    fn release_snapshot(&mut self, id: SnapshotId) -> bool {
+        if id >= self.snapshots.len() {
+            return false;
+        }
+        let released = self.snapshots.split_off(id);
+        if let Some(previous) = self.snapshots.last_mut() {
+            // The oldest copies are the ones the previous snapshot needs. Transitions are only
+            // needed until the previous snapshot saved the bundle.
+            for snapshot in released {
+                for (address, account) in snapshot.accounts {
+                    previous.accounts.entry(address).or_insert(account);
+                }
+                if previous.bundle.is_none() {
+                    for (address, transition) in snapshot.transitions {
+                        previous.transitions.entry(address).or_insert(transition);
+                    }
+                    previous.bundle = snapshot.bundle;
+                }
+            }
+        }
+        true
+    }
+
+    fn snapshot_count(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,6 +477,42 @@ mod tests {
     };
     use primitives::{keccak256, U256};
 
+    #[test]
+    fn snapshot_revert() {
+        let address = Address::with_last_byte(1);
+        let mut state = State::builder().with_bundle_update().build();
+        state.insert_account(
+            address,
+            AccountInfo {
+                balance: U256::from(1),
+                ..Default::default()
+            },
+        );
+        let balance = |state: &mut State<EmptyDB>| state.basic(address).unwrap().unwrap().balance;
+
+        let first = state.snapshot();
+        state.increment_balances([(address, 1)]).unwrap();
+        state.merge_transitions(BundleRetention::Reverts);
+        let second = state.snapshot();
+        state.increment_balances([(address, 1)]).unwrap();
+        assert_eq!(balance(&mut state), U256::from(3));
+
+        assert!(state.revert_to(second));
+        assert_eq!(balance(&mut state), U256::from(2));
+        assert!(state
+            .transition_state
+            .as_ref()
+            .unwrap()
+            .transitions
+            .is_empty());
+        assert_eq!(state.bundle_state.state.len(), 1);
+
+        assert!(state.revert_to(first));
+        assert_eq!(balance(&mut state), U256::from(1));
+        assert!(state.bundle_state.state.is_empty());
+        assert!(!state.revert_to(first));
+    }
+
     #[test]
     fn block_hash_cache() {
         let mut state = State::builder().build();
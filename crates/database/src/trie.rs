@@ -0,0 +1,10 @@
+//! Merkle-Patricia trie used to calculate the state root.
+//!
+//! [`StateTrie`] keeps the account and storage tries in memory and updates them from
+//! [`crate::BundleState`] so the state root can be calculated after every block without
+//! rehashing the whole state.
+pub mod mpt;
+pub mod state_trie;
+
+pub use mpt::{MerklePatriciaTrie, ProofNodes, TrieError, EMPTY_ROOT_HASH};
+pub use state_trie::StateTrie;
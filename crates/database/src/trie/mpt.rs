@@ -0,0 +1,928 @@
+//! Merkle-Patricia trie with cached node references.
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use core::{fmt, mem};
+use primitives::{keccak256, Bytes, HashMap, B256};
+use std::{boxed::Box, vec, vec::Vec};
+
+/// Root of the empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    primitives::b256!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// In-memory Merkle-Patricia trie.
+///
+/// Every node caches its RLP reference, and inserts and removals only clear the cache of
+/// the nodes on the modified path. Calculating the root after a small number of updates
+/// hashes only those paths instead of the whole trie.
+///
+/// Keys are used as is, secure tries should hash the key before inserting it.
+///
+/// Trie created with [`MerklePatriciaTrie::from_root`] starts as a single blinded node,
+/// only its hash is known. Nodes are revealed from [`ProofNodes`] by the `*_proven` methods
+/// when they are on the path of the accessed key. Methods without the suffix panic if they
+/// encounter a blinded node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerklePatriciaTrie {
+    root: Node,
+    len: usize,
+}
+
+impl MerklePatriciaTrie {
+    /// Creates a new empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a blinded trie with the given root hash.
+    pub fn from_root(root: B256) -> Self {
+        let root = if root == EMPTY_ROOT_HASH {
+            Node::Empty
+        } else {
+            Node::Hash(root)
+        };
+        Self { root, len: 0 }
+    }
+
+    /// Returns the number of values in the trie.
+    ///
+    /// Values under blinded nodes are not counted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie is empty.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.root, Node::Empty)
+    }
+
+    /// Returns the value stored under the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is a blinded node on the path.
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.root.get(&to_nibbles(key))
+    }
+
+    /// Reveals the nodes on the path and returns the value stored under the key.
+    ///
+    /// Absence of the key is proven as well, so `None` is returned only if the revealed nodes
+    /// show that the key is not in the trie.
+    pub fn get_proven(
+        &mut self,
+        key: &[u8],
+        nodes: &ProofNodes,
+    ) -> Result<Option<&[u8]>, TrieError> {
+        let path = to_nibbles(key);
+        self.reveal(&path, nodes, false)?;
+        Ok(self.root.get(&path))
+    }
+
+    /// Reveals the nodes on the path and inserts the value, see [`Self::insert`].
+    pub fn insert_proven(
+        &mut self,
+        key: &[u8],
+        value: Vec<u8>,
+        nodes: &ProofNodes,
+    ) -> Result<bool, TrieError> {
+        if value.is_empty() {
+            self.remove_proven(key, nodes)?;
+            return Ok(false);
+        }
+        self.reveal(&to_nibbles(key), nodes, false)?;
+        Ok(self.insert(key, value))
+    }
+
+    /// Reveals the nodes on the path and removes the key, see [`Self::remove`].
+    ///
+    /// If removal collapses a branch, its remaining child has to be revealed as well.
+    pub fn remove_proven(&mut self, key: &[u8], nodes: &ProofNodes) -> Result<bool, TrieError> {
+        self.reveal(&to_nibbles(key), nodes, true)?;
+        Ok(self.remove(key))
+    }
+
+    /// Reveals all nodes needed to access the path.
+    ///
+    /// Revealing does not change the root, so the trie stays valid if it fails.
+    fn reveal(&mut self, path: &[u8], nodes: &ProofNodes, removal: bool) -> Result<(), TrieError> {
+        let revealed = self.root.reveal_path(path, nodes, removal)?;
+        self.len += revealed;
+        Ok(())
+    }
+
+    /// Returns the RLP encoded nodes on the path to the key.
+    ///
+    /// Nodes that are embedded in their parent are not included. The proof of an absent
+    /// key ends with the node where the path diverges. Proof stops at the first blinded node.
+    pub fn proof(&mut self, key: &[u8]) -> Vec<Bytes> {
+        let mut proof = Vec::new();
+        self.root.proof(&to_nibbles(key), true, &mut proof);
+        proof
+    }
+
+    /// Inserts the value under the key and returns `true` if the key was not present.
+    ///
+    /// Inserting an empty value removes the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is a blinded node on the path.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) -> bool {
+        if value.is_empty() {
+            self.remove(key);
+            return false;
+        }
+        let root = mem::take(&mut self.root);
+        let (root, inserted) = root.insert(&to_nibbles(key), value);
+        self.root = root;
+        self.len += inserted as usize;
+        inserted
+    }
+
+    /// Removes the key and returns `true` if it was present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is a blinded node on the path or if the remaining child of a
+    /// collapsed branch is blinded.
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        let root = mem::take(&mut self.root);
+        let (root, removed) = root.remove(&to_nibbles(key));
+        self.root = root;
+        self.len = self.len.saturating_sub(removed as usize);
+        removed
+    }
+
+    /// Removes all values from the trie.
+    pub fn clear(&mut self) {
+        self.root = Node::Empty;
+        self.len = 0;
+    }
+
+    /// Calculates the root hash of the trie.
+    ///
+    /// Node references are cached so calling this again without modifications is free.
+    pub fn root(&mut self) -> B256 {
+        let reference = self.root.reference();
+        if reference.len() == 33 {
+            B256::from_slice(&reference[1..])
+        } else {
+            keccak256(&reference)
+        }
+    }
+}
+
+/// RLP encoded trie nodes by their hash.
+///
+/// Nodes are keyed by the hash of their encoding so any node returned by the set is
+/// authentic with respect to the root that references it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofNodes {
+    nodes: HashMap<B256, Bytes>,
+}
+
+impl ProofNodes {
+    /// Creates the set from RLP encoded nodes.
+    pub fn new(nodes: impl IntoIterator<Item = Bytes>) -> Self {
+        nodes.into_iter().collect()
+    }
+
+    /// Inserts the RLP encoded node.
+    pub fn insert(&mut self, node: Bytes) {
+        self.nodes.insert(keccak256(&node), node);
+    }
+
+    /// Returns the node with the given hash.
+    pub fn get(&self, hash: &B256) -> Option<&Bytes> {
+        self.nodes.get(hash)
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl FromIterator<Bytes> for ProofNodes {
+    fn from_iter<T: IntoIterator<Item = Bytes>>(iter: T) -> Self {
+        let mut nodes = Self::default();
+        for node in iter {
+            nodes.insert(node);
+        }
+        nodes
+    }
+}
+
+impl Extend<Bytes> for ProofNodes {
+    fn extend<T: IntoIterator<Item = Bytes>>(&mut self, iter: T) {
+        for node in iter {
+            self.insert(node);
+        }
+    }
+}
+
+/// Error returned when a blinded node can't be revealed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrieError {
+    /// Node with the hash is not in the proof.
+    MissingNode(B256),
+    /// Node with the hash is not a valid trie node.
+    InvalidNode(B256),
+}
+
+impl fmt::Display for TrieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingNode(hash) => write!(f, "trie node {hash} is missing from the proof"),
+            Self::InvalidNode(hash) => write!(f, "trie node {hash} is invalid"),
+        }
+    }
+}
+
+impl core::error::Error for TrieError {}
+
+const BLINDED: &str = "blinded trie node on the path";
+
+/// Trie node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Node {
+    /// Empty node.
+    #[default]
+    Empty,
+    /// Blinded node, only the hash of its encoding is known.
+    Hash(B256),
+    /// Leaf node with the remaining key nibbles.
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+        cache: Option<Vec<u8>>,
+    },
+    /// Extension node with the shared key nibbles.
+    Extension {
+        key: Vec<u8>,
+        child: Box<Node>,
+        cache: Option<Vec<u8>>,
+    },
+    /// Branch node with a child for every nibble.
+    Branch {
+        children: Box<[Node; 16]>,
+        value: Option<Vec<u8>>,
+        cache: Option<Vec<u8>>,
+    },
+}
+
+impl Node {
+    fn leaf(key: &[u8], value: Vec<u8>) -> Self {
+        Self::Leaf {
+            key: key.to_vec(),
+            value,
+            cache: None,
+        }
+    }
+
+    /// Creates an extension node if the key is not empty, otherwise returns the child.
+    fn extension(key: &[u8], child: Node) -> Self {
+        if key.is_empty() {
+            return child;
+        }
+        Self::Extension {
+            key: key.to_vec(),
+            child: Box::new(child),
+            cache: None,
+        }
+    }
+
+    fn empty_branch() -> Self {
+        Self::Branch {
+            children: Box::default(),
+            value: None,
+            cache: None,
+        }
+    }
+
+    /// Decodes the RLP encoded node, embedded children are decoded as well.
+    fn decode(encoded: &[u8]) -> Option<Self> {
+        let items = rlp_items(encoded)?;
+        match items.as_slice() {
+            [key, value] => {
+                let (key, is_leaf) = decode_hex_prefix(rlp_string(key)?)?;
+                if is_leaf {
+                    Some(Self::leaf(&key, rlp_string(value)?.to_vec()))
+                } else if key.is_empty() {
+                    None
+                } else {
+                    Some(Self::extension(&key, Self::decode_child(value)?))
+                }
+            }
+            [children @ .., value] if children.len() == 16 => {
+                let mut decoded: Box<[Node; 16]> = Box::default();
+                for (slot, child) in decoded.iter_mut().zip(children) {
+                    *slot = Self::decode_child(child)?;
+                }
+                let value = rlp_string(value)?;
+                Some(Self::Branch {
+                    children: decoded,
+                    value: (!value.is_empty()).then(|| value.to_vec()),
+                    cache: None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the child reference, either a hash or an embedded node.
+    fn decode_child(reference: &[u8]) -> Option<Self> {
+        if reference == [EMPTY_STRING_CODE] {
+            return Some(Self::Empty);
+        }
+        let mut node = if reference.first()? >= &alloy_rlp::EMPTY_LIST_CODE {
+            Self::decode(reference)?
+        } else {
+            let hash = rlp_string(reference)?;
+            return (hash.len() == 32).then(|| Self::Hash(B256::from_slice(hash)));
+        };
+        node.set_cache(reference.to_vec());
+        Some(node)
+    }
+
+    fn set_cache(&mut self, reference: Vec<u8>) {
+        if let Self::Leaf { cache, .. }
+        | Self::Extension { cache, .. }
+        | Self::Branch { cache, .. } = self
+        {
+            *cache = Some(reference);
+        }
+    }
+
+    /// Replaces the blinded node with the node from the proof.
+    ///
+    /// Returns the number of values that became visible.
+    fn reveal_node(&mut self, nodes: &ProofNodes) -> Result<usize, TrieError> {
+        let Self::Hash(hash) = *self else {
+            return Ok(0);
+        };
+        let encoded = nodes.get(&hash).ok_or(TrieError::MissingNode(hash))?;
+        let mut node = Self::decode(encoded).ok_or(TrieError::InvalidNode(hash))?;
+        let mut reference = Vec::with_capacity(33);
+        hash.encode(&mut reference);
+        node.set_cache(reference);
+        *self = node;
+        Ok(self.count_values())
+    }
+
+    /// Counts the values of the node that are not blinded.
+    fn count_values(&self) -> usize {
+        match self {
+            Self::Empty | Self::Hash(_) => 0,
+            Self::Leaf { .. } => 1,
+            Self::Extension { child, .. } => child.count_values(),
+            Self::Branch {
+                children, value, ..
+            } => children.iter().map(Self::count_values).sum::<usize>() + value.is_some() as usize,
+        }
+    }
+
+    /// Reveals the nodes on the path.
+    ///
+    /// For removal, the other child of a branch that would be left with a single entry is
+    /// revealed too, as the branch collapses into it.
+    fn reveal_path(
+        &mut self,
+        path: &[u8],
+        nodes: &ProofNodes,
+        removal: bool,
+    ) -> Result<usize, TrieError> {
+        let mut revealed = self.reveal_node(nodes)?;
+        match self {
+            Self::Empty | Self::Leaf { .. } => {}
+            Self::Hash(_) => unreachable!("node is revealed"),
+            Self::Extension { key, child, .. } => {
+                if let Some(rest) = path.strip_prefix(key.as_slice()) {
+                    revealed += child.reveal_path(rest, nodes, removal)?;
+                }
+            }
+            Self::Branch {
+                children, value, ..
+            } => {
+                let removed_child = match path.split_first() {
+                    None => None,
+                    Some((nibble, rest)) => {
+                        let child = &mut children[*nibble as usize];
+                        revealed += child.reveal_path(rest, nodes, removal)?;
+                        if !matches!(child, Self::Leaf { key, .. } if key == rest) {
+                            return Ok(revealed);
+                        }
+                        Some(*nibble as usize)
+                    }
+                };
+                if removal && (removed_child.is_some() || value.is_some()) {
+                    let mut remaining = children
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(nibble, child)| {
+                            Some(*nibble) != removed_child && !matches!(child, Self::Empty)
+                        })
+                        .map(|(_, child)| child);
+                    let remaining_value = value.is_some() && removed_child.is_some();
+                    if let (Some(child), None, false) =
+                        (remaining.next(), remaining.next(), remaining_value)
+                    {
+                        revealed += child.reveal_node(nodes)?;
+                    }
+                }
+            }
+        }
+        Ok(revealed)
+    }
+
+    /// Appends the encoded nodes on the path to the proof.
+    fn proof(&mut self, path: &[u8], is_root: bool, proof: &mut Vec<Bytes>) {
+        if matches!(self, Self::Empty | Self::Hash(_)) {
+            return;
+        }
+        let encoded = self.encode();
+        if is_root || encoded.len() >= 32 {
+            proof.push(encoded.into());
+        }
+        match self {
+            Self::Extension { key, child, .. } => {
+                if let Some(rest) = path.strip_prefix(key.as_slice()) {
+                    child.proof(rest, false, proof);
+                }
+            }
+            Self::Branch { children, .. } => {
+                if let Some((nibble, rest)) = path.split_first() {
+                    children[*nibble as usize].proof(rest, false, proof);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get(&self, path: &[u8]) -> Option<&[u8]> {
+        match self {
+            Self::Empty => None,
+            Self::Hash(_) => panic!("{BLINDED}"),
+            Self::Leaf { key, value, .. } => (key == path).then_some(value.as_slice()),
+            Self::Extension { key, child, .. } => path
+                .strip_prefix(key.as_slice())
+                .and_then(|rest| child.get(rest)),
+            Self::Branch {
+                children, value, ..
+            } => match path.split_first() {
+                None => value.as_deref(),
+                Some((nibble, rest)) => children[*nibble as usize].get(rest),
+            },
+        }
+    }
+
+    /// Inserts the value and returns the new node and `true` if the key was not present.
+    fn insert(self, path: &[u8], new_value: Vec<u8>) -> (Self, bool) {
+        match self {
+            Self::Empty => (Self::leaf(path, new_value), true),
+            Self::Hash(_) => panic!("{BLINDED}"),
+            Self::Leaf { key, value, .. } => {
+                if key == path {
+                    return (Self::leaf(path, new_value), false);
+                }
+                let common = common_prefix(&key, path);
+                let branch = Self::empty_branch()
+                    .insert_into_branch(&key[common..], Self::leaf(&[], value))
+                    .insert_into_branch(&path[common..], Self::leaf(&[], new_value));
+                (Self::extension(&key[..common], branch), true)
+            }
+            Self::Extension { key, child, .. } => {
+                let common = common_prefix(&key, path);
+                if common == key.len() {
+                    let (child, inserted) = child.insert(&path[common..], new_value);
+                    return (Self::extension(&key, child), inserted);
+                }
+                let branch = Self::empty_branch()
+                    .insert_into_branch(
+                        &key[common..common + 1],
+                        Self::extension(&key[common + 1..], *child),
+                    )
+                    .insert_into_branch(&path[common..], Self::leaf(&[], new_value));
+                (Self::extension(&key[..common], branch), true)
+            }
+            Self::Branch {
+                mut children,
+                mut value,
+                ..
+            } => {
+                let inserted = match path.split_first() {
+                    None => value.replace(new_value).is_none(),
+                    Some((nibble, rest)) => {
+                        let slot = &mut children[*nibble as usize];
+                        let (child, inserted) = mem::take(slot).insert(rest, new_value);
+                        *slot = child;
+                        inserted
+                    }
+                };
+                let node = Self::Branch {
+                    children,
+                    value,
+                    cache: None,
+                };
+                (node, inserted)
+            }
+        }
+    }
+
+    /// Places `node` into the branch under `path`.
+    ///
+    /// The first nibble of the path selects the child, the rest is prepended to the node key.
+    /// Leaf with an empty path becomes the branch value.
+    fn insert_into_branch(self, path: &[u8], node: Node) -> Self {
+        let Self::Branch {
+            mut children,
+            mut value,
+            ..
+        } = self
+        else {
+            unreachable!("node is a branch")
+        };
+        match path.split_first() {
+            None => {
+                let Self::Leaf { value: v, .. } = node else {
+                    unreachable!("only leaf can be stored as branch value")
+                };
+                value = Some(v);
+            }
+            Some((nibble, rest)) => {
+                children[*nibble as usize] = node.with_prefix(rest);
+            }
+        }
+        Self::Branch {
+            children,
+            value,
+            cache: None,
+        }
+    }
+
+    /// Prepends the nibbles to the key of the node.
+    fn with_prefix(self, prefix: &[u8]) -> Self {
+        if prefix.is_empty() {
+            return self;
+        }
+        match self {
+            Self::Leaf { key, value, .. } => Self::leaf(&[prefix, &key].concat(), value),
+            Self::Extension { key, child, .. } => Self::extension(&[prefix, &key].concat(), *child),
+            node @ Self::Branch { .. } => Self::extension(prefix, node),
+            Self::Empty => Self::Empty,
+            Self::Hash(_) => panic!("{BLINDED}"),
+        }
+    }
+
+    /// Removes the key and returns the new node and `true` if the key was present.
+    fn remove(self, path: &[u8]) -> (Self, bool) {
+        match self {
+            Self::Empty => (Self::Empty, false),
+            Self::Hash(_) => panic!("{BLINDED}"),
+            Self::Leaf { ref key, .. } => {
+                if key == path {
+                    (Self::Empty, true)
+                } else {
+                    (self, false)
+                }
+            }
+            Self::Extension { key, child, cache } => {
+                let Some(rest) = path.strip_prefix(key.as_slice()) else {
+                    return (Self::Extension { key, child, cache }, false);
+                };
+                let (child, removed) = child.remove(rest);
+                if !removed {
+                    return (
+                        Self::Extension {
+                            key,
+                            child: Box::new(child),
+                            cache,
+                        },
+                        false,
+                    );
+                }
+                (child.with_prefix(&key), true)
+            }
+            Self::Branch {
+                mut children,
+                mut value,
+                cache,
+            } => {
+                let removed = match path.split_first() {
+                    None => value.take().is_some(),
+                    Some((nibble, rest)) => {
+                        let slot = &mut children[*nibble as usize];
+                        let (child, removed) = mem::take(slot).remove(rest);
+                        *slot = child;
+                        removed
+                    }
+                };
+                if !removed {
+                    let node = Self::Branch {
+                        children,
+                        value,
+                        cache,
+                    };
+                    return (node, false);
+                }
+                (Self::normalize_branch(children, value), true)
+            }
+        }
+    }
+
+    /// Collapses the branch if it has less than two entries left.
+    fn normalize_branch(mut children: Box<[Node; 16]>, value: Option<Vec<u8>>) -> Self {
+        let mut occupied = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| !matches!(child, Self::Empty))
+            .map(|(nibble, _)| nibble);
+        let first = occupied.next();
+        let entries = occupied.count() + first.is_some() as usize + value.is_some() as usize;
+        if entries >= 2 {
+            return Self::Branch {
+                children,
+                value,
+                cache: None,
+            };
+        }
+        match (first, value) {
+            (None, Some(value)) => Self::leaf(&[], value),
+            (Some(nibble), None) => mem::take(&mut children[nibble]).with_prefix(&[nibble as u8]),
+            _ => Self::Empty,
+        }
+    }
+
+    /// Returns the RLP reference of the node.
+    ///
+    /// It is the RLP encoding of the node if it is shorter than 32 bytes, otherwise RLP
+    /// encoded hash of the encoding.
+    fn reference(&mut self) -> Vec<u8> {
+        let cache = match self {
+            Self::Empty => return vec![EMPTY_STRING_CODE],
+            Self::Hash(hash) => {
+                let mut reference = Vec::with_capacity(33);
+                hash.encode(&mut reference);
+                return reference;
+            }
+            Self::Leaf { cache, .. }
+            | Self::Extension { cache, .. }
+            | Self::Branch { cache, .. } => cache,
+        };
+        if let Some(reference) = cache {
+            return reference.clone();
+        }
+
+        let encoded = self.encode();
+        let reference = if encoded.len() < 32 {
+            encoded
+        } else {
+            let mut reference = Vec::with_capacity(33);
+            keccak256(&encoded).encode(&mut reference);
+            reference
+        };
+        if let Self::Leaf { cache, .. }
+        | Self::Extension { cache, .. }
+        | Self::Branch { cache, .. } = self
+        {
+            *cache = Some(reference.clone());
+        }
+        reference
+    }
+
+    /// RLP encodes the node.
+    fn encode(&mut self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Self::Empty => return vec![EMPTY_STRING_CODE],
+            Self::Hash(_) => unreachable!("blinded node is referenced by its hash"),
+            Self::Leaf { key, value, .. } => {
+                hex_prefix(key, true).as_slice().encode(&mut payload);
+                value.as_slice().encode(&mut payload);
+            }
+            Self::Extension { key, child, .. } => {
+                hex_prefix(key, false).as_slice().encode(&mut payload);
+                payload.extend_from_slice(&child.reference());
+            }
+            Self::Branch {
+                children, value, ..
+            } => {
+                for child in children.iter_mut() {
+                    payload.extend_from_slice(&child.reference());
+                }
+                match value {
+                    Some(value) => value.as_slice().encode(&mut payload),
+                    None => payload.push(EMPTY_STRING_CODE),
+                }
+            }
+        }
+        let mut out = Vec::with_capacity(payload.len() + 3);
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend_from_slice(&payload);
+        out
+    }
+}
+
+/// Splits bytes into nibbles.
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Returns the length of the common prefix.
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Hex-prefix encoding of the nibbles as defined in the Yellow Paper appendix C.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let odd = nibbles.len() % 2 == 1;
+    let flag = ((is_leaf as u8) << 1 | odd as u8) << 4;
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if odd {
+        out.push(flag | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+/// Decodes hex-prefix encoded nibbles and returns them with the leaf flag.
+fn decode_hex_prefix(encoded: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let (first, rest) = encoded.split_first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(rest));
+    Some((nibbles, flag & 2 == 2))
+}
+
+/// Splits the RLP list into its raw encoded items.
+fn rlp_items(mut encoded: &[u8]) -> Option<Vec<&[u8]>> {
+    let header = Header::decode(&mut encoded).ok()?;
+    if !header.list || header.payload_length != encoded.len() {
+        return None;
+    }
+    let mut items = Vec::with_capacity(17);
+    while !encoded.is_empty() {
+        let mut rest = encoded;
+        let item = Header::decode(&mut rest).ok()?;
+        let length = encoded.len() - rest.len() + item.payload_length;
+        if length > encoded.len() {
+            return None;
+        }
+        let (raw, rest) = encoded.split_at(length);
+        items.push(raw);
+        encoded = rest;
+    }
+    Some(items)
+}
+
+/// Returns the payload of the RLP string.
+fn rlp_string(mut encoded: &[u8]) -> Option<&[u8]> {
+    let header = Header::decode(&mut encoded).ok()?;
+    (!header.list && header.payload_length == encoded.len()).then_some(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::b256;
+
+    #[test]
+    fn empty_root() {
+        assert_eq!(MerklePatriciaTrie::new().root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn known_root() {
+        let mut trie = MerklePatriciaTrie::new();
+        trie.insert(b"doe", b"reindeer".to_vec());
+        trie.insert(b"dog", b"puppy".to_vec());
+        trie.insert(b"dogglesworth", b"cat".to_vec());
+        assert_eq!(
+            trie.root(),
+            b256!("0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+        );
+        assert_eq!(trie.get(b"dog"), Some(&b"puppy"[..]));
+        assert_eq!(trie.get(b"do"), None);
+    }
+
+    #[test]
+    fn remove_restores_root() {
+        let mut trie = MerklePatriciaTrie::new();
+        let keys: Vec<B256> = (0u64..64).map(|i| keccak256(i.to_be_bytes())).collect();
+        for key in &keys[..32] {
+            trie.insert(key.as_slice(), key[..8].to_vec());
+        }
+        let root = trie.root();
+
+        for key in &keys[32..] {
+            trie.insert(key.as_slice(), key[..8].to_vec());
+        }
+        assert_ne!(trie.root(), root);
+        for key in &keys[32..] {
+            assert!(trie.remove(key.as_slice()));
+        }
+        assert_eq!(trie.len(), 32);
+        assert_eq!(trie.root(), root);
+
+        // Trie built in a different order has the same root.
+        let mut reversed = MerklePatriciaTrie::new();
+        for key in keys[..32].iter().rev() {
+            reversed.insert(key.as_slice(), key[..8].to_vec());
+        }
+        assert_eq!(reversed.root(), root);
+
+        for key in &keys[..32] {
+            trie.remove(key.as_slice());
+        }
+        assert!(trie.is_empty());
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn proven_access_on_blinded_trie() {
+        let mut full = MerklePatriciaTrie::new();
+        let keys: Vec<B256> = (0u64..64).map(|i| keccak256(i.to_be_bytes())).collect();
+        for key in &keys {
+            full.insert(key.as_slice(), key[..8].to_vec());
+        }
+        let root = full.root();
+
+        let nodes = ProofNodes::new(full.proof(keys[0].as_slice()));
+        let mut blinded = MerklePatriciaTrie::from_root(root);
+        assert_eq!(blinded.root(), root);
+        assert_eq!(
+            blinded.get_proven(keys[0].as_slice(), &nodes),
+            Ok(Some(&keys[0][..8]))
+        );
+        assert!(matches!(
+            blinded.get_proven(keys[1].as_slice(), &nodes),
+            Err(TrieError::MissingNode(_))
+        ));
+
+        // Absent key is proven by the nodes on its path.
+        let absent = keccak256(b"absent");
+        let nodes = ProofNodes::new(full.proof(absent.as_slice()));
+        let mut blinded = MerklePatriciaTrie::from_root(root);
+        assert_eq!(blinded.get_proven(absent.as_slice(), &nodes), Ok(None));
+
+        // Updates of the revealed paths produce the same root as the full trie.
+        let mut nodes = ProofNodes::new(full.proof(absent.as_slice()));
+        for key in &keys[..4] {
+            nodes.extend(full.proof(key.as_slice()));
+        }
+        let mut blinded = MerklePatriciaTrie::from_root(root);
+        for key in &keys[..4] {
+            full.insert(key.as_slice(), vec![1]);
+            blinded
+                .insert_proven(key.as_slice(), vec![1], &nodes)
+                .unwrap();
+        }
+        full.insert(absent.as_slice(), vec![2]);
+        blinded
+            .insert_proven(absent.as_slice(), vec![2], &nodes)
+            .unwrap();
+        assert_eq!(blinded.root(), full.root());
+    }
+
+    #[test]
+    fn removal_reveals_remaining_child() {
+        let mut full = MerklePatriciaTrie::new();
+        full.insert(&[0x10], vec![1; 32]);
+        full.insert(&[0x20], vec![2; 32]);
+        let root = full.root();
+
+        let mut nodes = ProofNodes::new(full.proof(&[0x10]));
+        let mut blinded = MerklePatriciaTrie::from_root(root);
+        assert!(matches!(
+            blinded.remove_proven(&[0x10], &nodes),
+            Err(TrieError::MissingNode(_))
+        ));
+        assert_eq!(blinded.root(), root);
+
+        nodes.extend(full.proof(&[0x20]));
+        assert_eq!(blinded.remove_proven(&[0x10], &nodes), Ok(true));
+        full.remove(&[0x10]);
+        assert_eq!(blinded.root(), full.root());
+        assert_eq!(blinded.get(&[0x20]), Some(&[2; 32][..]));
+    }
+}
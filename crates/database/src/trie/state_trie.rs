@@ -0,0 +1,322 @@
+//! Persistent account and storage tries updated from state changes.
+use super::{MerklePatriciaTrie, EMPTY_ROOT_HASH};
+use crate::states::{BundleState, OriginalValuesKnown, PlainAccount, StateChangeset};
+use alloy_rlp::{Encodable, Header};
+use primitives::{
+    keccak256, Address, Bytes, HashMap, HashSet, StorageKey, StorageValue, B256, U256,
+};
+use state::AccountInfo;
+use std::vec::Vec;
+
+/// Account fields that are part of the account trie leaf.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TrieAccountInfo {
+    pub(crate) nonce: u64,
+    pub(crate) balance: U256,
+    pub(crate) code_hash: B256,
+}
+
+impl From<&AccountInfo> for TrieAccountInfo {
+    fn from(info: &AccountInfo) -> Self {
+        Self {
+            nonce: info.nonce,
+            balance: info.balance,
+            code_hash: info.code_hash,
+        }
+    }
+}
+
+/// State trie that keeps the account trie and all storage tries in memory.
+///
+/// Tries are keyed by `keccak256` of the address and storage slot. After the initial state is
+/// inserted, changes are applied from a [`StateChangeset`] or a [`BundleState`] and only the
+/// modified paths are rehashed when calculating the [`StateTrie::root`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateTrie {
+    /// Account trie.
+    accounts: MerklePatriciaTrie,
+    /// Storage trie of every account with non empty storage.
+    storages: HashMap<Address, MerklePatriciaTrie>,
+    /// Account info needed to re-encode the account leaf when only storage changes.
+    infos: HashMap<Address, TrieAccountInfo>,
+    /// Accounts whose leaf needs to be updated before calculating the root.
+    pending: HashSet<Address>,
+}
+
+impl StateTrie {
+    /// Creates a new empty state trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the state trie from plain accounts.
+    pub fn from_plain_accounts<'a>(
+        accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+    ) -> Self {
+        let mut trie = Self::new();
+        for (address, account) in accounts {
+            trie.insert_account(
+                address,
+                &account.info,
+                account.storage.iter().map(|(k, v)| (*k, *v)),
+            );
+        }
+        trie
+    }
+
+    /// Inserts or replaces the account together with its storage.
+    pub fn insert_account(
+        &mut self,
+        address: Address,
+        info: &AccountInfo,
+        storage: impl IntoIterator<Item = (StorageKey, StorageValue)>,
+    ) {
+        let storage_trie = self.storages.entry(address).or_default();
+        storage_trie.clear();
+        for (key, value) in storage {
+            insert_slot(storage_trie, key, value);
+        }
+        self.infos.insert(address, info.into());
+        self.pending.insert(address);
+    }
+
+    /// Removes the account and its storage.
+    pub fn remove_account(&mut self, address: Address) {
+        self.storages.remove(&address);
+        self.infos.remove(&address);
+        self.pending.insert(address);
+    }
+
+    /// Applies the changeset created by [`BundleState::to_plain_state`].
+    pub fn apply_changeset(&mut self, changeset: &StateChangeset) {
+        for storage in &changeset.storage {
+            let storage_trie = self.storages.entry(storage.address).or_default();
+            if storage.wipe_storage {
+                storage_trie.clear();
+            }
+            for (key, value) in &storage.storage {
+                insert_slot(storage_trie, *key, *value);
+            }
+            self.pending.insert(storage.address);
+        }
+
+        for (address, info) in &changeset.accounts {
+            match info {
+                Some(info) => {
+                    self.infos.insert(*address, info.into());
+                    self.pending.insert(*address);
+                }
+                None => self.remove_account(*address),
+            }
+        }
+    }
+
+    /// Applies all changes of the bundle.
+    ///
+    /// Bundle can be taken after every block with [`crate::State::take_bundle`], or it can
+    /// be kept for multiple blocks as reapplying the same changes is a no-op.
+    pub fn apply_bundle(&mut self, bundle: &BundleState) {
+        self.apply_changeset(&bundle.to_plain_state(OriginalValuesKnown::Yes));
+    }
+
+    /// Calculates the storage root of the account.
+    pub fn storage_root(&mut self, address: Address) -> B256 {
+        self.storages
+            .get_mut(&address)
+            .map(MerklePatriciaTrie::root)
+            .unwrap_or(EMPTY_ROOT_HASH)
+    }
+
+    /// Calculates the state root.
+    pub fn root(&mut self) -> B256 {
+        let pending: Vec<Address> = self.pending.drain().collect();
+        for address in pending {
+            let key = keccak256(address);
+            let Some(info) = self.infos.get(&address).copied() else {
+                self.accounts.remove(key.as_slice());
+                continue;
+            };
+            let storage_root = match self.storages.get_mut(&address) {
+                Some(storage) if storage.is_empty() => {
+                    self.storages.remove(&address);
+                    EMPTY_ROOT_HASH
+                }
+                Some(storage) => storage.root(),
+                None => EMPTY_ROOT_HASH,
+            };
+            self.accounts
+                .insert(key.as_slice(), encode_account(&info, storage_root));
+        }
+        self.accounts.root()
+    }
+
+    /// Returns the proof of the account, see [`MerklePatriciaTrie::proof`].
+    pub fn account_proof(&mut self, address: Address) -> Vec<Bytes> {
+        self.root();
+        self.accounts.proof(keccak256(address).as_slice())
+    }
+
+    /// Returns the proof of the storage slot in the storage trie of the account.
+    pub fn storage_proof(&mut self, address: Address, slot: StorageKey) -> Vec<Bytes> {
+        self.root();
+        self.storages
+            .get_mut(&address)
+            .map(|storage| storage.proof(keccak256(slot.to_be_bytes::<32>()).as_slice()))
+            .unwrap_or_default()
+    }
+}
+
+/// Inserts the RLP encoded slot value, zero value removes the slot.
+fn insert_slot(trie: &mut MerklePatriciaTrie, key: StorageKey, value: StorageValue) {
+    let key = keccak256(key.to_be_bytes::<32>());
+    trie.insert(key.as_slice(), encode_slot(value));
+}
+
+/// RLP encodes the slot value, zero value is encoded as empty value.
+pub(crate) fn encode_slot(value: StorageValue) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    if !value.is_zero() {
+        value.encode(&mut encoded);
+    }
+    encoded
+}
+
+/// RLP encodes the account as `[nonce, balance, storage_root, code_hash]`.
+pub(crate) fn encode_account(info: &TrieAccountInfo, storage_root: B256) -> Vec<u8> {
+    let payload_length = info.nonce.length()
+        + info.balance.length()
+        + storage_root.length()
+        + info.code_hash.length();
+    let mut out = Vec::with_capacity(payload_length + 2);
+    Header {
+        list: true,
+        payload_length,
+    }
+    .encode(&mut out);
+    info.nonce.encode(&mut out);
+    info.balance.encode(&mut out);
+    storage_root.encode(&mut out);
+    info.code_hash.encode(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{states::bundle_state::BundleRetention, InMemoryDB, State};
+    use database_interface::{Database, DatabaseCommit};
+    use primitives::{address, b256, KECCAK_EMPTY};
+    use state::{Account, AccountStatus, EvmStorageSlot};
+
+    fn plain_root(accounts: &HashMap<Address, PlainAccount>) -> B256 {
+        StateTrie::from_plain_accounts(accounts.iter().map(|(a, acc)| (*a, acc))).root()
+    }
+
+    #[test]
+    fn empty_state_root() {
+        assert_eq!(
+            StateTrie::new().root(),
+            b256!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+        );
+    }
+
+    #[test]
+    fn known_state_root() {
+        // Genesis state root of the ethereum/tests blockchain tests that fund only the
+        // `0xa94f…` sender with `0x09184e72a000` wei.
+        let mut accounts: HashMap<Address, PlainAccount> = HashMap::default();
+        accounts.insert(
+            address!("0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"),
+            PlainAccount {
+                info: AccountInfo::from_balance(U256::from(0x09184e72a000u64)),
+                storage: Default::default(),
+            },
+        );
+        assert_eq!(
+            plain_root(&accounts),
+            b256!("0x7dba07d6b448a186e9612e5f737d1c909dce473e53199901a302c00646d523c1")
+        );
+    }
+
+    #[test]
+    fn incremental_root_matches_full_rebuild() {
+        let a = address!("0x1000000000000000000000000000000000000001");
+        let b = address!("0x2000000000000000000000000000000000000002");
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(a, AccountInfo::from_balance(U256::from(10)));
+        db.insert_account_info(b, AccountInfo::from_balance(U256::from(20)));
+        db.insert_account_storage(b, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let mut expected: HashMap<Address, PlainAccount> = HashMap::default();
+        expected.insert(
+            a,
+            PlainAccount {
+                info: AccountInfo::from_balance(U256::from(10)),
+                storage: Default::default(),
+            },
+        );
+        let mut b_storage = HashMap::default();
+        b_storage.insert(U256::from(1), U256::from(1));
+        expected.insert(
+            b,
+            PlainAccount {
+                info: AccountInfo::from_balance(U256::from(20)),
+                storage: b_storage,
+            },
+        );
+        let mut trie = StateTrie::from_plain_accounts(expected.iter().map(|(k, v)| (*k, v)));
+        assert_eq!(trie.root(), plain_root(&expected));
+
+        let mut state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        let a_info = state.basic(a).unwrap().unwrap();
+        let b_info = state.basic(b).unwrap().unwrap();
+
+        // Clear slot 1, write slot 2 of `b` and destroy `a`.
+        let mut changes: primitives::HashMap<Address, Account> = Default::default();
+        changes.insert(
+            b,
+            Account {
+                info: AccountInfo { nonce: 1, ..b_info },
+                storage: [
+                    (
+                        U256::from(1),
+                        EvmStorageSlot::new_changed(U256::from(1), U256::ZERO),
+                    ),
+                    (
+                        U256::from(2),
+                        EvmStorageSlot::new_changed(U256::ZERO, U256::from(5)),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                status: AccountStatus::Touched,
+            },
+        );
+        changes.insert(
+            a,
+            Account {
+                info: a_info,
+                storage: Default::default(),
+                status: AccountStatus::Touched | AccountStatus::SelfDestructed,
+            },
+        );
+        state.commit(changes);
+        state.merge_transitions(BundleRetention::PlainState);
+        trie.apply_bundle(&state.take_bundle());
+
+        expected.remove(&a);
+        let b_account = expected.get_mut(&b).unwrap();
+        b_account.info.nonce = 1;
+        b_account.storage.clear();
+        b_account.storage.insert(U256::from(2), U256::from(5));
+        assert_eq!(b_account.info.code_hash, KECCAK_EMPTY);
+        assert_eq!(trie.root(), plain_root(&expected));
+        assert_eq!(trie.storage_root(a), EMPTY_ROOT_HASH);
+        assert_ne!(trie.storage_root(b), EMPTY_ROOT_HASH);
+    }
+}
@@ -0,0 +1,326 @@
+//! Execution witness recording and replay.
+//!
+//! [`RecordingDB`] wraps any [`Database`] and records every value it returns. The recorded
+//! [`ExecutionWitness`] contains the minimal state needed to re-execute the same block and can
+//! be served by [`WitnessDB`], for example inside a zkVM guest program.
+use core::fmt;
+use database_interface::{DBErrorMarker, Database, DatabaseRef};
+use primitives::{Address, HashMap, StorageKey, StorageValue, B256, KECCAK_EMPTY};
+use state::{AccountInfo, Bytecode};
+
+/// State accessed during execution.
+///
+/// Values are the ones returned by the database on the first lookup, which is the state
+/// before the execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// Accounts without code, `None` if the account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots of the accounts.
+    pub storage: HashMap<Address, HashMap<StorageKey, StorageValue>>,
+    /// Bytecodes by their hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl ExecutionWitness {
+    /// Merges the other witness into this one.
+    ///
+    /// Values already present in `self` are kept, as they were recorded first.
+    pub fn extend(&mut self, other: ExecutionWitness) {
+        for (address, info) in other.accounts {
+            self.accounts.entry(address).or_insert(info);
+        }
+        for (address, storage) in other.storage {
+            let slots = self.storage.entry(address).or_default();
+            for (index, value) in storage {
+                slots.entry(index).or_insert(value);
+            }
+        }
+        for (hash, code) in other.contracts {
+            self.contracts.entry(hash).or_insert(code);
+        }
+        for (number, hash) in other.block_hashes {
+            self.block_hashes.entry(number).or_insert(hash);
+        }
+    }
+}
+
+/// Database wrapper that records all lookups into an [`ExecutionWitness`].
+#[derive(Clone, Debug, Default)]
+pub struct RecordingDB<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    /// Recorded witness.
+    pub witness: ExecutionWitness,
+}
+
+impl<DB> RecordingDB<DB> {
+    /// Creates a new recording database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: ExecutionWitness::default(),
+        }
+    }
+
+    /// Returns the recorded witness.
+    pub fn witness(&self) -> &ExecutionWitness {
+        &self.witness
+    }
+
+    /// Consumes the wrapper and returns the recorded witness.
+    pub fn into_witness(self) -> ExecutionWitness {
+        self.witness
+    }
+
+    /// Consumes the wrapper and returns the database and the recorded witness.
+    pub fn into_parts(self) -> (DB, ExecutionWitness) {
+        (self.db, self.witness)
+    }
+}
+
+impl<DB: Database> Database for RecordingDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        if let Some(code) = info.as_ref().and_then(|info| info.code.clone()) {
+            let code_hash = info.as_ref().unwrap().code_hash;
+            self.witness.contracts.entry(code_hash).or_insert(code);
+        }
+        self.witness
+            .accounts
+            .entry(address)
+            .or_insert_with(|| info.as_ref().map(AccountInfo::copy_without_code));
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness
+            .contracts
+            .entry(code_hash)
+            .or_insert_with(|| code.clone());
+        Ok(code)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.entry(number).or_insert(hash);
+        Ok(hash)
+    }
+}
+
+/// Lookup that is not part of the witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessError {
+    /// Account was not recorded.
+    MissingAccount(Address),
+    /// Storage slot was not recorded.
+    MissingStorage(Address, StorageKey),
+    /// Bytecode was not recorded.
+    MissingCode(B256),
+    /// Block hash was not recorded.
+    MissingBlockHash(u64),
+}
+
+impl DBErrorMarker for WitnessError {}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => write!(f, "account {address} is not in the witness"),
+            Self::MissingStorage(address, index) => {
+                write!(f, "storage {index} of {address} is not in the witness")
+            }
+            Self::MissingCode(hash) => write!(f, "bytecode {hash} is not in the witness"),
+            Self::MissingBlockHash(number) => {
+                write!(f, "block hash {number} is not in the witness")
+            }
+        }
+    }
+}
+
+impl core::error::Error for WitnessError {}
+
+/// Database that serves only the values recorded in the [`ExecutionWitness`].
+///
+/// Any lookup that was not recorded fails with [`WitnessError`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WitnessDB {
+    /// Witness the values are served from.
+    pub witness: ExecutionWitness,
+}
+
+impl WitnessDB {
+    /// Creates a new database from the witness.
+    pub fn new(witness: ExecutionWitness) -> Self {
+        Self { witness }
+    }
+}
+
+impl From<ExecutionWitness> for WitnessDB {
+    fn from(witness: ExecutionWitness) -> Self {
+        Self::new(witness)
+    }
+}
+
+impl DatabaseRef for WitnessDB {
+    type Error = WitnessError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self
+            .witness
+            .accounts
+            .get(&address)
+            .ok_or(WitnessError::MissingAccount(address))?;
+        Ok(info.clone().map(|mut info| {
+            info.code = self.witness.contracts.get(&info.code_hash).cloned();
+            info
+        }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.witness
+            .contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(WitnessError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.witness
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+            .copied()
+            .ok_or(WitnessError::MissingStorage(address, index))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.witness
+            .block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(WitnessError::MissingBlockHash(number))
+    }
+}
+
+impl Database for WitnessDB {
+    type Error = WitnessError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use primitives::{address, bytes, U256};
+
+    #[test]
+    fn record_and_replay() {
+        let a = address!("0x1000000000000000000000000000000000000001");
+        let missing = address!("0x2000000000000000000000000000000000000002");
+        let code = Bytecode::new_legacy(bytes!("0x6001600055"));
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(a, AccountInfo::default().with_code(code.clone()));
+        db.insert_account_storage(a, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let mut recording = RecordingDB::new(db);
+        let info = recording.basic(a).unwrap().unwrap();
+        assert_eq!(recording.basic(missing).unwrap(), None);
+        assert_eq!(recording.storage(a, U256::from(1)).unwrap(), U256::from(2));
+        assert_eq!(recording.storage(a, U256::from(3)).unwrap(), U256::ZERO);
+        let block_hash = recording.block_hash(7).unwrap();
+
+        let witness = recording.into_witness();
+        assert_eq!(witness.accounts.len(), 2);
+        assert_eq!(witness.accounts[&a].as_ref().unwrap().code, None);
+        assert_eq!(witness.contracts[&info.code_hash], code);
+        assert_eq!(witness.storage[&a].len(), 2);
+
+        let mut replay = WitnessDB::new(witness);
+        assert_eq!(replay.basic(a).unwrap(), Some(info.clone()));
+        assert_eq!(replay.basic(missing).unwrap(), None);
+        assert_eq!(replay.code_by_hash(info.code_hash).unwrap(), code);
+        assert_eq!(replay.storage(a, U256::from(3)).unwrap(), U256::ZERO);
+        assert_eq!(replay.block_hash(7).unwrap(), block_hash);
+
+        let other = address!("0x3000000000000000000000000000000000000003");
+        assert_eq!(
+            replay.basic(other),
+            Err(WitnessError::MissingAccount(other))
+        );
+        assert_eq!(
+            replay.storage(a, U256::from(4)),
+            Err(WitnessError::MissingStorage(a, U256::from(4)))
+        );
+        assert_eq!(replay.block_hash(8), Err(WitnessError::MissingBlockHash(8)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn witness_serde_roundtrip() {
+        let mut witness = ExecutionWitness::default();
+        witness.accounts.insert(
+            Address::with_last_byte(1),
+            Some(AccountInfo::from_balance(U256::from(1))),
+        );
+        witness.block_hashes.insert(1, B256::with_last_byte(1));
+
+        let serialized = serde_json::to_string(&witness).unwrap();
+        let deserialized: ExecutionWitness = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, witness);
+    }
+}
@@ -0,0 +1,1164 @@
+//! Block level execution built on top of [`ExecuteCommitEvm`] and [`SystemCallCommitEvm`].
+//!
+//! [`BlockExecutor`] runs the pre-block system calls, executes transactions in order while
+//! tracking the block gas, applies block rewards and withdrawals and finally runs the
+//! post-block system calls that produce [EIP-7685] requests.
+//!
+//! Every step is a trait method with a default mainnet implementation so chains can override
+//! only the parts that differ, similar to how [`crate::Handler`] is used for transactions.
+//!
+//! [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+use crate::{system_call::SystemCall, EvmTr, ExecuteCommitEvm, ExecuteEvm, SystemCallCommitEvm};
+use context_interface::{
+    result::{ExecutionResult, HaltReason, HaltReasonTr},
+    Block, Cfg, ContextTr, Database, JournalTr, Transaction,
+};
+use core::fmt;
+use primitives::{address, b256, hardfork::SpecId, Address, Bytes, Log, B256, U256};
+use state::{BlockAccessList, BlockAccessListError};
+use std::{
+    collections::{btree_map, BTreeMap},
+    vec,
+    vec::Vec,
+};
+
+pub use database_interface::BlockStateDb;
+
+/// Address of the [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788) beacon roots contract.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// Address of the [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935) history storage contract.
+pub const HISTORY_STORAGE_ADDRESS: Address = address!("0x0000F90827F1C53a10cb7A02335B175320002935");
+
+/// Address of the [EIP-7002](https://eips.ethereum.org/EIPS/eip-7002) withdrawal request contract.
+pub const WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS: Address =
+    address!("0x00000961Ef480Eb55e80D19ad83579A64c007002");
+
+/// Address of the [EIP-7251](https://eips.ethereum.org/EIPS/eip-7251) consolidation request contract.
+pub const CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS: Address =
+    address!("0x0000BBdDc7CE488642fb579F8B00f3a590007251");
+
+/// Address of the mainnet deposit contract used for [EIP-6110](https://eips.ethereum.org/EIPS/eip-6110) deposit requests.
+pub const MAINNET_DEPOSIT_CONTRACT_ADDRESS: Address =
+    address!("0x00000000219ab540356cBB839Cbe05303d7705Fa");
+
+/// Topic of the deposit contract `DepositEvent(bytes,bytes,bytes,bytes,bytes)` event.
+pub const DEPOSIT_EVENT_TOPIC: B256 =
+    b256!("0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5");
+
+/// [EIP-7685] request type of deposit requests.
+///
+/// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+pub const DEPOSIT_REQUEST_TYPE: u8 = 0x00;
+
+/// [EIP-7685] request type of withdrawal requests.
+///
+/// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+pub const WITHDRAWAL_REQUEST_TYPE: u8 = 0x01;
+
+/// [EIP-7685] request type of consolidation requests.
+///
+/// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+pub const CONSOLIDATION_REQUEST_TYPE: u8 = 0x02;
+
+/// Number of wei in one gwei. Withdrawal amounts are denominated in gwei.
+pub const GWEI_TO_WEI: u128 = 1_000_000_000;
+
+/// Validator withdrawal introduced in [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier issued by consensus layer.
+    pub index: u64,
+    /// Index of validator associated with withdrawal.
+    pub validator_index: u64,
+    /// Target address for withdrawn ether.
+    pub address: Address,
+    /// Value of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns the withdrawal amount in wei.
+    #[inline]
+    pub fn amount_wei(&self) -> u128 {
+        self.amount as u128 * GWEI_TO_WEI
+    }
+}
+
+/// Uncle block header data needed to calculate pre-merge block rewards.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ommer {
+    /// Beneficiary of the ommer block.
+    pub beneficiary: Address,
+    /// Number of the ommer block.
+    pub number: u64,
+}
+
+/// Input of the block execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockInput<BLOCK, TX> {
+    /// Block environment the transactions are executed in.
+    pub block: BLOCK,
+    /// Transactions in the order they are executed.
+    pub transactions: Vec<TX>,
+    /// Hash of the parent block.
+    ///
+    /// Made available to `BLOCKHASH` and stored by the [EIP-2935] system call from Prague.
+    ///
+    /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+    pub parent_hash: Option<B256>,
+    /// Parent beacon block root stored by the [EIP-4788] system call from Cancun.
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
+    /// Ommers of the block, rewarded before the merge.
+    pub ommers: Vec<Ommer>,
+    /// Withdrawals applied after all transactions, from Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// [EIP-7928] block access list validated against the execution.
+    ///
+    /// The block access list is built by the journal, see [`JournalTr::set_block_access_list`].
+    ///
+    /// [EIP-7928]: https://eips.ethereum.org/EIPS/eip-7928
+    pub block_access_list: Option<BlockAccessList>,
+    /// Builds the [EIP-7928] block access list even if none is validated.
+    ///
+    /// [EIP-7928]: https://eips.ethereum.org/EIPS/eip-7928
+    pub build_block_access_list: bool,
+}
+
+impl<BLOCK, TX> BlockInput<BLOCK, TX> {
+    /// Creates a new block input with the given block and transactions.
+    pub fn new(block: BLOCK, transactions: Vec<TX>) -> Self {
+        Self {
+            block,
+            transactions,
+            parent_hash: None,
+            parent_beacon_block_root: None,
+            ommers: Vec::new(),
+            withdrawals: None,
+            block_access_list: None,
+            build_block_access_list: false,
+        }
+    }
+
+    /// Sets the parent block hash.
+    pub fn with_parent_hash(mut self, parent_hash: B256) -> Self {
+        self.parent_hash = Some(parent_hash);
+        self
+    }
+
+    /// Sets the parent beacon block root.
+    pub fn with_parent_beacon_block_root(mut self, root: B256) -> Self {
+        self.parent_beacon_block_root = Some(root);
+        self
+    }
+
+    /// Sets the ommers of the block.
+    pub fn with_ommers(mut self, ommers: Vec<Ommer>) -> Self {
+        self.ommers = ommers;
+        self
+    }
+
+    /// Sets the withdrawals of the block.
+    pub fn with_withdrawals(mut self, withdrawals: Vec<Withdrawal>) -> Self {
+        self.withdrawals = Some(withdrawals);
+        self
+    }
+
+    /// Sets the block access list that is validated against the execution.
+    pub fn with_block_access_list(mut self, block_access_list: BlockAccessList) -> Self {
+        self.block_access_list = Some(block_access_list);
+        self
+    }
+
+    /// Builds the block access list and returns it in the [`BlockExecutionOutput`].
+    pub fn with_build_block_access_list(mut self) -> Self {
+        self.build_block_access_list = true;
+        self
+    }
+}
+
+/// Receipt of the executed transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// Type of the transaction.
+    pub tx_type: u8,
+    /// If transaction was successful.
+    pub success: bool,
+    /// Gas used by this and all previous transactions in the block.
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+}
+
+/// Transactions executed by [`BlockExecutor::execute_transactions`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutedTransactions<HaltReasonTy = HaltReason> {
+    /// Execution result of each transaction.
+    pub results: Vec<ExecutionResult<HaltReasonTy>>,
+    /// Receipt of each transaction.
+    pub receipts: Vec<Receipt>,
+    /// Gas used by all transactions.
+    pub gas_used: u64,
+    /// Blob gas used by all transactions.
+    pub blob_gas_used: u64,
+}
+
+/// Output of the block execution.
+///
+/// `Bundle` is the [`BlockStateDb::Bundle`] of the database, the `BundleState` for `State`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockExecutionOutput<Bundle, HaltReasonTy = HaltReason> {
+    /// Execution result of each transaction.
+    pub results: Vec<ExecutionResult<HaltReasonTy>>,
+    /// Receipt of each transaction.
+    pub receipts: Vec<Receipt>,
+    /// [EIP-7685] requests, each prefixed with its request type.
+    ///
+    /// Requests with empty data are omitted.
+    ///
+    /// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+    pub requests: Vec<Bytes>,
+    /// Gas used by all transactions.
+    pub gas_used: u64,
+    /// Blob gas used by all transactions.
+    pub blob_gas_used: u64,
+    /// State changes of the block together with reverts.
+    pub bundle_state: Bundle,
+    /// [EIP-7928] block access list, if it is built or validated.
+    ///
+    /// [EIP-7928]: https://eips.ethereum.org/EIPS/eip-7928
+    pub block_access_list: Option<BlockAccessList>,
+}
+
+/// Block execution error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockExecutionError<E> {
+    /// Transaction at `index` failed to execute.
+    Transaction {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Execution error.
+        error: E,
+    },
+    /// Transaction gas limit is more than the gas left in the block.
+    TransactionGasLimitMoreThanAvailableBlockGas {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Gas limit of the transaction.
+        transaction_gas_limit: u64,
+        /// Gas left in the block.
+        block_available_gas: u64,
+    },
+    /// System call failed to execute.
+    SystemCall {
+        /// Address of the system contract.
+        address: Address,
+        /// Execution error.
+        error: E,
+    },
+    /// System call was executed but did not succeed.
+    SystemCallFailed {
+        /// Address of the system contract.
+        address: Address,
+    },
+    /// System contract required by the spec has no code.
+    MissingSystemContract {
+        /// Address of the system contract.
+        address: Address,
+    },
+    /// Deposit contract emitted a log that does not follow the `DepositEvent` layout.
+    InvalidDepositEventLayout,
+    /// Block access list was provided but the journal does not build it.
+    BlockAccessListNotBuilt,
+    /// Provided block access list does not match the execution.
+    InvalidBlockAccessList(BlockAccessListError),
+    /// Error that occurred outside of transaction execution, for example while applying withdrawals.
+    Other(E),
+}
+
+impl<E> From<E> for BlockExecutionError<E> {
+    fn from(error: E) -> Self {
+        Self::Other(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for BlockExecutionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction { index, error } => {
+                write!(f, "transaction {index} failed: {error}")
+            }
+            Self::TransactionGasLimitMoreThanAvailableBlockGas {
+                index,
+                transaction_gas_limit,
+                block_available_gas,
+            } => write!(
+                f,
+                "transaction {index} gas limit {transaction_gas_limit} is more than block available gas {block_available_gas}"
+            ),
+            Self::SystemCall { address, error } => {
+                write!(f, "system call to {address} failed: {error}")
+            }
+            Self::SystemCallFailed { address } => {
+                write!(f, "system call to {address} did not succeed")
+            }
+            Self::MissingSystemContract { address } => {
+                write!(f, "system contract {address} has no code")
+            }
+            Self::InvalidDepositEventLayout => f.write_str("invalid deposit event layout"),
+            Self::BlockAccessListNotBuilt => {
+                f.write_str("block access list is not built by the journal")
+            }
+            Self::InvalidBlockAccessList(error) => fmt::Display::fmt(error, f),
+            Self::Other(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for BlockExecutionError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Transaction { error, .. }
+            | Self::SystemCall { error, .. }
+            | Self::Other(error) => Some(error),
+            Self::InvalidBlockAccessList(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Helper type that extracts the database of the EVM context.
+pub type BlockExecutorDb<EVM> = <<EVM as EvmTr>::Context as ContextTr>::Db;
+
+/// Helper type that extracts the bundle of the database of the EVM context.
+pub type BlockExecutorBundle<EVM> = <BlockExecutorDb<EVM> as BlockStateDb>::Bundle;
+
+/// Helper type of the output of [`BlockExecutor::execute_block`].
+pub type BlockExecutorOutput<BE> = BlockExecutionOutput<
+    BlockExecutorBundle<<BE as BlockExecutor>::Evm>,
+    <BE as BlockExecutor>::HaltReason,
+>;
+
+/// Block executor.
+///
+/// The [`BlockExecutor::execute_block`] method serves as the entry point and runs the phases
+/// of the block in order:
+///   * Pre-block - [EIP-4788] beacon root and [EIP-2935] block hash system calls.
+///   * Transactions - executes and commits transactions while tracking block gas.
+///   * Block rewards - pre-merge miner and ommer rewards.
+///   * Withdrawals - [EIP-4895] balance increments.
+///   * Post-block - [EIP-6110] deposits and [EIP-7002]/[EIP-7251] request system calls.
+///
+/// At the end all transitions are merged and the [`BlockStateDb::Bundle`] is taken from the
+/// database.
+///
+/// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+/// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+/// [EIP-6110]: https://eips.ethereum.org/EIPS/eip-6110
+/// [EIP-7002]: https://eips.ethereum.org/EIPS/eip-7002
+/// [EIP-7251]: https://eips.ethereum.org/EIPS/eip-7251
+pub trait BlockExecutor {
+    /// The EVM used to execute transactions and system calls.
+    type Evm: EvmTr<Context: ContextTr<Db: BlockStateDb>>
+        + SystemCallCommitEvm<CommitOutput = Result<ExecutionResult<Self::HaltReason>, Self::Error>>;
+    /// The error type returned by the EVM.
+    type Error: From<<BlockExecutorDb<Self::Evm> as Database>::Error>;
+    /// The halt reason type included in the results.
+    type HaltReason: HaltReasonTr;
+
+    /// Executes the block and returns receipts, requests and the merged bundle.
+    fn execute_block(
+        &mut self,
+        evm: &mut Self::Evm,
+        input: BlockInput<<Self::Evm as ExecuteEvm>::Block, <Self::Evm as ExecuteEvm>::Tx>,
+    ) -> Result<BlockExecutorOutput<Self>, BlockExecutionError<Self::Error>> {
+        let BlockInput {
+            block,
+            transactions,
+            parent_hash,
+            parent_beacon_block_root,
+            ommers,
+            withdrawals,
+            block_access_list: expected_block_access_list,
+            build_block_access_list,
+        } = input;
+        evm.set_block(block);
+
+        let journal = evm.ctx().journal();
+        if build_block_access_list || expected_block_access_list.is_some() {
+            journal.set_block_access_list(Some(BlockAccessList::new()));
+        }
+        journal.set_block_access_index(0);
+        self.apply_pre_block(evm, parent_hash, parent_beacon_block_root)?;
+        let executed = self.execute_transactions(evm, transactions)?;
+        evm.ctx()
+            .journal()
+            .set_block_access_index(executed.results.len() as u64 + 1);
+        self.apply_block_rewards(evm, &ommers)?;
+        if let Some(withdrawals) = withdrawals {
+            self.apply_withdrawals(evm, &withdrawals)?;
+        }
+        let requests = self.apply_post_block(evm, &executed.receipts)?;
+
+        let block_access_list = evm.ctx().journal().take_block_access_list();
+        let db = evm.ctx().db();
+        db.merge_transitions();
+        let bundle_state = db.take_bundle();
+        if let Some(expected) = &expected_block_access_list {
+            block_access_list
+                .as_ref()
+                .ok_or(BlockExecutionError::BlockAccessListNotBuilt)?
+                .validate(expected)
+                .map_err(BlockExecutionError::InvalidBlockAccessList)?;
+        }
+
+        Ok(BlockExecutionOutput {
+            results: executed.results,
+            receipts: executed.receipts,
+            requests,
+            gas_used: executed.gas_used,
+            blob_gas_used: executed.blob_gas_used,
+            bundle_state,
+            block_access_list,
+        })
+    }
+
+    /// Runs system calls that need to be executed before any transaction.
+    ///
+    /// Parent hash is made available to `BLOCKHASH`. From Cancun the parent beacon block root
+    /// is stored in the [EIP-4788] contract and from Prague the parent hash is stored in
+    /// the [EIP-2935] contract. Calls are skipped for the genesis block and if the contract
+    /// has no code. The [`BlockExecutor::pre_block_system_calls`] run last and require the
+    /// contract to exist.
+    ///
+    /// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    fn apply_pre_block(
+        &mut self,
+        evm: &mut Self::Evm,
+        parent_hash: Option<B256>,
+        parent_beacon_block_root: Option<B256>,
+    ) -> Result<(), BlockExecutionError<Self::Error>> {
+        let spec: SpecId = evm.ctx_ref().cfg().spec().into();
+        let number = evm.ctx_ref().block().number();
+        if number == 0 {
+            return Ok(());
+        }
+
+        if let Some(parent_hash) = parent_hash {
+            evm.ctx().db().insert_block_hash(number - 1, parent_hash);
+        }
+
+        if spec.is_enabled_in(SpecId::CANCUN) {
+            if let Some(root) = parent_beacon_block_root {
+                if has_code(evm, BEACON_ROOTS_ADDRESS)? {
+                    transact_system_call(evm, BEACON_ROOTS_ADDRESS, root.0.into())?;
+                }
+            }
+        }
+
+        if spec.is_enabled_in(SpecId::PRAGUE) {
+            if let Some(parent_hash) = parent_hash {
+                if has_code(evm, HISTORY_STORAGE_ADDRESS)? {
+                    transact_system_call(evm, HISTORY_STORAGE_ADDRESS, parent_hash.0.into())?;
+                }
+            }
+        }
+
+        for SystemCall { address, data } in self.pre_block_system_calls(evm) {
+            if !has_code(evm, address)? {
+                return Err(BlockExecutionError::MissingSystemContract { address });
+            }
+            transact_system_call(evm, address, data)?;
+        }
+        Ok(())
+    }
+
+    /// Chain specific system calls executed before any transaction, after the Ethereum ones.
+    ///
+    /// Empty for mainnet.
+    fn pre_block_system_calls(&self, _evm: &Self::Evm) -> Vec<SystemCall> {
+        Vec::new()
+    }
+
+    /// Executes and commits transactions in order.
+    ///
+    /// Fails if a transaction gas limit is more than the gas left in the block or if any
+    /// transaction is invalid.
+    fn execute_transactions(
+        &mut self,
+        evm: &mut Self::Evm,
+        transactions: Vec<<Self::Evm as ExecuteEvm>::Tx>,
+    ) -> Result<ExecutedTransactions<Self::HaltReason>, BlockExecutionError<Self::Error>> {
+        let block_gas_limit = evm.ctx_ref().block().gas_limit();
+        let mut executed = ExecutedTransactions {
+            results: Vec::with_capacity(transactions.len()),
+            receipts: Vec::with_capacity(transactions.len()),
+            gas_used: 0,
+            blob_gas_used: 0,
+        };
+
+        for (index, tx) in transactions.into_iter().enumerate() {
+            let block_available_gas = block_gas_limit.saturating_sub(executed.gas_used);
+            let transaction_gas_limit = tx.gas_limit();
+            if transaction_gas_limit > block_available_gas {
+                return Err(
+                    BlockExecutionError::TransactionGasLimitMoreThanAvailableBlockGas {
+                        index,
+                        transaction_gas_limit,
+                        block_available_gas,
+                    },
+                );
+            }
+            let tx_type = tx.tx_type();
+            let blob_gas = tx.total_blob_gas();
+
+            evm.ctx().journal().set_block_access_index(index as u64 + 1);
+            let result = evm
+                .transact_commit(tx)
+                .map_err(|error| BlockExecutionError::Transaction { index, error })?;
+
+            executed.gas_used += result.gas_used();
+            executed.blob_gas_used += blob_gas;
+            executed.receipts.push(Receipt {
+                tx_type,
+                success: result.is_success(),
+                cumulative_gas_used: executed.gas_used,
+                logs: result.logs().to_vec(),
+            });
+            executed.results.push(result);
+        }
+        Ok(executed)
+    }
+
+    /// Rewards the block beneficiary and ommer beneficiaries before the merge.
+    fn apply_block_rewards(
+        &mut self,
+        evm: &mut Self::Evm,
+        ommers: &[Ommer],
+    ) -> Result<(), BlockExecutionError<Self::Error>> {
+        let spec: SpecId = evm.ctx_ref().cfg().spec().into();
+        let Some(base_reward) = block_reward(spec) else {
+            return Ok(());
+        };
+        let block = evm.ctx_ref().block();
+        let number = block.number();
+
+        let mut balances = vec![(
+            block.beneficiary(),
+            base_reward + base_reward / 32 * ommers.len() as u128,
+        )];
+        for ommer in ommers {
+            let reward = (8 + ommer.number).saturating_sub(number) as u128 * base_reward / 8;
+            balances.push((ommer.beneficiary, reward));
+        }
+        increment_balances(evm, balances).map_err(|e| BlockExecutionError::Other(e.into()))
+    }
+
+    /// Applies [EIP-4895](https://eips.ethereum.org/EIPS/eip-4895) withdrawals.
+    fn apply_withdrawals(
+        &mut self,
+        evm: &mut Self::Evm,
+        withdrawals: &[Withdrawal],
+    ) -> Result<(), BlockExecutionError<Self::Error>> {
+        let spec: SpecId = evm.ctx_ref().cfg().spec().into();
+        if !spec.is_enabled_in(SpecId::SHANGHAI) {
+            return Ok(());
+        }
+        increment_balances(
+            evm,
+            withdrawals
+                .iter()
+                .map(|withdrawal| (withdrawal.address, withdrawal.amount_wei())),
+        )
+        .map_err(|e| BlockExecutionError::Other(e.into()))
+    }
+
+    /// Address of the contract whose logs are parsed as [EIP-6110] deposit requests.
+    ///
+    /// [EIP-6110]: https://eips.ethereum.org/EIPS/eip-6110
+    fn deposit_contract_address(&self) -> Option<Address> {
+        Some(MAINNET_DEPOSIT_CONTRACT_ADDRESS)
+    }
+
+    /// Collects [EIP-7685] requests after all transactions are executed.
+    ///
+    /// From Prague this parses deposit requests from receipts and runs the [EIP-7002]
+    /// and [EIP-7251] system calls. Both system contracts must exist and the calls must succeed.
+    ///
+    /// [EIP-7002]: https://eips.ethereum.org/EIPS/eip-7002
+    /// [EIP-7251]: https://eips.ethereum.org/EIPS/eip-7251
+    /// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+    fn apply_post_block(
+        &mut self,
+        evm: &mut Self::Evm,
+        receipts: &[Receipt],
+    ) -> Result<Vec<Bytes>, BlockExecutionError<Self::Error>> {
+        let spec: SpecId = evm.ctx_ref().cfg().spec().into();
+        let mut requests = Vec::new();
+        if !spec.is_enabled_in(SpecId::PRAGUE) {
+            return Ok(requests);
+        }
+
+        if let Some(deposit_contract) = self.deposit_contract_address() {
+            let mut deposits = vec![DEPOSIT_REQUEST_TYPE];
+            for receipt in receipts {
+                parse_deposits_from_logs(deposit_contract, &receipt.logs, &mut deposits)?;
+            }
+            if deposits.len() > 1 {
+                requests.push(deposits.into());
+            }
+        }
+
+        for (address, request_type) in [
+            (
+                WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
+                WITHDRAWAL_REQUEST_TYPE,
+            ),
+            (
+                CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+                CONSOLIDATION_REQUEST_TYPE,
+            ),
+        ] {
+            if !has_code(evm, address)? {
+                return Err(BlockExecutionError::MissingSystemContract { address });
+            }
+            let output = transact_system_call(evm, address, Bytes::new())?;
+            if !output.is_empty() {
+                let mut request = Vec::with_capacity(output.len() + 1);
+                request.push(request_type);
+                request.extend_from_slice(&output);
+                requests.push(request.into());
+            }
+        }
+
+        Ok(requests)
+    }
+}
+
+/// Mainnet block executor.
+///
+/// Uses default implementations of all [`BlockExecutor`] methods.
+#[derive(Debug)]
+pub struct MainnetBlockExecutor<EVM, ERROR> {
+    /// Address of the deposit contract, `None` disables deposit requests.
+    pub deposit_contract_address: Option<Address>,
+    _phantom: core::marker::PhantomData<(EVM, ERROR)>,
+}
+
+impl<EVM, ERROR> MainnetBlockExecutor<EVM, ERROR> {
+    /// Creates a new executor with the mainnet deposit contract.
+    pub fn new() -> Self {
+        Self {
+            deposit_contract_address: Some(MAINNET_DEPOSIT_CONTRACT_ADDRESS),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets the deposit contract address.
+    pub fn with_deposit_contract_address(mut self, address: Option<Address>) -> Self {
+        self.deposit_contract_address = address;
+        self
+    }
+}
+
+impl<EVM, ERROR> Default for MainnetBlockExecutor<EVM, ERROR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<EVM, ERROR> BlockExecutor for MainnetBlockExecutor<EVM, ERROR>
+where
+    EVM: EvmTr<Context: ContextTr<Db: BlockStateDb>>
+        + SystemCallCommitEvm<CommitOutput = Result<ExecutionResult<HaltReason>, ERROR>>,
+    ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type HaltReason = HaltReason;
+
+    fn deposit_contract_address(&self) -> Option<Address> {
+        self.deposit_contract_address
+    }
+}
+
+/// Returns the block reward for the given spec or `None` if there is no reward.
+pub fn block_reward(spec: SpecId) -> Option<u128> {
+    const ETH: u128 = 1_000_000_000_000_000_000;
+    if spec.is_enabled_in(SpecId::MERGE) {
+        None
+    } else if spec.is_enabled_in(SpecId::CONSTANTINOPLE) {
+        Some(2 * ETH)
+    } else if spec.is_enabled_in(SpecId::BYZANTIUM) {
+        Some(3 * ETH)
+    } else {
+        Some(5 * ETH)
+    }
+}
+
+/// Parses [EIP-6110](https://eips.ethereum.org/EIPS/eip-6110) deposit requests from logs of
+/// the deposit contract and appends them to `out`.
+///
+/// Each deposit is encoded as `pubkey ++ withdrawal_credentials ++ amount ++ signature ++ index`.
+pub fn parse_deposits_from_logs<E>(
+    deposit_contract: Address,
+    logs: &[Log],
+    out: &mut Vec<u8>,
+) -> Result<(), BlockExecutionError<E>> {
+    // (offset, size) of each field in the ABI encoded event data.
+    const FIELDS: [(usize, usize); 5] = [(160, 48), (256, 32), (320, 8), (384, 96), (512, 8)];
+
+    for log in logs {
+        if log.address != deposit_contract || log.topics().first() != Some(&DEPOSIT_EVENT_TOPIC) {
+            continue;
+        }
+        let data = log.data.data.as_ref();
+        if data.len() != 576 {
+            return Err(BlockExecutionError::InvalidDepositEventLayout);
+        }
+        for (i, (offset, size)) in FIELDS.into_iter().enumerate() {
+            let head = &data[i * 32..(i + 1) * 32];
+            let size_word = &data[offset..offset + 32];
+            if !is_u64_word(head, offset as u64) || !is_u64_word(size_word, size as u64) {
+                return Err(BlockExecutionError::InvalidDepositEventLayout);
+            }
+            out.extend_from_slice(&data[offset + 32..offset + 32 + size]);
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the 32 byte big endian word is equal to `value`.
+fn is_u64_word(word: &[u8], value: u64) -> bool {
+    word[..24].iter().all(|b| *b == 0) && word[24..] == value.to_be_bytes()
+}
+
+/// Returns `true` if the account at `address` has code.
+fn has_code<EVM, E>(evm: &mut EVM, address: Address) -> Result<bool, BlockExecutionError<E>>
+where
+    EVM: EvmTr,
+    E: From<<<EVM::Context as ContextTr>::Db as Database>::Error>,
+{
+    let info = evm
+        .ctx()
+        .db()
+        .basic(address)
+        .map_err(|e| BlockExecutionError::Other(e.into()))?;
+    Ok(info.is_some_and(|info| !info.is_empty_code_hash()))
+}
+
+/// Increments the balances in the database and records them in the block access list of the
+/// journal, see [`JournalTr::record_balance_change`].
+fn increment_balances<EVM>(
+    evm: &mut EVM,
+    balances: impl IntoIterator<Item = (Address, u128)>,
+) -> Result<(), <BlockExecutorDb<EVM> as Database>::Error>
+where
+    EVM: EvmTr<Context: ContextTr<Db: BlockStateDb>>,
+{
+    let balances: Vec<_> = balances.into_iter().collect();
+    let mut originals = BTreeMap::new();
+    for (address, _) in &balances {
+        if let btree_map::Entry::Vacant(entry) = originals.entry(*address) {
+            entry.insert(account_balance(evm, *address)?);
+        }
+    }
+    evm.ctx().db().increment_balances(balances)?;
+    for (address, original) in originals {
+        let balance = account_balance(evm, address)?;
+        evm.ctx()
+            .journal()
+            .record_balance_change(address, original, balance);
+    }
+    Ok(())
+}
+
+/// Returns the balance of the account in the database, zero if it does not exist.
+fn account_balance<EVM: EvmTr>(
+    evm: &mut EVM,
+    address: Address,
+) -> Result<U256, <BlockExecutorDb<EVM> as Database>::Error> {
+    Ok(evm
+        .ctx()
+        .db()
+        .basic(address)?
+        .map(|info| info.balance)
+        .unwrap_or_default())
+}
+
+/// Executes and commits a system call and returns its output.
+///
+/// Fails if the call does not succeed.
+fn transact_system_call<EVM, H, E>(
+    evm: &mut EVM,
+    address: Address,
+    data: Bytes,
+) -> Result<Bytes, BlockExecutionError<E>>
+where
+    EVM: SystemCallCommitEvm<CommitOutput = Result<ExecutionResult<H>, E>>,
+{
+    let result = evm
+        .transact_system_call_commit(address, data)
+        .map_err(|error| BlockExecutionError::SystemCall { address, error })?;
+    if !result.is_success() {
+        return Err(BlockExecutionError::SystemCallFailed { address });
+    }
+    Ok(result.into_output().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MainBuilder, MainContext};
+    use context::{BlockEnv, Context, TxEnv};
+    use database::{InMemoryDB, RecordingDB, State, WitnessDB};
+    use primitives::{bytes, LogData, TxKind};
+    use state::{AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const RECIPIENT: Address = address!("0x2000000000000000000000000000000000000002");
+    const BENEFICIARY: Address = address!("0x3000000000000000000000000000000000000003");
+
+    static HISTORY_STORAGE_CODE: Bytes = bytes!("0x3373fffffffffffffffffffffffffffffffffffffffe14604657602036036042575f35600143038111604257611fff81430311604257611fff9006545f5260205ff35b5f5ffd5b5f35611fff60014303065500");
+
+    fn caller_db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CALLER,
+            AccountInfo::from_balance(U256::from(1_000_000_000_000_000u64)),
+        );
+        db.insert_account_info(
+            HISTORY_STORAGE_ADDRESS,
+            AccountInfo::default().with_code(Bytecode::new_legacy(HISTORY_STORAGE_CODE.clone())),
+        );
+        db
+    }
+
+    fn state_with_caller() -> State<InMemoryDB> {
+        State::builder()
+            .with_database(caller_db())
+            .with_bundle_update()
+            .build()
+    }
+
+    fn transfer(nonce: u64) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(RECIPIENT),
+            value: U256::from(100),
+            gas_limit: 21_000,
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    fn block(number: u64) -> BlockEnv {
+        BlockEnv {
+            number,
+            beneficiary: BENEFICIARY,
+            gas_limit: 50_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn execute_block_with_withdrawals() {
+        let mut state = state_with_caller();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(&mut state)
+            .build_mainnet();
+
+        let withdrawal = Withdrawal {
+            address: RECIPIENT,
+            amount: 2,
+            ..Default::default()
+        };
+        let input = BlockInput::new(block(1), vec![transfer(0), transfer(1)])
+            .with_withdrawals(vec![withdrawal]);
+
+        let output = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input)
+            .unwrap();
+
+        assert_eq!(output.gas_used, 42_000);
+        assert_eq!(
+            output
+                .receipts
+                .iter()
+                .map(|r| r.cumulative_gas_used)
+                .collect::<Vec<_>>(),
+            vec![21_000, 42_000]
+        );
+        assert!(output.receipts.iter().all(|r| r.success));
+        assert!(output.requests.is_empty());
+
+        let recipient = output.bundle_state.account(&RECIPIENT).unwrap();
+        assert_eq!(
+            recipient.info.as_ref().unwrap().balance,
+            U256::from(200 + 2 * GWEI_TO_WEI)
+        );
+        let caller = output.bundle_state.account(&CALLER).unwrap();
+        assert_eq!(caller.info.as_ref().unwrap().nonce, 2);
+        assert_eq!(output.bundle_state.reverts.len(), 1);
+    }
+
+    #[test]
+    fn build_and_validate_block_access_list() {
+        let execute = |input: BlockInput<BlockEnv, TxEnv>| {
+            let mut state = State::builder()
+                .with_database(caller_db())
+                .with_bundle_update()
+                .build();
+            let mut evm = Context::mainnet()
+                .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+                .with_db(&mut state)
+                .build_mainnet();
+            MainnetBlockExecutor::default().execute_block(&mut evm, input)
+        };
+        let input = || {
+            BlockInput::new(block(1), vec![transfer(0), transfer(1)]).with_withdrawals(vec![
+                Withdrawal {
+                    address: RECIPIENT,
+                    amount: 1,
+                    ..Default::default()
+                },
+            ])
+        };
+
+        assert!(execute(input()).unwrap().block_access_list.is_none());
+        let list = execute(input().with_build_block_access_list())
+            .unwrap()
+            .block_access_list
+            .unwrap();
+        let caller = &list.accounts[&CALLER];
+        assert_eq!(caller.nonce_changes, [(1, 1), (2, 2)]);
+        let recipient = &list.accounts[&RECIPIENT];
+        assert_eq!(
+            recipient.balance_changes,
+            [
+                (1, U256::from(100)),
+                (2, U256::from(200)),
+                (3, U256::from(200 + GWEI_TO_WEI)),
+            ]
+        );
+
+        assert!(execute(input().with_block_access_list(list.clone())).is_ok());
+        let mut invalid = list;
+        invalid.record_nonce_change(3, CALLER, 3);
+        assert_eq!(
+            execute(input().with_block_access_list(invalid)).unwrap_err(),
+            BlockExecutionError::InvalidBlockAccessList(BlockAccessListError::AccountMismatch(
+                CALLER
+            ))
+        );
+    }
+
+    #[test]
+    fn replay_block_from_witness() {
+        let input = || {
+            BlockInput::new(block(1), vec![transfer(0), transfer(1)])
+                .with_parent_hash(B256::with_last_byte(1))
+                .with_withdrawals(vec![Withdrawal {
+                    address: RECIPIENT,
+                    amount: 1,
+                    ..Default::default()
+                }])
+        };
+
+        let mut state = State::builder()
+            .with_database(RecordingDB::new(caller_db()))
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(&mut state)
+            .build_mainnet();
+        let expected = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input())
+            .unwrap();
+        let witness = state.database.into_witness();
+        assert!(witness.accounts.contains_key(&CALLER));
+        assert!(witness.accounts.contains_key(&RECIPIENT));
+
+        let mut state = State::builder()
+            .with_database(WitnessDB::new(witness))
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(&mut state)
+            .build_mainnet();
+        let output = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input())
+            .unwrap();
+        assert_eq!(output, expected);
+
+        // Lookup outside of the witness fails.
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(&mut state)
+            .build_mainnet();
+        let tx = TxEnv {
+            kind: TxKind::Call(address!("0x4000000000000000000000000000000000000004")),
+            ..transfer(2)
+        };
+        let err = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, BlockInput::new(block(2), vec![tx]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BlockExecutionError::Transaction { index: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn block_gas_limit_is_enforced() {
+        let mut state = state_with_caller();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::CANCUN)
+            .with_db(&mut state)
+            .build_mainnet();
+
+        let input = BlockInput::new(block(1), vec![transfer(0), transfer(1), transfer(2)]);
+        let err = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            BlockExecutionError::TransactionGasLimitMoreThanAvailableBlockGas {
+                index: 2,
+                transaction_gas_limit: 21_000,
+                block_available_gas: 8_000,
+            }
+        );
+    }
+
+    #[test]
+    fn pre_block_history_storage() {
+        let mut state = state_with_caller();
+        // Requests contracts are needed for prague blocks.
+        state.insert_account(
+            WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS,
+            AccountInfo::default().with_code(Bytecode::new_legacy(bytes!("0x00"))),
+        );
+        state.insert_account(
+            CONSOLIDATION_REQUEST_PREDEPLOY_ADDRESS,
+            AccountInfo::default().with_code(Bytecode::new_legacy(bytes!("0x00"))),
+        );
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::PRAGUE)
+            .with_db(&mut state)
+            .build_mainnet();
+
+        let parent_hash = B256::with_last_byte(0x11);
+        let input =
+            BlockInput::<BlockEnv, TxEnv>::new(block(1), vec![]).with_parent_hash(parent_hash);
+        let output = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input)
+            .unwrap();
+
+        let history = output
+            .bundle_state
+            .account(&HISTORY_STORAGE_ADDRESS)
+            .unwrap();
+        assert_eq!(
+            history.storage_slot(U256::ZERO),
+            Some(U256::from_be_bytes(parent_hash.0))
+        );
+        assert_eq!(evm.ctx.db().block_hash(0).unwrap(), parent_hash);
+    }
+
+    #[test]
+    fn prague_requires_request_contracts() {
+        let mut state = state_with_caller();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::PRAGUE)
+            .with_db(&mut state)
+            .build_mainnet();
+
+        let input = BlockInput::<BlockEnv, TxEnv>::new(block(1), vec![]);
+        let err = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BlockExecutionError::MissingSystemContract {
+                address: WITHDRAWAL_REQUEST_PREDEPLOY_ADDRESS
+            }
+        );
+    }
+
+    #[test]
+    fn pre_merge_block_rewards() {
+        let mut state = state_with_caller();
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::PETERSBURG)
+            .with_db(&mut state)
+            .build_mainnet();
+
+        let ommer = Ommer {
+            beneficiary: RECIPIENT,
+            number: 9,
+        };
+        let input = BlockInput::<BlockEnv, TxEnv>::new(block(10), vec![]).with_ommers(vec![ommer]);
+        let output = MainnetBlockExecutor::default()
+            .execute_block(&mut evm, input)
+            .unwrap();
+
+        let eth = U256::from(10).pow(U256::from(18));
+        let beneficiary = output.bundle_state.account(&BENEFICIARY).unwrap();
+        assert_eq!(
+            beneficiary.info.as_ref().unwrap().balance,
+            eth * U256::from(2) + eth * U256::from(2) / U256::from(32)
+        );
+        let ommer = output.bundle_state.account(&RECIPIENT).unwrap();
+        assert_eq!(
+            ommer.info.as_ref().unwrap().balance,
+            eth * U256::from(2) * U256::from(7) / U256::from(8)
+        );
+    }
+
+    #[test]
+    fn parse_deposit_request() {
+        let mut data = vec![0u8; 576];
+        for (i, (offset, size)) in [(160, 48), (256, 32), (320, 8), (384, 96), (512, 8)]
+            .into_iter()
+            .enumerate()
+        {
+            data[i * 32 + 24..(i + 1) * 32].copy_from_slice(&(offset as u64).to_be_bytes());
+            data[offset + 24..offset + 32].copy_from_slice(&(size as u64).to_be_bytes());
+            data[offset + 32..offset + 32 + size].fill(i as u8 + 1);
+        }
+        let log = Log {
+            address: MAINNET_DEPOSIT_CONTRACT_ADDRESS,
+            data: LogData::new_unchecked(vec![DEPOSIT_EVENT_TOPIC], data.into()),
+        };
+
+        let mut out = Vec::new();
+        parse_deposits_from_logs::<()>(
+            MAINNET_DEPOSIT_CONTRACT_ADDRESS,
+            std::slice::from_ref(&log),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(out.len(), 192);
+        assert!(out[..48].iter().all(|b| *b == 1));
+        assert!(out[184..].iter().all(|b| *b == 5));
+
+        let mut invalid = log;
+        invalid.data = LogData::new_unchecked(vec![DEPOSIT_EVENT_TOPIC], Bytes::new());
+        assert_eq!(
+            parse_deposits_from_logs::<()>(MAINNET_DEPOSIT_CONTRACT_ADDRESS, &[invalid], &mut out),
+            Err(BlockExecutionError::InvalidDepositEventLayout)
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Bundle simulation, as done by searchers and block builders.
+//!
+//! A bundle is an ordered list of transactions executed on top of a block. Transactions that
+//! are allowed to revert are dropped from the bundle when they fail, any other failure aborts
+//! the whole bundle.
+use crate::{EvmTr, ExecuteEvm};
+use context_interface::{
+    result::{ExecutionResult, HaltReason, ResultAndState},
+    Block, ContextTr, Database, Transaction,
+};
+use core::fmt;
+use database_interface::{DatabaseCommit, DatabaseSnapshot};
+use primitives::U256;
+use std::vec::Vec;
+
+/// Transaction of a bundle.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BundleTx<TX> {
+    /// The transaction.
+    pub tx: TX,
+    /// Whether the transaction is dropped from the bundle if it reverts or halts, instead of
+    /// aborting the bundle.
+    pub revert_allowed: bool,
+}
+
+impl<TX> BundleTx<TX> {
+    /// Creates a transaction that must succeed.
+    pub fn new(tx: TX) -> Self {
+        Self {
+            tx,
+            revert_allowed: false,
+        }
+    }
+
+    /// Creates a transaction that is dropped from the bundle if it fails.
+    pub fn revert_allowed(tx: TX) -> Self {
+        Self {
+            tx,
+            revert_allowed: true,
+        }
+    }
+}
+
+/// Outcome of a transaction of the bundle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleTxOutcome<HaltReasonTy = HaltReason> {
+    /// Execution result of the transaction.
+    pub result: ExecutionResult<HaltReasonTy>,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Gas price paid by the transaction, see [`Transaction::effective_gas_price`].
+    pub effective_gas_price: u128,
+    /// Increase of the block beneficiary balance, the priority fees and the direct payments.
+    pub coinbase_payment: U256,
+    /// Whether the transaction failed and its state changes were discarded.
+    pub discarded: bool,
+}
+
+/// Outcome of a bundle that was applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleOutcome<HaltReasonTy = HaltReason> {
+    /// Outcomes of the transactions, in the bundle order.
+    pub transactions: Vec<BundleTxOutcome<HaltReasonTy>>,
+    /// Gas used by the transactions that were not discarded.
+    pub gas_used: u64,
+    /// Coinbase payment of the transactions that were not discarded.
+    pub coinbase_payment: U256,
+}
+
+impl<HaltReasonTy> BundleOutcome<HaltReasonTy> {
+    /// Returns the coinbase payment per unit of gas, used to rank bundles.
+    pub fn bundle_gas_price(&self) -> U256 {
+        self.coinbase_payment
+            .checked_div(U256::from(self.gas_used))
+            .unwrap_or_default()
+    }
+}
+
+/// Error of [`execute_bundle`], the state changes of the bundle are reverted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleError<HaltReasonTy, E> {
+    /// Transaction could not be executed, for example it is invalid.
+    Transaction {
+        /// Index of the transaction in the bundle.
+        index: usize,
+        /// Error of the execution.
+        error: E,
+    },
+    /// Transaction that is not allowed to revert reverted or halted.
+    Reverted {
+        /// Index of the transaction in the bundle.
+        index: usize,
+        /// Execution result of the transaction.
+        result: ExecutionResult<HaltReasonTy>,
+    },
+    /// Error of the database outside of the execution.
+    Database(E),
+}
+
+impl<HaltReasonTy, E: fmt::Display> fmt::Display for BundleError<HaltReasonTy, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction { index, error } => {
+                write!(f, "bundle transaction {index} failed: {error}")
+            }
+            Self::Reverted { index, .. } => {
+                write!(f, "bundle transaction {index} is not allowed to revert")
+            }
+            Self::Database(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl<HaltReasonTy: fmt::Debug, E: core::error::Error + 'static> core::error::Error
+    for BundleError<HaltReasonTy, E>
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Transaction { error, .. } | Self::Database(error) => Some(error),
+            Self::Reverted { .. } => None,
+        }
+    }
+}
+
+/// Executes the bundle transactions in order on top of the current state and commits them.
+///
+/// Transactions that are allowed to revert and fail are kept in the outcome but their state
+/// changes are discarded. If a transaction can not be executed or fails without being allowed
+/// to revert, the whole bundle is aborted and the database is reverted with a
+/// [`DatabaseSnapshot`], so it can be `CacheDB` or `State`.
+///
+/// To simulate the bundle without applying it, take a snapshot of the database before the
+/// call and revert to it afterwards.
+pub fn execute_bundle<EVM, HaltReasonTy, ERROR>(
+    evm: &mut EVM,
+    transactions: impl IntoIterator<Item = BundleTx<EVM::Tx>>,
+) -> Result<BundleOutcome<HaltReasonTy>, BundleError<HaltReasonTy, ERROR>>
+where
+    EVM: ExecuteEvm<Output = Result<ResultAndState<HaltReasonTy>, ERROR>>
+        + EvmTr<Context: ContextTr<Db: DatabaseCommit + DatabaseSnapshot>>,
+    ERROR: From<<<EVM::Context as ContextTr>::Db as Database>::Error>,
+{
+    let snapshot = evm.ctx().db().snapshot();
+    let outcome = apply_bundle(evm, transactions);
+    let db = evm.ctx().db();
+    if outcome.is_ok() {
+        db.release_snapshot(snapshot);
+    } else {
+        db.revert_to(snapshot);
+    }
+    outcome
+}
+
+/// Executes and commits the bundle transactions, see [`execute_bundle`].
+fn apply_bundle<EVM, HaltReasonTy, ERROR>(
+    evm: &mut EVM,
+    transactions: impl IntoIterator<Item = BundleTx<EVM::Tx>>,
+) -> Result<BundleOutcome<HaltReasonTy>, BundleError<HaltReasonTy, ERROR>>
+where
+    EVM: ExecuteEvm<Output = Result<ResultAndState<HaltReasonTy>, ERROR>>
+        + EvmTr<Context: ContextTr<Db: DatabaseCommit>>,
+    ERROR: From<<<EVM::Context as ContextTr>::Db as Database>::Error>,
+{
+    let block = evm.ctx_ref().block();
+    let beneficiary = block.beneficiary();
+    let basefee = block.basefee() as u128;
+    let mut balance = evm
+        .ctx()
+        .db()
+        .basic(beneficiary)
+        .map_err(|e| BundleError::Database(e.into()))?
+        .map(|info| info.balance)
+        .unwrap_or_default();
+
+    let mut outcome = BundleOutcome {
+        transactions: Vec::new(),
+        gas_used: 0,
+        coinbase_payment: U256::ZERO,
+    };
+    for (index, BundleTx { tx, revert_allowed }) in transactions.into_iter().enumerate() {
+        let effective_gas_price = tx.effective_gas_price(basefee);
+        let ResultAndState { result, state } = evm
+            .transact(tx)
+            .map_err(|error| BundleError::Transaction { index, error })?;
+        let discarded = !result.is_success();
+        if discarded && !revert_allowed {
+            return Err(BundleError::Reverted { index, result });
+        }
+
+        let new_balance = state
+            .get(&beneficiary)
+            .map(|account| account.info.balance)
+            .unwrap_or(balance);
+        let coinbase_payment = new_balance.saturating_sub(balance);
+        let gas_used = result.gas_used();
+        if !discarded {
+            evm.ctx().db().commit(state);
+            balance = new_balance;
+            outcome.gas_used += gas_used;
+            outcome.coinbase_payment += coinbase_payment;
+        }
+        outcome.transactions.push(BundleTxOutcome {
+            result,
+            gas_used,
+            effective_gas_price,
+            coinbase_payment,
+            discarded,
+        });
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MainBuilder, MainContext};
+    use context::{Context, TxEnv};
+    use database::InMemoryDB;
+    use primitives::{address, Address, Bytes, TxKind};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const COINBASE: Address = address!("0x2000000000000000000000000000000000000002");
+    const REVERTER: Address = address!("0x3000000000000000000000000000000000000003");
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            REVERTER,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(vec![
+                opcode::PUSH0,
+                opcode::PUSH0,
+                opcode::REVERT,
+            ]))),
+        );
+        db
+    }
+
+    fn tx(nonce: u64, to: Address, value: u64) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(to),
+            gas_limit: 100_000,
+            gas_price: 2,
+            value: U256::from(value),
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn discard_allowed_reverts() {
+        let mut evm = Context::mainnet()
+            .modify_block_chained(|block| {
+                block.beneficiary = COINBASE;
+                block.basefee = 1;
+            })
+            .with_db(db())
+            .build_mainnet();
+
+        let outcome = execute_bundle(
+            &mut evm,
+            [
+                BundleTx::new(tx(0, COINBASE, 100)),
+                BundleTx::revert_allowed(tx(1, REVERTER, 0)),
+                BundleTx::new(tx(1, COINBASE, 0)),
+            ],
+        )
+        .unwrap();
+
+        let [first, reverted, last] = &outcome.transactions[..] else {
+            panic!("three transactions");
+        };
+        assert_eq!(first.effective_gas_price, 2);
+        assert_eq!(first.gas_used, 21_000);
+        // Direct payment and the priority fee of one wei per gas.
+        assert_eq!(first.coinbase_payment, U256::from(100 + 21_000));
+        assert!(reverted.discarded && !reverted.result.is_success());
+        // The reverted transaction is discarded, so its nonce is used again.
+        assert!(!last.discarded);
+        assert_eq!(outcome.gas_used, 42_000);
+        assert_eq!(outcome.coinbase_payment, U256::from(100 + 42_000));
+        assert_eq!(evm.ctx().db().basic(CALLER).unwrap().unwrap().nonce, 2);
+    }
+
+    #[test]
+    fn abort_on_disallowed_revert() {
+        let mut evm = Context::mainnet()
+            .modify_block_chained(|block| block.beneficiary = COINBASE)
+            .with_db(db())
+            .build_mainnet();
+
+        let error = execute_bundle(
+            &mut evm,
+            [
+                BundleTx::new(tx(0, COINBASE, 100)),
+                BundleTx::new(tx(1, REVERTER, 0)),
+            ],
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, BundleError::Reverted { index: 1, .. }));
+        let db = evm.ctx().db();
+        assert_eq!(db.basic(CALLER).unwrap().unwrap().nonce, 0);
+        assert_eq!(db.basic(COINBASE).unwrap(), None);
+        assert_eq!(db.snapshot_count(), 0);
+    }
+}
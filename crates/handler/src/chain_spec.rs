@@ -0,0 +1,640 @@
+//! Declarative specification of EVM chains that build on the Ethereum execution.
+//!
+//! Most L2s and app-chains differ from Ethereum in a handful of places: their own hardfork
+//! enum, extra precompiles, additional transaction types, where transaction fees go and
+//! system calls at the start of every block. [`ChainSpec`] bundles these so a chain is
+//! declared by implementing a single trait on a marker type:
+//!
+//!   * [`ChainEvm`] executes transactions with [`ChainHandler`] and [`ChainPrecompiles`].
+//!   * [`ChainBlockExecutor`] executes blocks and runs the [`ChainSpec::system_calls`].
+//!
+//! Chains that need to change the execution itself, like the OP Stack deposits, still
+//! implement [`Handler`] directly.
+use crate::{
+    block::{BlockExecutor, BlockExecutorDb, BlockStateDb, MAINNET_DEPOSIT_CONTRACT_ADDRESS},
+    evm::EvmTr,
+    instructions::{EthInstructions, InstructionProvider},
+    system_call::{SystemCall, SystemCallCommitEvm, SystemCallEvm, SystemCallTx},
+    validation, EthFrame, EthPrecompiles, EvmTrError, ExecuteCommitEvm, ExecuteEvm, Frame,
+    FrameResult, Handler, PrecompileProvider,
+};
+use context::{ContextSetters, Evm, JournalOutput};
+use context_interface::{
+    result::{EVMError, ExecutionResult, HaltReasonTr, InvalidTransaction, ResultAndState},
+    transaction::TransactionType,
+    Block, Cfg, ContextTr, Database, JournalTr, Transaction,
+};
+use core::fmt::Debug;
+use database_interface::DatabaseCommit;
+use interpreter::{
+    interpreter::EthInterpreter, FrameInput, Gas, InputsImpl, Interpreter, InterpreterResult,
+    InterpreterTypes,
+};
+use precompile::{PrecompileSpecId, Precompiles};
+use primitives::{hardfork::SpecId, Address, Bytes, U256};
+use std::{boxed::Box, string::String, vec::Vec};
+
+/// Specification of an EVM chain.
+///
+/// All methods have defaults that match Ethereum, so a chain only overrides what differs.
+pub trait ChainSpec: Clone + Debug + 'static {
+    /// Hardfork enum of the chain, mapped to the Ethereum [`SpecId`] it builds on.
+    type Spec: Into<SpecId> + Copy + Default + PartialEq + Debug;
+    /// Halt reason included in the execution results.
+    type HaltReason: HaltReasonTr;
+
+    /// Returns the precompiles of the given spec.
+    fn precompiles(spec: Self::Spec) -> &'static Precompiles {
+        Precompiles::new(PrecompileSpecId::from_spec_id(spec.into()))
+    }
+
+    /// Returns whether the chain accepts the given non-Ethereum transaction type.
+    fn is_extra_tx_type_supported(_spec: Self::Spec, _tx_type: u8) -> bool {
+        false
+    }
+
+    /// Returns the recipients of the transaction fees.
+    fn fee_recipients(_spec: Self::Spec) -> FeeRecipients {
+        FeeRecipients::default()
+    }
+
+    /// Returns the system calls executed before the transactions of the block.
+    ///
+    /// The system contracts must exist. Skipped for the genesis block.
+    fn system_calls(_spec: Self::Spec, _block: &impl Block) -> Vec<SystemCall> {
+        Vec::new()
+    }
+
+    /// Returns the address of the [EIP-6110] deposit contract, `None` disables deposit requests.
+    ///
+    /// [EIP-6110]: https://eips.ethereum.org/EIPS/eip-6110
+    fn deposit_contract_address() -> Option<Address> {
+        Some(MAINNET_DEPOSIT_CONTRACT_ADDRESS)
+    }
+}
+
+/// Recipients of the transaction fees.
+///
+/// The default matches Ethereum: the priority fee goes to the block beneficiary and the base
+/// fee is burned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeRecipients {
+    /// Receives the priority fee, the block beneficiary if `None`.
+    pub priority_fee: Option<Address>,
+    /// Receives the base fee, burned if `None`.
+    pub base_fee: Option<Address>,
+}
+
+/// Transfers the transaction fees to the [`ChainSpec::fee_recipients`].
+///
+/// Before London the whole effective gas price is the priority fee.
+pub fn reward_fee_recipients<CS, CTX>(
+    context: &mut CTX,
+    gas: &Gas,
+) -> Result<(), <CTX::Db as Database>::Error>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<Cfg: Cfg<Spec = CS::Spec>>,
+{
+    let spec = context.cfg().spec();
+    let recipients = CS::fee_recipients(spec);
+    let block = context.block();
+    let beneficiary = block.beneficiary();
+    let basefee = block.basefee() as u128;
+    let effective_gas_price = context.tx().effective_gas_price(basefee);
+    let gas_used = (gas.spent() - gas.refunded() as u64) as u128;
+
+    let eth_spec: SpecId = spec.into();
+    let (base_fee_price, priority_fee_price) = if eth_spec.is_enabled_in(SpecId::LONDON) {
+        (
+            effective_gas_price.min(basefee),
+            effective_gas_price.saturating_sub(basefee),
+        )
+    } else {
+        (0, effective_gas_price)
+    };
+
+    let rewards = [
+        (
+            Some(recipients.priority_fee.unwrap_or(beneficiary)),
+            priority_fee_price,
+        ),
+        (recipients.base_fee, base_fee_price),
+    ];
+    for (address, price) in rewards {
+        let Some(address) = address else {
+            continue;
+        };
+        let account = context.journal().load_account(address)?;
+        account.data.mark_touch();
+        account.data.info.balance = account
+            .data
+            .info
+            .balance
+            .saturating_add(U256::from(price * gas_used));
+    }
+    Ok(())
+}
+
+/// The [`PrecompileProvider`] that follows the [`ChainSpec::precompiles`] schedule.
+#[derive(Clone, Debug)]
+pub struct ChainPrecompiles<CS: ChainSpec> {
+    /// Precompiles of the current spec.
+    pub inner: EthPrecompiles,
+    /// Current spec. `None` means that spec was not set yet.
+    pub spec: Option<CS::Spec>,
+}
+
+impl<CS: ChainSpec> Default for ChainPrecompiles<CS> {
+    fn default() -> Self {
+        let spec = CS::Spec::default();
+        Self {
+            inner: EthPrecompiles {
+                precompiles: CS::precompiles(spec),
+                spec: spec.into(),
+            },
+            spec: None,
+        }
+    }
+}
+
+impl<CS, CTX> PrecompileProvider<CTX> for ChainPrecompiles<CS>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<Cfg: Cfg<Spec = CS::Spec>>,
+{
+    type Output = InterpreterResult;
+
+    fn set_spec(&mut self, spec: CS::Spec) -> bool {
+        if self.spec == Some(spec) {
+            return false;
+        }
+        self.inner = EthPrecompiles {
+            precompiles: CS::precompiles(spec),
+            spec: spec.into(),
+        };
+        self.spec = Some(spec);
+        true
+    }
+
+    fn run(
+        &mut self,
+        context: &mut CTX,
+        address: &Address,
+        inputs: &InputsImpl,
+        is_static: bool,
+        gas_limit: u64,
+    ) -> Result<Option<Self::Output>, String> {
+        <EthPrecompiles as PrecompileProvider<CTX>>::run(
+            &mut self.inner,
+            context,
+            address,
+            inputs,
+            is_static,
+            gas_limit,
+        )
+    }
+
+    fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
+        self.inner.warm_addresses()
+    }
+
+    fn contains(&self, address: &Address) -> bool {
+        self.inner.contains(address)
+    }
+}
+
+/// The [`Handler`] of chains declared with a [`ChainSpec`].
+///
+/// Rejects transaction types the chain does not support and pays the fees to the
+/// [`ChainSpec::fee_recipients`]. Everything else is the mainnet execution.
+pub struct ChainHandler<CS, EVM, ERROR, FRAME> {
+    _phantom: core::marker::PhantomData<(CS, EVM, ERROR, FRAME)>,
+}
+
+impl<CS, EVM, ERROR, FRAME> ChainHandler<CS, EVM, ERROR, FRAME> {
+    /// Creates a new handler.
+    pub fn new() -> Self {
+        Self {
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<CS, EVM, ERROR, FRAME> Default for ChainHandler<CS, EVM, ERROR, FRAME> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CS, EVM, ERROR, FRAME> Handler for ChainHandler<CS, EVM, ERROR, FRAME>
+where
+    CS: ChainSpec,
+    EVM: EvmTr<
+        Context: ContextTr<
+            Journal: JournalTr<FinalOutput = JournalOutput>,
+            Cfg: Cfg<Spec = CS::Spec>,
+        >,
+    >,
+    ERROR: EvmTrError<EVM>,
+    FRAME: Frame<Evm = EVM, Error = ERROR, FrameResult = FrameResult, FrameInit = FrameInput>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type Frame = FRAME;
+    type HaltReason = CS::HaltReason;
+
+    fn validate_env(&self, evm: &mut Self::Evm) -> Result<(), Self::Error> {
+        let ctx = evm.ctx_ref();
+        let tx_type = ctx.tx().tx_type();
+        if TransactionType::from(tx_type) == TransactionType::Custom
+            && !CS::is_extra_tx_type_supported(ctx.cfg().spec(), tx_type)
+        {
+            return Err(InvalidTransaction::TxTypeNotSupported { tx_type }.into());
+        }
+        validation::validate_env(evm.ctx())
+    }
+
+    fn reward_beneficiary(
+        &self,
+        evm: &mut Self::Evm,
+        exec_result: &mut <Self::Frame as Frame>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        reward_fee_recipients::<CS, _>(evm.ctx(), exec_result.gas()).map_err(From::from)
+    }
+}
+
+/// EVM of a chain declared with a [`ChainSpec`].
+pub struct ChainEvm<CS: ChainSpec, CTX, INSP = ()>(
+    pub Evm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, ChainPrecompiles<CS>>,
+);
+
+impl<CS: ChainSpec, CTX: ContextTr> ChainEvm<CS, CTX> {
+    /// Creates a new EVM with the chain precompiles.
+    pub fn new(ctx: CTX) -> Self {
+        Self(Evm {
+            ctx,
+            inspector: (),
+            instruction: EthInstructions::new_mainnet(),
+            precompiles: ChainPrecompiles::default(),
+        })
+    }
+}
+
+impl<CS, CTX, INSP> EvmTr for ChainEvm<CS, CTX, INSP>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<Cfg: Cfg<Spec = CS::Spec>>,
+{
+    type Context = CTX;
+    type Instructions = EthInstructions<EthInterpreter, CTX>;
+    type Precompiles = ChainPrecompiles<CS>;
+
+    fn run_interpreter(
+        &mut self,
+        interpreter: &mut Interpreter<
+            <Self::Instructions as InstructionProvider>::InterpreterTypes,
+        >,
+    ) -> <<Self::Instructions as InstructionProvider>::InterpreterTypes as InterpreterTypes>::Output
+    {
+        self.0.run_interpreter(interpreter)
+    }
+
+    fn ctx(&mut self) -> &mut Self::Context {
+        self.0.ctx()
+    }
+
+    fn ctx_ref(&self) -> &Self::Context {
+        self.0.ctx_ref()
+    }
+
+    fn ctx_instructions(&mut self) -> (&mut Self::Context, &mut Self::Instructions) {
+        self.0.ctx_instructions()
+    }
+
+    fn ctx_precompiles(&mut self) -> (&mut Self::Context, &mut Self::Precompiles) {
+        self.0.ctx_precompiles()
+    }
+}
+
+/// Type alias for the error type of the [`ChainEvm`].
+type ChainError<CTX> = EVMError<<<CTX as ContextTr>::Db as Database>::Error, InvalidTransaction>;
+
+impl<CS, CTX, INSP> ExecuteEvm for ChainEvm<CS, CTX, INSP>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<Journal: JournalTr<FinalOutput = JournalOutput>, Cfg: Cfg<Spec = CS::Spec>>
+        + ContextSetters,
+{
+    type Output = Result<ResultAndState<CS::HaltReason>, ChainError<CTX>>;
+
+    type Tx = <CTX as ContextTr>::Tx;
+
+    type Block = <CTX as ContextTr>::Block;
+
+    fn set_tx(&mut self, tx: Self::Tx) {
+        self.0.ctx.set_tx(tx);
+    }
+
+    fn set_block(&mut self, block: Self::Block) {
+        self.0.ctx.set_block(block);
+    }
+
+    fn replay(&mut self) -> Self::Output {
+        let mut h = ChainHandler::<CS, _, _, EthFrame<_, _, _>>::new();
+        h.run(self)
+    }
+}
+
+impl<CS, CTX, INSP> ExecuteCommitEvm for ChainEvm<CS, CTX, INSP>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<
+            Journal: JournalTr<FinalOutput = JournalOutput>,
+            Cfg: Cfg<Spec = CS::Spec>,
+            Db: DatabaseCommit,
+        > + ContextSetters,
+{
+    type CommitOutput = Result<ExecutionResult<CS::HaltReason>, ChainError<CTX>>;
+
+    fn replay_commit(&mut self) -> Self::CommitOutput {
+        self.replay().map(|r| {
+            self.ctx().db().commit(r.state);
+            r.result
+        })
+    }
+}
+
+impl<CS, CTX, INSP> SystemCallEvm for ChainEvm<CS, CTX, INSP>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<
+            Journal: JournalTr<FinalOutput = JournalOutput>,
+            Cfg: Cfg<Spec = CS::Spec>,
+            Tx: SystemCallTx,
+        > + ContextSetters,
+{
+    fn transact_system_call(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Self::Output {
+        self.set_tx(CTX::Tx::new_system_tx(data, system_contract_address));
+        let mut h = ChainHandler::<CS, _, _, EthFrame<_, _, _>>::new();
+        h.run_system_call(self)
+    }
+}
+
+impl<CS, CTX, INSP> SystemCallCommitEvm for ChainEvm<CS, CTX, INSP>
+where
+    CS: ChainSpec,
+    CTX: ContextTr<
+            Journal: JournalTr<FinalOutput = JournalOutput>,
+            Cfg: Cfg<Spec = CS::Spec>,
+            Db: DatabaseCommit,
+            Tx: SystemCallTx,
+        > + ContextSetters,
+{
+    fn transact_system_call_commit(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Self::CommitOutput {
+        self.transact_system_call(system_contract_address, data)
+            .map(|r| {
+                self.ctx().db().commit(r.state);
+                r.result
+            })
+    }
+}
+
+/// The [`BlockExecutor`] of chains declared with a [`ChainSpec`].
+///
+/// Runs the [`ChainSpec::system_calls`] after the Ethereum pre-block system calls.
+#[derive(Debug)]
+pub struct ChainBlockExecutor<CS, EVM, ERROR> {
+    /// Address of the deposit contract, `None` disables deposit requests.
+    pub deposit_contract_address: Option<Address>,
+    _phantom: core::marker::PhantomData<(CS, EVM, ERROR)>,
+}
+
+impl<CS: ChainSpec, EVM, ERROR> ChainBlockExecutor<CS, EVM, ERROR> {
+    /// Creates a new executor with the [`ChainSpec::deposit_contract_address`].
+    pub fn new() -> Self {
+        Self {
+            deposit_contract_address: CS::deposit_contract_address(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets the deposit contract address.
+    pub fn with_deposit_contract_address(mut self, address: Option<Address>) -> Self {
+        self.deposit_contract_address = address;
+        self
+    }
+}
+
+impl<CS: ChainSpec, EVM, ERROR> Default for ChainBlockExecutor<CS, EVM, ERROR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CS, EVM, ERROR> BlockExecutor for ChainBlockExecutor<CS, EVM, ERROR>
+where
+    CS: ChainSpec,
+    EVM: EvmTr<Context: ContextTr<Db: BlockStateDb, Cfg: Cfg<Spec = CS::Spec>>>
+        + SystemCallCommitEvm<CommitOutput = Result<ExecutionResult<CS::HaltReason>, ERROR>>,
+    ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type HaltReason = CS::HaltReason;
+
+    fn deposit_contract_address(&self) -> Option<Address> {
+        self.deposit_contract_address
+    }
+
+    fn pre_block_system_calls(&self, evm: &Self::Evm) -> Vec<SystemCall> {
+        let ctx = evm.ctx_ref();
+        CS::system_calls(ctx.cfg().spec(), ctx.block())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::BlockInput, MainContext};
+    use context::{BlockEnv, CfgEnv, Context, TxEnv};
+    use context_interface::result::HaltReason;
+    use database::{InMemoryDB, State};
+    use precompile::{PrecompileOutput, PrecompileResult, PrecompileWithAddress};
+    use primitives::{address, bytes, TxKind};
+    use state::{AccountInfo, Bytecode};
+    use std::{sync::OnceLock, vec};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const ECHO: Address = address!("0x0000000000000000000000000000000000000100");
+    const BASE_FEE_VAULT: Address = address!("0x2000000000000000000000000000000000000002");
+    const SYSTEM_CONTRACT: Address = address!("0x3000000000000000000000000000000000000003");
+    const APP_TX_TYPE: u8 = 0x64;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    enum AppSpec {
+        Genesis,
+        #[default]
+        V1,
+    }
+
+    impl From<AppSpec> for SpecId {
+        fn from(spec: AppSpec) -> Self {
+            match spec {
+                AppSpec::Genesis => SpecId::CANCUN,
+                AppSpec::V1 => SpecId::PRAGUE,
+            }
+        }
+    }
+
+    fn echo(input: &[u8], _gas_limit: u64) -> PrecompileResult {
+        Ok(PrecompileOutput::new(15, Bytes::copy_from_slice(input)))
+    }
+
+    /// App-chain with an echo precompile and a custom transaction type from `V1`, a base fee
+    /// vault and a system call that stores the block number.
+    #[derive(Clone, Debug)]
+    struct AppChain;
+
+    impl ChainSpec for AppChain {
+        type Spec = AppSpec;
+        type HaltReason = HaltReason;
+
+        fn precompiles(spec: AppSpec) -> &'static Precompiles {
+            static V1: OnceLock<Precompiles> = OnceLock::new();
+            match spec {
+                AppSpec::Genesis => Precompiles::cancun(),
+                AppSpec::V1 => V1.get_or_init(|| {
+                    let mut precompiles = Precompiles::prague().clone();
+                    precompiles.extend([PrecompileWithAddress(ECHO, echo)]);
+                    precompiles
+                }),
+            }
+        }
+
+        fn is_extra_tx_type_supported(spec: AppSpec, tx_type: u8) -> bool {
+            spec == AppSpec::V1 && tx_type == APP_TX_TYPE
+        }
+
+        fn fee_recipients(_spec: AppSpec) -> FeeRecipients {
+            FeeRecipients {
+                base_fee: Some(BASE_FEE_VAULT),
+                ..Default::default()
+            }
+        }
+
+        fn system_calls(_spec: AppSpec, block: &impl Block) -> Vec<SystemCall> {
+            vec![SystemCall {
+                address: SYSTEM_CONTRACT,
+                data: U256::from(block.number()).to_be_bytes_vec().into(),
+            }]
+        }
+    }
+
+    fn caller_db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db
+    }
+
+    fn call_echo(tx_type: u8) -> TxEnv {
+        TxEnv {
+            tx_type,
+            caller: CALLER,
+            kind: TxKind::Call(ECHO),
+            data: bytes!("01"),
+            gas_limit: 100_000,
+            gas_price: 12,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn precompile_schedule_and_fee_recipients() {
+        let ctx = Context::mainnet()
+            .with_db(caller_db())
+            .with_cfg(CfgEnv::new_with_spec(AppSpec::V1))
+            .with_block(BlockEnv {
+                basefee: 10,
+                ..Default::default()
+            });
+        let mut evm = ChainEvm::<AppChain, _>::new(ctx);
+
+        let ResultAndState { result, state } = evm.transact(call_echo(0)).unwrap();
+        assert_eq!(result.output(), Some(&bytes!("01")));
+        // Execution spends 21031 gas: 21000 intrinsic gas, 16 for the calldata byte and 15 for
+        // the precompile. The Prague calldata floor of 21000 + 10 * 4 tokens is higher.
+        assert_eq!(result.gas_used(), 21_040);
+        assert_eq!(state[&BASE_FEE_VAULT].info.balance, U256::from(10 * 21_040));
+        assert_eq!(state[&Address::ZERO].info.balance, U256::from(2 * 21_040));
+
+        evm.0.ctx.modify_cfg(|cfg| cfg.spec = AppSpec::Genesis);
+        let result = evm.transact(call_echo(0)).unwrap().result;
+        assert_eq!(result.output(), Some(&Bytes::new()));
+    }
+
+    #[test]
+    fn extra_tx_types() {
+        let ctx = Context::mainnet()
+            .with_db(caller_db())
+            .with_cfg(CfgEnv::new_with_spec(AppSpec::V1));
+        let mut evm = ChainEvm::<AppChain, _>::new(ctx);
+
+        assert!(evm.transact(call_echo(APP_TX_TYPE)).is_ok());
+        assert_eq!(
+            evm.transact(call_echo(0x7F)).unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::TxTypeNotSupported { tx_type: 0x7F })
+        );
+
+        evm.0.ctx.modify_cfg(|cfg| cfg.spec = AppSpec::Genesis);
+        assert_eq!(
+            evm.transact(call_echo(APP_TX_TYPE)).unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::TxTypeNotSupported {
+                tx_type: APP_TX_TYPE
+            })
+        );
+    }
+
+    #[test]
+    fn block_system_calls() {
+        // CALLDATALOAD(0) is stored in slot 0.
+        let mut db = caller_db();
+        db.insert_account_info(
+            SYSTEM_CONTRACT,
+            AccountInfo::default().with_code(Bytecode::new_legacy(bytes!("60003560005500"))),
+        );
+        let mut state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        let ctx = Context::mainnet()
+            .with_db(&mut state)
+            .with_cfg(CfgEnv::new_with_spec(AppSpec::Genesis));
+        let mut evm = ChainEvm::<AppChain, _>::new(ctx);
+
+        let block = BlockEnv {
+            number: 5,
+            ..Default::default()
+        };
+        let mut executor = ChainBlockExecutor::<AppChain, _, _>::new();
+        assert_eq!(
+            executor.deposit_contract_address,
+            Some(MAINNET_DEPOSIT_CONTRACT_ADDRESS)
+        );
+        let output = executor
+            .execute_block(&mut evm, BlockInput::new(block, vec![]))
+            .unwrap();
+
+        let storage = &output.bundle_state.state[&SYSTEM_CONTRACT].storage;
+        assert_eq!(storage[&U256::ZERO].present_value, U256::from(5));
+    }
+}
@@ -0,0 +1,276 @@
+//! Gas estimation, similar to `eth_estimateGas`.
+use crate::{EvmTr, ExecuteEvm};
+use context::TxEnv;
+use context_interface::{
+    result::{ExecutionResult, HaltReason, ResultAndState},
+    Cfg, ContextTr, Transaction,
+};
+use interpreter::gas::{calculate_initial_tx_gas_for_tx, InitialAndFloorGas, CALL_STIPEND};
+use primitives::Bytes;
+
+/// Transaction whose gas limit can be changed, used by [`estimate_gas`] to execute the
+/// transaction with different gas limits.
+pub trait EstimateGasTx: Transaction + Clone {
+    /// Sets the gas limit of the transaction.
+    fn set_gas_limit(&mut self, gas_limit: u64);
+}
+
+impl EstimateGasTx for TxEnv {
+    fn set_gas_limit(&mut self, gas_limit: u64) {
+        self.gas_limit = gas_limit;
+    }
+}
+
+/// Outcome of the gas estimation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GasEstimate<HaltReasonTy = HaltReason> {
+    /// Transaction succeeds with the gas limit.
+    Success {
+        /// Lowest gas limit the transaction succeeds with.
+        gas_limit: u64,
+        /// Execution result with the estimated gas limit.
+        result: ExecutionResult<HaltReasonTy>,
+    },
+    /// Transaction reverts with the highest gas limit, so it reverts with every gas limit.
+    Revert {
+        /// Gas used with the highest gas limit.
+        gas_used: u64,
+        /// Revert data.
+        output: Bytes,
+    },
+    /// Transaction halts with the highest gas limit, so it halts with every gas limit.
+    Halt {
+        /// Reason of the halt.
+        reason: HaltReasonTy,
+        /// Gas used with the highest gas limit.
+        gas_used: u64,
+    },
+}
+
+impl<HaltReasonTy> GasEstimate<HaltReasonTy> {
+    /// Returns the estimated gas limit if the transaction succeeds.
+    pub fn gas_limit(&self) -> Option<u64> {
+        match self {
+            Self::Success { gas_limit, .. } => Some(*gas_limit),
+            _ => None,
+        }
+    }
+}
+
+/// Estimates the lowest gas limit the transaction succeeds with.
+///
+/// The gas limit of the transaction is the highest gas limit that is tried, so it should be
+/// capped by the caller, for example with the block gas limit and the gas the sender can pay
+/// for. If the transaction reverts or halts with it, the failure is returned.
+///
+/// The gas used by a successful execution is not the gas limit the transaction needs, as the
+/// refunds are subtracted from it and calls can only forward 63/64 of the available gas. The
+/// search starts with an optimistic limit, the gas spent before refunds together with the call
+/// stipend scaled by 64/63, and continues with a binary search between the gas used and the
+/// highest gas limit. The lower bound is never below the intrinsic gas and the [EIP-7623]
+/// calldata floor, so the executions do not fail validation.
+///
+/// State changes are not committed. Any error of the execution is returned as is.
+///
+/// [EIP-7623]: https://eips.ethereum.org/EIPS/eip-7623
+pub fn estimate_gas<EVM, HaltReasonTy, ERROR>(
+    evm: &mut EVM,
+    mut tx: EVM::Tx,
+) -> Result<GasEstimate<HaltReasonTy>, ERROR>
+where
+    EVM:
+        ExecuteEvm<Tx: EstimateGasTx, Output = Result<ResultAndState<HaltReasonTy>, ERROR>> + EvmTr,
+{
+    let spec = evm.ctx_ref().cfg().spec().into();
+    let InitialAndFloorGas {
+        initial_gas,
+        floor_gas,
+    } = calculate_initial_tx_gas_for_tx(&tx, spec);
+
+    let mut hi = tx.gas_limit();
+    let mut best = evm.transact(tx.clone())?.result;
+    let (gas_used, gas_refunded) = match best {
+        ExecutionResult::Success {
+            gas_used,
+            gas_refunded,
+            ..
+        } => (gas_used, gas_refunded),
+        ExecutionResult::Revert { gas_used, output } => {
+            return Ok(GasEstimate::Revert { gas_used, output })
+        }
+        ExecutionResult::Halt { reason, gas_used } => {
+            return Ok(GasEstimate::Halt { reason, gas_used })
+        }
+    };
+    // Highest gas limit the transaction fails with.
+    let mut lo = gas_used.max(initial_gas).max(floor_gas).saturating_sub(1);
+
+    let mut execute = |gas_limit: u64| -> Result<Option<ExecutionResult<HaltReasonTy>>, ERROR> {
+        tx.set_gas_limit(gas_limit);
+        let result = evm.transact(tx.clone())?.result;
+        Ok(result.is_success().then_some(result))
+    };
+
+    let optimistic = (gas_used + gas_refunded + CALL_STIPEND) * 64 / 63;
+    if lo < optimistic && optimistic < hi {
+        match execute(optimistic)? {
+            Some(result) => {
+                hi = optimistic;
+                best = result;
+            }
+            None => lo = optimistic,
+        }
+    }
+
+    while lo + 1 < hi {
+        // The needed gas is usually close to the lower bound, so the step is capped.
+        let mid = (lo + (hi - lo) / 2).min(lo * 2);
+        match execute(mid)? {
+            Some(result) => {
+                hi = mid;
+                best = result;
+            }
+            None => lo = mid,
+        }
+    }
+
+    Ok(GasEstimate::Success {
+        gas_limit: hi,
+        result: best,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MainBuilder, MainContext};
+    use context::{CfgEnv, Context};
+    use database::InMemoryDB;
+    use primitives::{address, hardfork::SpecId, Address, TxKind, U256};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    const CALLEE: Address = address!("0x3000000000000000000000000000000000000003");
+
+    fn db(target: &[u8]) -> InMemoryDB {
+        // SSTORE(1, 0), refunds the cleared slot, and burns gas with SLOADs.
+        let mut callee = vec![opcode::PUSH1, 0, opcode::PUSH1, 1, opcode::SSTORE];
+        for slot in 2..40 {
+            callee.extend([opcode::PUSH1, slot, opcode::SLOAD, opcode::POP]);
+        }
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(target.to_vec()))),
+        );
+        db.insert_account_info(
+            CALLEE,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(callee))),
+        );
+        db.insert_account_storage(CALLEE, U256::from(1), U256::from(1))
+            .unwrap();
+        db
+    }
+
+    fn tx(data: Bytes) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 1_000_000,
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn run_estimate(target: &[u8], tx: TxEnv, spec: SpecId) -> (GasEstimate, InMemoryDB) {
+        let db = db(target);
+        let mut cfg = CfgEnv::default();
+        cfg.spec = spec;
+        let mut evm = Context::mainnet()
+            .with_db(db.clone())
+            .with_cfg(cfg)
+            .build_mainnet();
+        (estimate_gas(&mut evm, tx).unwrap(), db)
+    }
+
+    /// Returns `true` if the transaction succeeds with the gas limit.
+    fn succeeds(db: &InMemoryDB, tx: TxEnv, gas_limit: u64, spec: SpecId) -> bool {
+        let mut cfg = CfgEnv::default();
+        cfg.spec = spec;
+        let mut evm = Context::mainnet()
+            .with_db(db.clone())
+            .with_cfg(cfg)
+            .build_mainnet();
+        evm.transact(TxEnv { gas_limit, ..tx })
+            .is_ok_and(|result| result.result.is_success())
+    }
+
+    #[test]
+    fn call_with_refund() {
+        // CALL(GAS, CALLEE, 0, 0, 0, 0, 0) and revert if it fails.
+        let mut target = vec![opcode::PUSH1, 0, opcode::DUP1, opcode::DUP1, opcode::DUP1];
+        target.extend([opcode::DUP1, opcode::PUSH20]);
+        target.extend(CALLEE.as_slice());
+        target.extend([
+            opcode::GAS,
+            opcode::CALL,
+            opcode::PUSH1,
+            0x24,
+            opcode::JUMPI,
+        ]);
+        target.extend([opcode::PUSH1, 0, opcode::DUP1, opcode::REVERT]);
+        target.extend([opcode::JUMPDEST, opcode::STOP]);
+        assert_eq!(target[0x24], opcode::JUMPDEST);
+
+        let tx = tx(Bytes::new());
+        let (estimate, db) = run_estimate(&target, tx.clone(), SpecId::CANCUN);
+        let GasEstimate::Success { gas_limit, result } = estimate else {
+            panic!("transaction should succeed");
+        };
+        let ExecutionResult::Success {
+            gas_used,
+            gas_refunded,
+            ..
+        } = result
+        else {
+            panic!("transaction should succeed");
+        };
+        assert!(gas_refunded > 0);
+        // Refunds and the gas kept by the caller make the limit higher than the gas spent.
+        assert!(gas_limit > gas_used + gas_refunded);
+        assert!(succeeds(&db, tx.clone(), gas_limit, SpecId::CANCUN));
+        assert!(!succeeds(&db, tx, gas_limit - 1, SpecId::CANCUN));
+    }
+
+    #[test]
+    fn calldata_floor() {
+        let tx = tx(Bytes::from(vec![1u8; 1000]));
+        let target = [opcode::STOP];
+        let (estimate, _) = run_estimate(&target, tx.clone(), SpecId::PRAGUE);
+        // 21000 + 1000 tokens * 4 * 10
+        assert_eq!(estimate.gas_limit(), Some(61_000));
+
+        let (estimate, _) = run_estimate(&target, tx, SpecId::CANCUN);
+        assert_eq!(estimate.gas_limit(), Some(37_000));
+    }
+
+    #[test]
+    fn revert_and_halt() {
+        let target = [opcode::PUSH1, 0, opcode::DUP1, opcode::REVERT];
+        let (estimate, _) = run_estimate(&target, tx(Bytes::new()), SpecId::CANCUN);
+        assert!(matches!(estimate, GasEstimate::Revert { .. }));
+
+        let target = [opcode::INVALID];
+        let (estimate, _) = run_estimate(&target, tx(Bytes::new()), SpecId::CANCUN);
+        assert!(matches!(
+            estimate,
+            GasEstimate::Halt {
+                gas_used: 1_000_000,
+                ..
+            }
+        ));
+    }
+}
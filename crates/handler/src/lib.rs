@@ -8,6 +8,10 @@ extern crate alloc as std;
 // Mainnet related handlers.
 
 pub mod api;
+pub mod block;
+pub mod bundle;
+pub mod chain_spec;
+pub mod estimate_gas;
 pub mod evm;
 pub mod execution;
 mod frame;
@@ -17,14 +21,26 @@ pub mod instructions;
 mod item_or_result;
 mod mainnet_builder;
 mod mainnet_handler;
+#[cfg(feature = "std")]
+pub mod parallel;
 pub mod post_execution;
 pub mod pre_execution;
 mod precompile_provider;
+pub mod simulate;
 pub mod system_call;
 pub mod validation;
 
 // Public exports
 pub use api::{ExecuteCommitEvm, ExecuteEvm};
+pub use block::{
+    BlockExecutionError, BlockExecutionOutput, BlockExecutor, BlockInput, BlockStateDb,
+    MainnetBlockExecutor, Receipt,
+};
+pub use bundle::{execute_bundle, BundleError, BundleOutcome, BundleTx, BundleTxOutcome};
+pub use chain_spec::{
+    ChainBlockExecutor, ChainEvm, ChainHandler, ChainPrecompiles, ChainSpec, FeeRecipients,
+};
+pub use estimate_gas::{estimate_gas, EstimateGasTx, GasEstimate};
 pub use evm::EvmTr;
 pub use frame::{return_create, return_eofcreate, ContextTrDbError, EthFrame, Frame};
 pub use frame_data::{FrameData, FrameResult};
@@ -32,5 +48,12 @@ pub use handler::{EvmTrError, Handler};
 pub use item_or_result::{FrameInitOrResult, FrameOrResult, ItemOrResult};
 pub use mainnet_builder::{MainBuilder, MainContext, MainnetContext, MainnetEvm};
 pub use mainnet_handler::MainnetHandler;
-pub use precompile_provider::{EthPrecompiles, PrecompileProvider};
-pub use system_call::{SystemCallCommitEvm, SystemCallEvm, SystemCallTx, SYSTEM_ADDRESS};
+#[cfg(feature = "std")]
+pub use parallel::{ParallelExecutionOutput, ParallelExecutor};
+pub use precompile_provider::{EthPrecompiles, PrecompileProvider, RelocatedPrecompiles};
+pub use simulate::{
+    simulate, SimBlock, SimulateError, SimulateRequest, SimulatedBlock, SimulatedCall, SimulatedLog,
+};
+pub use system_call::{
+    SystemCall, SystemCallCommitEvm, SystemCallEvm, SystemCallTx, SYSTEM_ADDRESS,
+};
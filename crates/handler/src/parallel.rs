@@ -0,0 +1,369 @@
+//! Optimistic parallel execution of block transactions in the style of [Block-STM].
+//!
+//! [`ParallelExecutor`] speculatively executes all transactions of a block on worker threads,
+//! each with its own EVM over the pre-block state, usually a shared [`DatabaseRef`]. The
+//! transactions are then committed in block order with the EVM of the block. A transaction
+//! whose speculative execution read a value written by a previous transaction is executed
+//! again with the block EVM on top of the committed state. This makes the results and the
+//! committed state, including the `BundleState` of a `State` database, identical to
+//! sequential execution.
+//!
+//! Read and write sets are derived from the journaled state returned by every execution. The
+//! [`JournalEntry`](context::JournalEntry) list is cleared when the transaction is finalized,
+//! but the journaled state keeps every account and storage slot the transaction loaded, which
+//! is its read set, and marks the ones it changed, which is its write set.
+//!
+//! [`DatabaseRef`]: database_interface::DatabaseRef
+//! [Block-STM]: https://arxiv.org/abs/2203.06871
+use crate::{block::BlockExecutionError, EvmTr, ExecuteEvm};
+use context_interface::{
+    result::{ExecutionResult, HaltReason, ResultAndState},
+    Cfg, ContextTr, Database,
+};
+use core::num::NonZeroUsize;
+use database_interface::DatabaseCommit;
+use primitives::{hardfork::SpecId, Address, HashSet, StorageKey};
+use state::EvmState;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    vec::Vec,
+};
+
+/// Output of the parallel execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParallelExecutionOutput<HaltReasonTy = HaltReason> {
+    /// Execution result of each transaction.
+    pub results: Vec<ExecutionResult<HaltReasonTy>>,
+    /// Number of transactions that had to be executed again because of a conflict.
+    pub reexecuted: usize,
+}
+
+/// Executor that runs the transactions of a block in parallel.
+///
+/// Only transactions are executed, system calls, rewards and withdrawals have to be applied
+/// by the caller. Transactions that depend on each other, including the ones sharing the
+/// sender or the block beneficiary when priority fees are paid, are executed again in order,
+/// so the speedup depends on how independent the transactions are.
+#[derive(Clone, Copy, Debug)]
+pub struct ParallelExecutor {
+    /// Number of worker threads.
+    threads: NonZeroUsize,
+}
+
+impl Default for ParallelExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParallelExecutor {
+    /// Creates a new executor that uses all available cores.
+    pub fn new() -> Self {
+        Self {
+            threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        }
+    }
+
+    /// Sets the number of worker threads.
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Executes the transactions and commits them to the database of `evm`.
+    ///
+    /// `speculative_evm` builds the EVMs of the worker threads. They have to execute with the
+    /// same configuration and block as `evm`, over the state `evm` had before the call.
+    ///
+    /// Fails with the error of the first invalid transaction, the same one sequential
+    /// execution would fail with. The transactions before it stay committed.
+    pub fn execute<EVM, SEVM, HaltReasonTy, ERROR>(
+        &self,
+        evm: &mut EVM,
+        speculative_evm: impl Fn() -> SEVM + Sync,
+        transactions: Vec<EVM::Tx>,
+    ) -> Result<ParallelExecutionOutput<HaltReasonTy>, BlockExecutionError<ERROR>>
+    where
+        EVM: ExecuteEvm<Tx: Clone + Sync, Output = Result<ResultAndState<HaltReasonTy>, ERROR>>
+            + EvmTr<Context: ContextTr<Db: DatabaseCommit>>,
+        SEVM: ExecuteEvm<Tx = EVM::Tx, Output = Result<ResultAndState<HaltReasonTy>, ERROR>>,
+        HaltReasonTy: Send,
+        ERROR: From<<<EVM::Context as ContextTr>::Db as Database>::Error> + Send,
+    {
+        let mut speculative = self.execute_speculative(&speculative_evm, &transactions);
+
+        // Empty accounts touched by a transaction are removed from Spurious Dragon.
+        let spec: SpecId = evm.ctx_ref().cfg().spec().into();
+        let state_clear = spec.is_enabled_in(SpecId::SPURIOUS_DRAGON);
+        let mut written = WriteSet::default();
+        let mut output = ParallelExecutionOutput {
+            results: Vec::with_capacity(transactions.len()),
+            reexecuted: 0,
+        };
+
+        for (index, tx) in transactions.into_iter().enumerate() {
+            let result = match speculative[index].take().expect("executed") {
+                Ok(result) if !written.conflicts(&result.state) => result,
+                // Failed executions may depend on previous transactions, like the nonce.
+                _ => {
+                    output.reexecuted += 1;
+                    evm.transact(tx)
+                        .map_err(|error| BlockExecutionError::Transaction { index, error })?
+                }
+            };
+
+            let db = evm.ctx().db();
+            for (address, account) in result.state.iter().filter(|(_, a)| a.is_touched()) {
+                // Loads the account before the commit as the EVM would in sequential
+                // execution, which `State` needs to apply the changes.
+                let info =
+                    db.basic(*address)
+                        .map_err(|error| BlockExecutionError::Transaction {
+                            index,
+                            error: error.into(),
+                        })?;
+                // Touched empty accounts, like a beneficiary without fees, are removed, which
+                // changes nothing if the account does not exist.
+                let changed = if state_clear && account.is_empty() {
+                    info.is_some()
+                } else {
+                    info.as_ref() != Some(&account.info)
+                };
+                if changed || account.is_created() || account.is_selfdestructed() {
+                    written.accounts.insert(*address);
+                }
+                written.storage.extend(
+                    account
+                        .changed_storage_slots()
+                        .map(|(key, _)| (*address, *key)),
+                );
+            }
+            db.commit(result.state);
+            output.results.push(result.result);
+        }
+        Ok(output)
+    }
+
+    /// Executes all transactions with the speculative EVMs on the worker threads.
+    fn execute_speculative<SEVM, HaltReasonTy, ERROR>(
+        &self,
+        speculative_evm: &(impl Fn() -> SEVM + Sync),
+        transactions: &[SEVM::Tx],
+    ) -> Vec<Option<SEVM::Output>>
+    where
+        SEVM: ExecuteEvm<Tx: Clone + Sync, Output = Result<ResultAndState<HaltReasonTy>, ERROR>>,
+        HaltReasonTy: Send,
+        ERROR: Send,
+    {
+        let next = AtomicUsize::new(0);
+        let workers = self.threads.get().min(transactions.len());
+
+        let mut speculative: Vec<_> = (0..transactions.len()).map(|_| None).collect();
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut evm = speculative_evm();
+                        let mut executed = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(tx) = transactions.get(index) else {
+                                break;
+                            };
+                            executed.push((index, evm.transact(tx.clone())));
+                        }
+                        executed
+                    })
+                })
+                .collect();
+            for handle in handles {
+                let executed = handle.join().expect("worker thread panicked");
+                for (index, execution) in executed {
+                    speculative[index] = Some(execution);
+                }
+            }
+        });
+        speculative
+    }
+}
+
+/// Accounts and storage slots written by the committed transactions.
+#[derive(Debug, Default)]
+struct WriteSet {
+    /// Accounts whose info changed, or that were created or destroyed.
+    accounts: HashSet<Address>,
+    /// Storage slots whose value changed.
+    storage: HashSet<(Address, StorageKey)>,
+}
+
+impl WriteSet {
+    /// Returns `true` if the journaled state of a transaction loaded a written value.
+    fn conflicts(&self, state: &EvmState) -> bool {
+        state.iter().any(|(address, account)| {
+            self.accounts.contains(address)
+                || account
+                    .storage
+                    .keys()
+                    .any(|key| self.storage.contains(&(*address, *key)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExecuteCommitEvm, MainBuilder, MainContext};
+    use context::{
+        result::{EVMError, InvalidTransaction},
+        BlockEnv, CfgEnv, Context, TxEnv,
+    };
+    use database::{states::bundle_state::BundleRetention, BundleState, InMemoryDB, StateBuilder};
+    use database_interface::WrapDatabaseRef;
+    use primitives::{address, bytes, TxKind, U256};
+    use state::{AccountInfo, Bytecode};
+
+    const COUNTER: Address = address!("0xc000000000000000000000000000000000000000");
+
+    type Output = ParallelExecutionOutput<HaltReason>;
+    type Error = BlockExecutionError<EVMError<core::convert::Infallible, InvalidTransaction>>;
+
+    fn sender(i: u8) -> Address {
+        Address::with_last_byte(i + 1)
+    }
+
+    fn db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        for i in 0..16 {
+            db.insert_account_info(
+                sender(i),
+                AccountInfo::from_balance(U256::from(10u128.pow(18))),
+            );
+        }
+        // SLOAD(0) + 1 -> SSTORE(0)
+        let counter = Bytecode::new_legacy(bytes!("0x60005460010160005500"));
+        db.insert_account_info(COUNTER, AccountInfo::default().with_code(counter));
+        db
+    }
+
+    fn tx(from: u8, nonce: u64, to: Address, gas_price: u128) -> TxEnv {
+        TxEnv {
+            caller: sender(from),
+            kind: TxKind::Call(to),
+            value: U256::from(1),
+            gas_limit: 100_000,
+            gas_price,
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    fn cfg() -> CfgEnv {
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::CANCUN;
+        cfg
+    }
+
+    fn block() -> BlockEnv {
+        BlockEnv {
+            number: 1,
+            beneficiary: address!("0xbe00000000000000000000000000000000000000"),
+            ..Default::default()
+        }
+    }
+
+    /// Executes the transactions in parallel, or in order if `threads` is `None`, and returns
+    /// the output together with the bundle state.
+    fn execute(
+        db: &InMemoryDB,
+        transactions: Vec<TxEnv>,
+        threads: Option<usize>,
+    ) -> Result<(Output, BundleState), Error> {
+        let mut state = StateBuilder::new()
+            .with_database_ref(db)
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::mainnet()
+            .with_db(&mut state)
+            .with_cfg(cfg())
+            .with_block(block())
+            .build_mainnet();
+        let output = match threads {
+            Some(threads) => ParallelExecutor::new()
+                .with_threads(NonZeroUsize::new(threads).unwrap())
+                .execute(
+                    &mut evm,
+                    || {
+                        Context::mainnet()
+                            .with_db(WrapDatabaseRef(db))
+                            .with_cfg(cfg())
+                            .with_block(block())
+                            .build_mainnet()
+                    },
+                    transactions,
+                )?,
+            None => Output {
+                results: transactions
+                    .into_iter()
+                    .map(|tx| evm.transact_commit(tx).unwrap())
+                    .collect(),
+                reexecuted: 0,
+            },
+        };
+        state.merge_transitions(BundleRetention::Reverts);
+        Ok((output, state.take_bundle()))
+    }
+
+    #[test]
+    fn matches_sequential_execution() {
+        let db = db();
+
+        // Independent transfers, two calls incrementing the same slot and a second
+        // transaction of the first sender.
+        let mut transactions: Vec<_> = (0..8)
+            .map(|i| tx(i, 0, Address::with_last_byte(0x80 + i), 0))
+            .collect();
+        transactions.push(tx(8, 0, COUNTER, 0));
+        transactions.push(tx(9, 0, COUNTER, 0));
+        transactions.push(tx(0, 1, sender(1), 0));
+
+        let (output, bundle_state) = execute(&db, transactions.clone(), Some(4)).unwrap();
+        let (sequential, sequential_bundle_state) = execute(&db, transactions, None).unwrap();
+        assert_eq!(output.results, sequential.results);
+        assert_eq!(bundle_state, sequential_bundle_state);
+        assert_eq!(output.reexecuted, 2);
+        assert_eq!(
+            bundle_state.account(&COUNTER).unwrap().storage[&U256::ZERO].present_value,
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn beneficiary_fees_and_invalid_transaction() {
+        let db = db();
+
+        // Every transaction pays the beneficiary so all but the first conflict.
+        let transactions: Vec<_> = (0..16).map(|i| tx(i, 0, COUNTER, 1)).collect();
+        let (output, bundle_state) = execute(&db, transactions.clone(), Some(4)).unwrap();
+        let (sequential, sequential_bundle_state) = execute(&db, transactions, None).unwrap();
+        assert_eq!(output.results, sequential.results);
+        assert_eq!(bundle_state, sequential_bundle_state);
+        assert_eq!(output.reexecuted, 15);
+
+        // Nonce is valid only after the previous transaction of the same sender.
+        let transactions = vec![tx(0, 1, COUNTER, 0), tx(0, 0, COUNTER, 0)];
+        assert!(matches!(
+            execute(&db, transactions, Some(4)),
+            Err(BlockExecutionError::Transaction { index: 0, .. })
+        ));
+        let transactions = vec![
+            tx(0, 0, COUNTER, 0),
+            tx(0, 1, COUNTER, 0),
+            tx(1, 1, COUNTER, 0),
+        ];
+        assert!(matches!(
+            execute(&db, transactions, Some(4)),
+            Err(BlockExecutionError::Transaction { index: 2, .. })
+        ));
+    }
+}
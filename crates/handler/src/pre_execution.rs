@@ -30,6 +30,8 @@ pub fn load_accounts<
     let spec = gen_spec.clone().into();
     // sets eth spec id in journal
     context.journal().set_spec_id(spec);
+    let transfer_logs = context.cfg().is_transfer_log_enabled();
+    context.journal().set_transfer_logs(transfer_logs);
     let precompiles_changed = precompiles.set_spec(gen_spec);
     let empty_warmed_precompiles = context.journal().precompile_addresses().is_empty();
 
@@ -1,12 +1,14 @@
 use auto_impl::auto_impl;
 use context::{Cfg, LocalContextTr};
 use context_interface::ContextTr;
+use database_interface::{OverrideError, StateOverride};
 use interpreter::{CallInput, Gas, InputsImpl, InstructionResult, InterpreterResult};
 use precompile::PrecompileError;
 use precompile::{PrecompileSpecId, Precompiles};
-use primitives::{hardfork::SpecId, Address, Bytes};
+use primitives::{hardfork::SpecId, Address, Bytes, HashMap};
 use std::boxed::Box;
 use std::string::String;
+use std::vec::Vec;
 
 #[auto_impl(&mut, Box)]
 pub trait PrecompileProvider<CTX: ContextTr> {
@@ -145,3 +147,249 @@ impl<CTX: ContextTr> PrecompileProvider<CTX> for EthPrecompiles {
         self.contains(address)
     }
 }
+
+/// [`PrecompileProvider`] that moves precompiles to other addresses, as the
+/// `movePrecompileToAddress` state override of Geth.
+///
+/// A moved precompile runs at its new address, and its original address is no longer a
+/// precompile, so it can be given code. Only precompiles can be moved and they can not be moved
+/// to an address that is a precompile.
+#[derive(Clone, Debug, Default)]
+pub struct RelocatedPrecompiles<P> {
+    /// Wrapped precompile provider.
+    pub inner: P,
+    /// Original addresses of the moved precompiles by their new address.
+    moved: HashMap<Address, Address>,
+    /// Whether precompiles were moved since the warm addresses were last loaded.
+    moves_changed: bool,
+}
+
+impl<P> RelocatedPrecompiles<P> {
+    /// Creates a new provider without moved precompiles.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            moved: HashMap::default(),
+            moves_changed: false,
+        }
+    }
+
+    /// Creates a new provider with the precompile moves of the state overrides.
+    ///
+    /// Precompiles of the wrapped provider are those of the `CTX` it is used with.
+    pub fn with_state_override<CTX>(
+        inner: P,
+        overrides: &StateOverride,
+    ) -> Result<Self, OverrideError>
+    where
+        CTX: ContextTr,
+        P: PrecompileProvider<CTX>,
+    {
+        let mut provider = Self::new(inner);
+        provider.apply_state_override::<CTX>(overrides)?;
+        Ok(provider)
+    }
+
+    /// Returns the original addresses of the moved precompiles by their new address.
+    pub fn moves(&self) -> &HashMap<Address, Address> {
+        &self.moved
+    }
+
+    /// Replaces the moved precompiles, the moves are not validated.
+    pub fn set_moves(&mut self, moves: HashMap<Address, Address>) {
+        self.moved = moves;
+        self.moves_changed = true;
+    }
+
+    /// Moves the precompile at `from` to `to`.
+    pub fn with_move<CTX>(mut self, from: Address, to: Address) -> Result<Self, OverrideError>
+    where
+        CTX: ContextTr,
+        P: PrecompileProvider<CTX>,
+    {
+        self.move_precompile::<CTX>(from, to)?;
+        Ok(self)
+    }
+
+    /// Moves the precompiles of the state overrides.
+    pub fn apply_state_override<CTX>(
+        &mut self,
+        overrides: &StateOverride,
+    ) -> Result<(), OverrideError>
+    where
+        CTX: ContextTr,
+        P: PrecompileProvider<CTX>,
+    {
+        for (from, account) in overrides {
+            if let Some(to) = account.move_precompile_to {
+                self.move_precompile::<CTX>(*from, to)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the precompile at `from` to `to`.
+    ///
+    /// If `from` is the new address of a moved precompile, the precompile is moved again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `from` is not a precompile or `to` is a precompile.
+    pub fn move_precompile<CTX>(&mut self, from: Address, to: Address) -> Result<(), OverrideError>
+    where
+        CTX: ContextTr,
+        P: PrecompileProvider<CTX>,
+    {
+        if !<Self as PrecompileProvider<CTX>>::contains(self, &from) {
+            return Err(OverrideError::NotPrecompile(from));
+        }
+        if <Self as PrecompileProvider<CTX>>::contains(self, &to) {
+            return Err(OverrideError::MoveToPrecompile(to));
+        }
+        let original = self.moved.remove(&from).unwrap_or(from);
+        self.moved.insert(to, original);
+        self.moves_changed = true;
+        Ok(())
+    }
+
+    /// Returns the address of the precompile the address runs, `None` if it was moved away.
+    fn resolve(&self, address: &Address) -> Option<Address> {
+        if let Some(original) = self.moved.get(address) {
+            return Some(*original);
+        }
+        if self.moved.values().any(|original| original == address) {
+            return None;
+        }
+        Some(*address)
+    }
+}
+
+impl<CTX: ContextTr, P: PrecompileProvider<CTX>> PrecompileProvider<CTX>
+    for RelocatedPrecompiles<P>
+{
+    type Output = P::Output;
+
+    fn set_spec(&mut self, spec: <CTX::Cfg as Cfg>::Spec) -> bool {
+        // Warm addresses are reloaded if precompiles were moved.
+        let spec_changed = self.inner.set_spec(spec);
+        core::mem::take(&mut self.moves_changed) || spec_changed
+    }
+
+    fn run(
+        &mut self,
+        context: &mut CTX,
+        address: &Address,
+        inputs: &InputsImpl,
+        is_static: bool,
+        gas_limit: u64,
+    ) -> Result<Option<Self::Output>, String> {
+        let Some(address) = self.resolve(address) else {
+            return Ok(None);
+        };
+        self.inner
+            .run(context, &address, inputs, is_static, gas_limit)
+    }
+
+    fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
+        let addresses: Vec<_> = self
+            .inner
+            .warm_addresses()
+            .chain(self.moved.keys().copied())
+            .filter(|address| <Self as PrecompileProvider<CTX>>::contains(self, address))
+            .collect();
+        Box::new(addresses.into_iter())
+    }
+
+    fn contains(&self, address: &Address) -> bool {
+        self.resolve(address)
+            .is_some_and(|address| self.inner.contains(&address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExecuteEvm, MainBuilder, MainContext, MainnetContext};
+    use context::{Context, TxEnv};
+    use database::{AccountOverride, InMemoryDB, OverrideDB};
+    use primitives::{address, TxKind, U256};
+    use state::bytecode::opcode;
+
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+    const MOVED: Address = address!("0x0000000000000000000000000000000000001234");
+
+    #[test]
+    fn move_precompile() {
+        // Returns 42 as a word.
+        let code = [
+            opcode::PUSH1,
+            42,
+            opcode::PUSH1,
+            0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH1,
+            0,
+            opcode::RETURN,
+        ];
+        let overrides = StateOverride::from_iter([(
+            IDENTITY,
+            AccountOverride::default()
+                .with_code(Bytes::copy_from_slice(&code))
+                .with_move_precompile_to(MOVED),
+        )]);
+        let precompiles = RelocatedPrecompiles::with_state_override::<
+            MainnetContext<OverrideDB<InMemoryDB>>,
+        >(EthPrecompiles::default(), &overrides)
+        .unwrap();
+        let db = OverrideDB::new(InMemoryDB::default())
+            .with_state_override(&overrides)
+            .unwrap();
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet()
+            .with_precompiles(precompiles);
+
+        let mut transact = |to: Address| {
+            let tx = TxEnv {
+                kind: TxKind::Call(to),
+                data: Bytes::from_static(b"hello"),
+                ..Default::default()
+            };
+            let result = evm.transact(tx).unwrap().result;
+            assert!(result.is_success());
+            result.into_output().unwrap_or_default()
+        };
+        assert_eq!(transact(MOVED), Bytes::from_static(b"hello"));
+        assert_eq!(
+            transact(IDENTITY),
+            Bytes::from(U256::from(42).to_be_bytes_vec())
+        );
+    }
+
+    #[test]
+    fn invalid_precompile_moves() {
+        type Ctx = MainnetContext<InMemoryDB>;
+        let mut precompiles = RelocatedPrecompiles::new(EthPrecompiles::default());
+        assert_eq!(
+            precompiles.move_precompile::<Ctx>(MOVED, IDENTITY),
+            Err(OverrideError::NotPrecompile(MOVED))
+        );
+        let ecrecover = address!("0x0000000000000000000000000000000000000001");
+        assert_eq!(
+            precompiles.move_precompile::<Ctx>(IDENTITY, ecrecover),
+            Err(OverrideError::MoveToPrecompile(ecrecover))
+        );
+
+        // The original address is free after the move.
+        precompiles.move_precompile::<Ctx>(IDENTITY, MOVED).unwrap();
+        precompiles
+            .move_precompile::<Ctx>(ecrecover, IDENTITY)
+            .unwrap();
+        assert_eq!(
+            precompiles.move_precompile::<Ctx>(ecrecover, MOVED),
+            Err(OverrideError::NotPrecompile(ecrecover))
+        );
+    }
+}
@@ -0,0 +1,764 @@
+//! Simulation of a sequence of blocks, similar to `eth_simulateV1`.
+//!
+//! Each simulated block has its own calls, [`StateOverride`] and [`BlockOverride`]. Blocks are
+//! executed on top of each other with a [`BlockExecutor`], so later blocks see the state of the
+//! earlier ones, and the simulated blocks get synthetic hashes that are served to `BLOCKHASH`.
+use crate::{
+    block::{BlockExecutionError, BlockExecutor, BlockStateDb},
+    EvmTr, ExecuteEvm, PrecompileProvider, RelocatedPrecompiles,
+};
+use context::{BlockEnv, BlockOverride, CfgEnv, Context};
+use context_interface::{
+    result::{ExecutionResult, HaltReason},
+    JournalTr, Transaction,
+};
+use core::fmt;
+use database_interface::{OverrideError, StateOverride};
+use primitives::{hardfork::SpecId, keccak256, Address, HashMap, Log, B256, U256};
+use state::{Account, Bytecode, EvmStorageSlot};
+use std::vec::Vec;
+
+/// Seconds between the simulated blocks if the timestamp is not overridden.
+pub const SIMULATED_BLOCK_TIME: u64 = 12;
+
+/// Maximum number of blocks skipped between two simulated blocks, the `BLOCKHASH` window.
+pub const MAX_SKIPPED_BLOCKS: u64 = 256;
+
+/// Block of a simulation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimBlock<TX> {
+    /// Overrides of the block fields.
+    pub block_override: BlockOverride,
+    /// Overrides of the state applied before the block is executed.
+    pub state_override: StateOverride,
+    /// Calls executed in the block.
+    pub calls: Vec<TX>,
+}
+
+impl<TX> SimBlock<TX> {
+    /// Creates a new block with the calls and without overrides.
+    pub fn new(calls: Vec<TX>) -> Self {
+        Self {
+            block_override: BlockOverride::default(),
+            state_override: StateOverride::default(),
+            calls,
+        }
+    }
+
+    /// Sets the block overrides.
+    pub fn with_block_override(mut self, block_override: BlockOverride) -> Self {
+        self.block_override = block_override;
+        self
+    }
+
+    /// Sets the state overrides.
+    pub fn with_state_override(mut self, state_override: StateOverride) -> Self {
+        self.state_override = state_override;
+        self
+    }
+}
+
+/// Input of [`simulate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulateRequest<TX> {
+    /// Blocks in the order they are executed.
+    pub blocks: Vec<SimBlock<TX>>,
+    /// Whether the transactions are validated as in a real block.
+    ///
+    /// Without validation the nonce checks are disabled and the base fee is zero unless it is
+    /// overridden.
+    pub validation: bool,
+    /// Whether value transfers are returned as [ERC-7528] logs, see
+    /// [`CfgEnv::transfer_logs`].
+    ///
+    /// [ERC-7528]: https://eips.ethereum.org/EIPS/eip-7528
+    pub trace_transfers: bool,
+}
+
+impl<TX> SimulateRequest<TX> {
+    /// Creates a new request without validation and transfer logs.
+    pub fn new(blocks: Vec<SimBlock<TX>>) -> Self {
+        Self {
+            blocks,
+            validation: false,
+            trace_transfers: false,
+        }
+    }
+
+    /// Sets [`SimulateRequest::validation`].
+    pub fn with_validation(mut self, validation: bool) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Sets [`SimulateRequest::trace_transfers`].
+    pub fn with_trace_transfers(mut self, trace_transfers: bool) -> Self {
+        self.trace_transfers = trace_transfers;
+        self
+    }
+}
+
+/// Log of a simulated call with its position in the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedLog {
+    /// Emitted log.
+    pub log: Log,
+    /// Number of the block.
+    pub block_number: u64,
+    /// Synthetic hash of the block.
+    pub block_hash: B256,
+    /// Index of the call in the block.
+    pub transaction_index: u64,
+    /// Index of the log in the block.
+    pub log_index: u64,
+}
+
+/// Simulated call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedCall<HaltReasonTy = HaltReason> {
+    /// Execution result of the call.
+    pub result: ExecutionResult<HaltReasonTy>,
+    /// Logs of the call, with the transfer logs if they are traced.
+    pub logs: Vec<SimulatedLog>,
+}
+
+/// Simulated block.
+///
+/// `Bundle` is the [`BlockStateDb::Bundle`] of the database, the `BundleState` for `State`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedBlock<Bundle, HaltReasonTy = HaltReason> {
+    /// Block environment the calls were executed in.
+    pub block: BlockEnv,
+    /// Synthetic hash of the block.
+    pub hash: B256,
+    /// Hash of the parent block.
+    pub parent_hash: B256,
+    /// Gas used by all calls.
+    pub gas_used: u64,
+    /// Blob gas used by all calls.
+    pub blob_gas_used: u64,
+    /// Simulated calls in order.
+    pub calls: Vec<SimulatedCall<HaltReasonTy>>,
+    /// State changes of the block together with reverts.
+    pub bundle_state: Bundle,
+}
+
+/// Result of [`simulate`].
+type SimulateResult<BE, DB> = Result<
+    Vec<SimulatedBlock<<DB as BlockStateDb>::Bundle, <BE as BlockExecutor>::HaltReason>>,
+    SimulateError<<BE as BlockExecutor>::Error>,
+>;
+
+/// Simulation error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulateError<E> {
+    /// Block number is not greater than the number of the parent block.
+    BlockNumber {
+        /// Index of the simulated block.
+        block: usize,
+        /// Number of the block.
+        number: u64,
+        /// Number of the parent block.
+        parent: u64,
+    },
+    /// Block number skips more than [`MAX_SKIPPED_BLOCKS`] blocks after the parent block.
+    BlockGap {
+        /// Index of the simulated block.
+        block: usize,
+        /// Number of the block.
+        number: u64,
+        /// Number of the parent block.
+        parent: u64,
+    },
+    /// Block timestamp is not greater than the timestamp of the parent block.
+    Timestamp {
+        /// Index of the simulated block.
+        block: usize,
+        /// Timestamp of the block.
+        timestamp: u64,
+        /// Timestamp of the parent block.
+        parent: u64,
+    },
+    /// State overrides of the block are invalid.
+    StateOverride {
+        /// Index of the simulated block.
+        block: usize,
+        /// Override error.
+        error: OverrideError,
+    },
+    /// Block failed to execute.
+    Execution {
+        /// Index of the simulated block.
+        block: usize,
+        /// Execution error.
+        error: BlockExecutionError<E>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for SimulateError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlockNumber {
+                block,
+                number,
+                parent,
+            } => write!(
+                f,
+                "block {block} number {number} is not greater than parent number {parent}"
+            ),
+            Self::BlockGap {
+                block,
+                number,
+                parent,
+            } => write!(
+                f,
+                "block {block} number {number} skips more than {MAX_SKIPPED_BLOCKS} blocks after parent number {parent}"
+            ),
+            Self::Timestamp {
+                block,
+                timestamp,
+                parent,
+            } => write!(
+                f,
+                "block {block} timestamp {timestamp} is not greater than parent timestamp {parent}"
+            ),
+            Self::StateOverride { block, error } => {
+                write!(f, "block {block} has invalid state overrides: {error}")
+            }
+            Self::Execution { block, error } => write!(f, "block {block} failed: {error}"),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for SimulateError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::StateOverride { error, .. } => Some(error),
+            Self::Execution { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Simulates the blocks on top of the block and state of the EVM.
+///
+/// Every block is executed with the [`BlockExecutor`]: the state overrides are committed, the
+/// pre-block system calls run and the calls are executed in order. Post-block requests,
+/// withdrawals and block rewards are not applied. The number and timestamp of a block default
+/// to the ones of its parent increased by one and [`SIMULATED_BLOCK_TIME`], other fields are
+/// inherited from the parent.
+///
+/// The hashes of the simulated blocks, and of the skipped blocks if the numbers have gaps, are
+/// synthetic and are inserted into the database for `BLOCKHASH`, at most
+/// [`MAX_SKIPPED_BLOCKS`] blocks can be skipped. Precompiles are moved with
+/// [`RelocatedPrecompiles`] only for the block of the state override.
+///
+/// The block, the changed configuration and the precompiles of the EVM are restored when the
+/// simulation returns, also if it fails.
+///
+/// All changes are committed to the database of the EVM, so it is usually a `State` over a
+/// layer that is discarded afterwards, like `CacheDB::nest`.
+pub fn simulate<BE, TX, SPEC, DB, JOURNAL, CHAIN, P>(
+    executor: &mut BE,
+    evm: &mut BE::Evm,
+    request: SimulateRequest<TX>,
+) -> SimulateResult<BE, DB>
+where
+    BE: BlockExecutor,
+    BE::Evm: EvmTr<
+            Context = Context<BlockEnv, TX, CfgEnv<SPEC>, DB, JOURNAL, CHAIN>,
+            Precompiles = RelocatedPrecompiles<P>,
+        > + ExecuteEvm<Tx = TX>,
+    TX: Transaction,
+    SPEC: Into<SpecId> + Copy,
+    DB: BlockStateDb,
+    JOURNAL: JournalTr<Database = DB>,
+    P: PrecompileProvider<Context<BlockEnv, TX, CfgEnv<SPEC>, DB, JOURNAL, CHAIN>>,
+{
+    let (ctx, precompiles) = evm.ctx_precompiles();
+    let cfg = &mut ctx.cfg;
+    let (disable_nonce_check, transfer_logs) = (cfg.disable_nonce_check, cfg.transfer_logs);
+    cfg.disable_nonce_check |= !request.validation;
+    cfg.transfer_logs = request.trace_transfers;
+    let block = ctx.block.clone();
+    let moves = precompiles.moves().clone();
+
+    let result = simulate_blocks(executor, evm, request, &moves);
+
+    let (ctx, precompiles) = evm.ctx_precompiles();
+    ctx.cfg.disable_nonce_check = disable_nonce_check;
+    ctx.cfg.transfer_logs = transfer_logs;
+    ctx.block = block;
+    precompiles.set_moves(moves);
+    result
+}
+
+/// Simulates the blocks, `moves` are the precompile moves of the EVM every block starts with.
+fn simulate_blocks<BE, TX, SPEC, DB, JOURNAL, CHAIN, P>(
+    executor: &mut BE,
+    evm: &mut BE::Evm,
+    request: SimulateRequest<TX>,
+    moves: &HashMap<Address, Address>,
+) -> SimulateResult<BE, DB>
+where
+    BE: BlockExecutor,
+    BE::Evm: EvmTr<
+            Context = Context<BlockEnv, TX, CfgEnv<SPEC>, DB, JOURNAL, CHAIN>,
+            Precompiles = RelocatedPrecompiles<P>,
+        > + ExecuteEvm<Tx = TX>,
+    TX: Transaction,
+    SPEC: Into<SpecId> + Copy,
+    DB: BlockStateDb,
+    JOURNAL: JournalTr<Database = DB>,
+    P: PrecompileProvider<Context<BlockEnv, TX, CfgEnv<SPEC>, DB, JOURNAL, CHAIN>>,
+{
+    let mut parent = evm.ctx_ref().block.clone();
+    let mut parent_hash = evm
+        .ctx()
+        .journaled_state
+        .db()
+        .block_hash(parent.number)
+        .map_err(|e| SimulateError::Execution {
+            block: 0,
+            error: BlockExecutionError::Other(e.into()),
+        })?;
+
+    let mut simulated = Vec::with_capacity(request.blocks.len());
+    for (index, sim) in request.blocks.into_iter().enumerate() {
+        let mut block = parent.clone();
+        block.number = parent.number.saturating_add(1);
+        block.timestamp = parent.timestamp.saturating_add(SIMULATED_BLOCK_TIME);
+        if !request.validation {
+            block.basefee = 0;
+        }
+        sim.block_override.apply(&mut block);
+        if block.number <= parent.number {
+            return Err(SimulateError::BlockNumber {
+                block: index,
+                number: block.number,
+                parent: parent.number,
+            });
+        }
+        if block.number - parent.number - 1 > MAX_SKIPPED_BLOCKS {
+            return Err(SimulateError::BlockGap {
+                block: index,
+                number: block.number,
+                parent: parent.number,
+            });
+        }
+        if block.timestamp <= parent.timestamp {
+            return Err(SimulateError::Timestamp {
+                block: index,
+                timestamp: block.timestamp,
+                parent: parent.timestamp,
+            });
+        }
+
+        let execution_error = |error| SimulateError::Execution {
+            block: index,
+            error,
+        };
+        let (ctx, precompiles) = evm.ctx_precompiles();
+        precompiles.set_moves(moves.clone());
+        precompiles
+            .apply_state_override::<<BE::Evm as EvmTr>::Context>(&sim.state_override)
+            .map_err(|error| SimulateError::StateOverride {
+                block: index,
+                error,
+            })?;
+        let db = ctx.journaled_state.db();
+        // Skipped blocks are empty blocks on top of the parent.
+        for number in parent.number + 1..block.number {
+            let skipped = BlockEnv {
+                number,
+                ..parent.clone()
+            };
+            parent_hash = synthetic_block_hash(parent_hash, &skipped, 0);
+            db.insert_block_hash(number, parent_hash);
+        }
+        for (number, hash) in sim.block_override.block_hashes.iter().flatten() {
+            db.insert_block_hash(*number, *hash);
+        }
+        apply_state_override(db, &sim.state_override).map_err(|error| match error {
+            StateOverrideError::Override(error) => SimulateError::StateOverride {
+                block: index,
+                error,
+            },
+            StateOverrideError::Database(e) => {
+                execution_error(BlockExecutionError::Other(e.into()))
+            }
+        })?;
+        ctx.block = block.clone();
+
+        executor
+            .apply_pre_block(evm, Some(parent_hash), Some(B256::ZERO))
+            .map_err(execution_error)?;
+        let executed = executor
+            .execute_transactions(evm, sim.calls)
+            .map_err(execution_error)?;
+
+        let db = evm.ctx().journaled_state.db();
+        db.merge_transitions();
+        let bundle_state = db.take_bundle();
+        let hash = synthetic_block_hash(parent_hash, &block, executed.gas_used);
+        db.insert_block_hash(block.number, hash);
+
+        let mut log_index = 0;
+        let calls = executed
+            .results
+            .into_iter()
+            .enumerate()
+            .map(|(transaction_index, result)| {
+                let logs = result
+                    .logs()
+                    .iter()
+                    .cloned()
+                    .map(|log| {
+                        log_index += 1;
+                        SimulatedLog {
+                            log,
+                            block_number: block.number,
+                            block_hash: hash,
+                            transaction_index: transaction_index as u64,
+                            log_index: log_index - 1,
+                        }
+                    })
+                    .collect();
+                SimulatedCall { result, logs }
+            })
+            .collect();
+
+        simulated.push(SimulatedBlock {
+            block: block.clone(),
+            hash,
+            parent_hash,
+            gas_used: executed.gas_used,
+            blob_gas_used: executed.blob_gas_used,
+            calls,
+            bundle_state,
+        });
+        parent = block;
+        parent_hash = hash;
+    }
+    Ok(simulated)
+}
+
+/// Error of [`apply_state_override`].
+enum StateOverrideError<E> {
+    Override(OverrideError),
+    Database(E),
+}
+
+/// Commits the state overrides to the database.
+///
+/// Storage that is replaced with `state` is wiped by destroying the account before it is
+/// created again with the new storage.
+fn apply_state_override<DB: BlockStateDb>(
+    db: &mut DB,
+    overrides: &StateOverride,
+) -> Result<(), StateOverrideError<DB::Error>> {
+    // Validate all overrides before any of them is committed.
+    let mut codes = HashMap::<Address, Bytecode>::default();
+    for (address, account) in overrides {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(StateOverrideError::Override(
+                OverrideError::StateAndStateDiff(*address),
+            ));
+        }
+        if let Some(code) = &account.code {
+            let bytecode = Bytecode::new_raw_checked(code.clone()).map_err(|error| {
+                StateOverrideError::Override(OverrideError::InvalidCode(*address, error))
+            })?;
+            codes.insert(*address, bytecode);
+        }
+    }
+
+    for (address, account) in overrides {
+        let mut info = db
+            .basic(*address)
+            .map_err(StateOverrideError::Database)?
+            .unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code) = codes.remove(address) {
+            info = info.with_code(code);
+        }
+
+        let mut changed = Account::from(info).with_touched_mark();
+        if let Some(state) = &account.state {
+            let destroyed = Account::default()
+                .with_selfdestruct_mark()
+                .with_touched_mark();
+            db.commit(HashMap::from_iter([(*address, destroyed)]));
+            changed = changed
+                .with_created_mark()
+                .with_storage(state.iter().map(|(key, value)| {
+                    let slot = EvmStorageSlot::new_changed(U256::ZERO, (*value).into());
+                    ((*key).into(), slot)
+                }));
+        } else if let Some(state_diff) = &account.state_diff {
+            for (key, value) in state_diff {
+                let key = (*key).into();
+                let original = db
+                    .storage(*address, key)
+                    .map_err(StateOverrideError::Database)?;
+                changed
+                    .storage
+                    .insert(key, EvmStorageSlot::new_changed(original, (*value).into()));
+            }
+        }
+        db.commit(HashMap::from_iter([(*address, changed)]));
+    }
+    Ok(())
+}
+
+/// Returns the synthetic hash of a simulated block.
+///
+/// It is the hash of the parent hash and the block fields, as the simulated blocks have no
+/// header.
+fn synthetic_block_hash(parent_hash: B256, block: &BlockEnv, gas_used: u64) -> B256 {
+    let mut preimage = Vec::with_capacity(32 + 20 + 5 * 8);
+    preimage.extend_from_slice(parent_hash.as_slice());
+    preimage.extend_from_slice(block.beneficiary.as_slice());
+    for field in [
+        block.number,
+        block.timestamp,
+        block.gas_limit,
+        block.basefee,
+        gas_used,
+    ] {
+        preimage.extend_from_slice(&field.to_be_bytes());
+    }
+    keccak256(preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::MainnetBlockExecutor, EthPrecompiles, MainBuilder, MainContext};
+    use context::TxEnv;
+    use context_interface::journaled_state::{transfer_log, TRANSFER_LOG_ADDRESS};
+    use database::{AccountOverride, BundleState, InMemoryDB, State, StateBuilder};
+    use primitives::{address, Bytes, TxKind};
+    use state::bytecode::opcode;
+    use std::vec;
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+
+    /// Returns the stored slot 0 and the hash of block 1, stores the block number in slot 0 and
+    /// emits a log.
+    fn target_code() -> Bytes {
+        Bytes::from(vec![
+            opcode::PUSH1,
+            0,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            1,
+            opcode::BLOCKHASH,
+            opcode::PUSH1,
+            32,
+            opcode::MSTORE,
+            opcode::NUMBER,
+            opcode::PUSH1,
+            0,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::LOG0,
+            opcode::PUSH1,
+            64,
+            opcode::PUSH1,
+            0,
+            opcode::RETURN,
+        ])
+    }
+
+    fn call(value: u64) -> TxEnv {
+        TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            value: U256::from(value),
+            gas_limit: 100_000,
+            ..Default::default()
+        }
+    }
+
+    fn run_simulate(request: SimulateRequest<TxEnv>) -> Vec<SimulatedBlock<BundleState>> {
+        let mut state = StateBuilder::new()
+            .with_database(InMemoryDB::default())
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::mainnet()
+            .with_db(&mut state)
+            .build_mainnet()
+            .with_precompiles(RelocatedPrecompiles::new(EthPrecompiles::default()));
+        simulate(&mut MainnetBlockExecutor::new(), &mut evm, request).unwrap()
+    }
+
+    fn output(call: &SimulatedCall) -> (U256, B256) {
+        let output = call.result.output().unwrap();
+        (
+            U256::from_be_slice(&output[..32]),
+            B256::from_slice(&output[32..]),
+        )
+    }
+
+    fn overrides() -> StateOverride {
+        StateOverride::from_iter([
+            (
+                CALLER,
+                AccountOverride::default().with_balance(U256::from(100)),
+            ),
+            (TARGET, AccountOverride::default().with_code(target_code())),
+        ])
+    }
+
+    #[test]
+    fn simulate_blocks() {
+        let request = SimulateRequest::new(vec![
+            SimBlock::new(vec![call(5), call(0)]).with_state_override(overrides()),
+            SimBlock::new(vec![call(0)])
+                .with_block_override(BlockOverride::default().with_number(4)),
+        ])
+        .with_trace_transfers(true);
+        let blocks = run_simulate(request);
+
+        let [first, second] = blocks.as_slice() else {
+            panic!("two blocks should be simulated");
+        };
+        assert_eq!((first.block.number, first.block.timestamp), (1, 13));
+        assert_eq!((second.block.number, second.block.timestamp), (4, 25));
+        assert_ne!(second.parent_hash, first.hash);
+
+        // Logs are indexed across the calls and the transfer comes first.
+        let logs: Vec<_> = first
+            .calls
+            .iter()
+            .flat_map(|call| &call.logs)
+            .map(|log| (log.transaction_index, log.log_index, log.log.address))
+            .collect();
+        assert_eq!(
+            logs,
+            [(0, 0, TRANSFER_LOG_ADDRESS), (0, 1, TARGET), (1, 2, TARGET),]
+        );
+        assert_eq!(
+            first.calls[0].logs[0].log,
+            transfer_log(CALLER, TARGET, U256::from(5))
+        );
+        assert_eq!(first.calls[0].logs[0].block_hash, first.hash);
+        assert_eq!(output(&first.calls[1]), (U256::from(1), B256::ZERO));
+
+        // The second block sees the storage and the hash of the first one.
+        assert_eq!(output(&second.calls[0]), (U256::from(1), first.hash));
+        assert!(!second.bundle_state.state.is_empty());
+    }
+
+    #[test]
+    fn replace_storage() {
+        let state = HashMap::from_iter([(B256::with_last_byte(1), B256::with_last_byte(2))]);
+        let request = SimulateRequest::new(vec![
+            SimBlock::new(vec![call(0)]).with_state_override(overrides()),
+            SimBlock::new(vec![call(0)]).with_state_override(StateOverride::from_iter([(
+                TARGET,
+                AccountOverride::default().with_state(state),
+            )])),
+        ]);
+        let blocks = run_simulate(request);
+        // The slot stored by the first block is wiped.
+        assert_eq!(output(&blocks[1].calls[0]).0, U256::ZERO);
+    }
+
+    #[test]
+    fn invalid_blocks() {
+        let mut state = State::builder().with_bundle_update().build();
+        let mut evm = Context::mainnet()
+            .with_db(&mut state)
+            .build_mainnet()
+            .with_precompiles(RelocatedPrecompiles::new(EthPrecompiles::default()));
+        let request = SimulateRequest::new(vec![
+            SimBlock::<TxEnv>::default(),
+            SimBlock::default().with_block_override(BlockOverride::default().with_number(1)),
+        ]);
+        let error = simulate(&mut MainnetBlockExecutor::new(), &mut evm, request).unwrap_err();
+        assert_eq!(
+            error,
+            SimulateError::BlockNumber {
+                block: 1,
+                number: 1,
+                parent: 1,
+            }
+        );
+
+        let moved = address!("0x0000000000000000000000000000000000001234");
+        let request = SimulateRequest::new(vec![
+            SimBlock::<TxEnv>::default()
+                .with_state_override(StateOverride::from_iter([(
+                    IDENTITY,
+                    AccountOverride::default().with_move_precompile_to(moved),
+                )]))
+                .with_block_override(BlockOverride::default().with_number(5)),
+            SimBlock::default()
+                .with_block_override(BlockOverride::default().with_number(7 + MAX_SKIPPED_BLOCKS)),
+        ]);
+        let error = simulate(&mut MainnetBlockExecutor::new(), &mut evm, request).unwrap_err();
+        assert_eq!(
+            error,
+            SimulateError::BlockGap {
+                block: 1,
+                number: 7 + MAX_SKIPPED_BLOCKS,
+                parent: 5,
+            }
+        );
+        // The block and the precompiles are restored after the failure.
+        assert_eq!(evm.ctx().block, BlockEnv::default());
+        assert!(evm.precompiles.moves().is_empty());
+    }
+
+    #[test]
+    fn precompile_moves_are_per_block() {
+        let moved = address!("0x0000000000000000000000000000000000001234");
+        let call = |to| TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(to),
+            data: Bytes::from_static(b"hello"),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let request = SimulateRequest::new(vec![
+            SimBlock::new(vec![call(moved), call(IDENTITY)]).with_state_override(
+                StateOverride::from_iter([(
+                    IDENTITY,
+                    AccountOverride::default().with_move_precompile_to(moved),
+                )]),
+            ),
+            SimBlock::new(vec![call(moved), call(IDENTITY)]),
+        ]);
+        let blocks = run_simulate(request);
+
+        let outputs: Vec<Vec<_>> = blocks
+            .iter()
+            .map(|block| {
+                block
+                    .calls
+                    .iter()
+                    .map(|call| call.result.output().cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        let hello = Bytes::from_static(b"hello");
+        assert_eq!(
+            outputs,
+            [vec![hello.clone(), Bytes::new()], vec![Bytes::new(), hello]]
+        );
+    }
+}
@@ -9,6 +9,16 @@ use primitives::{address, Address, Bytes, TxKind};
 
 pub const SYSTEM_ADDRESS: Address = address!("0xfffffffffffffffffffffffffffffffffffffffe");
 
+/// System call to a system contract, executed with [`SystemCallCommitEvm::transact_system_call_commit`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemCall {
+    /// Address of the system contract.
+    pub address: Address,
+    /// Call data.
+    pub data: Bytes,
+}
+
 /// Creates the system transaction with default values and set data and tx call target to system contract address
 /// that is going to be called.
 ///
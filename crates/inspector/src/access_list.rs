@@ -0,0 +1,279 @@
+//! Access list generation, similar to `eth_createAccessList`.
+use crate::{InspectEvm, Inspector, InspectorEvmTr};
+use context::{
+    result::{ExecutionResult, HaltReason, ResultAndState},
+    transaction::{AccessList, AccessListItem},
+    Block, ContextTr, JournalTr, Transaction, TxEnv,
+};
+use core::mem;
+use interpreter::{
+    interpreter::EthInterpreter,
+    interpreter_types::{InputsTr, Jumps},
+    CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+};
+use primitives::{Address, HashSet, TxKind, B256};
+use state::bytecode::opcode;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Inspector that collects the [EIP-2930] access list of the transaction.
+///
+/// Records addresses accessed by `BALANCE`, `EXTCODE*`, `CALL*` and `SELFDESTRUCT` and storage
+/// slots accessed by `SLOAD` and `SSTORE`. The sender, the recipient, the precompiles and the
+/// block beneficiary are warm without the access list, so they are left out, but the storage
+/// slots of the recipient are included.
+///
+/// The inspector should be used for a single transaction, as the excluded addresses are taken
+/// from the first call of the transaction.
+///
+/// [EIP-2930]: https://eips.ethereum.org/EIPS/eip-2930
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListInspector {
+    /// Accessed addresses and their storage slots.
+    access_list: BTreeMap<Address, BTreeSet<B256>>,
+    /// Addresses that are warm without the access list.
+    excluded: HashSet<Address>,
+    /// If the excluded addresses are set.
+    initialized: bool,
+}
+
+impl AccessListInspector {
+    /// Creates a new inspector that starts from the given access list.
+    pub fn new(access_list: AccessList) -> Self {
+        Self {
+            access_list: access_list
+                .0
+                .into_iter()
+                .map(|item| (item.address, item.storage_keys.into_iter().collect()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the collected access list, sorted by address and storage slot.
+    pub fn access_list(&self) -> AccessList {
+        AccessList(
+            self.access_list
+                .iter()
+                .filter(|(address, slots)| !slots.is_empty() || !self.excluded.contains(*address))
+                .map(|(address, slots)| AccessListItem {
+                    address: *address,
+                    storage_keys: slots.iter().copied().collect(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns addresses that are left out of the access list.
+    pub fn excluded(&self) -> &HashSet<Address> {
+        &self.excluded
+    }
+
+    /// Sets the excluded addresses from the transaction and the block.
+    fn initialize<CTX: ContextTr>(&mut self, context: &mut CTX) {
+        if self.initialized {
+            return;
+        }
+        self.initialized = true;
+
+        let caller = context.tx().caller();
+        let recipient = match context.tx().kind() {
+            TxKind::Call(address) => address,
+            TxKind::Create => caller.create(context.tx().nonce()),
+        };
+        self.excluded
+            .extend([caller, recipient, context.block().beneficiary()]);
+        self.excluded
+            .extend(context.journal_ref().precompile_addresses().iter().copied());
+    }
+
+    fn add_address(&mut self, address: Address) {
+        if !self.excluded.contains(&address) {
+            self.access_list.entry(address).or_default();
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.access_list
+                        .entry(interp.input.target_address())
+                        .or_default()
+                        .insert(B256::from(slot));
+                }
+            }
+            opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::EXTCODESIZE
+            | opcode::BALANCE
+            | opcode::SELFDESTRUCT => {
+                if let Ok(address) = interp.stack.peek(0) {
+                    self.add_address(Address::from_word(B256::from(address)));
+                }
+            }
+            opcode::DELEGATECALL | opcode::CALL | opcode::STATICCALL | opcode::CALLCODE => {
+                if let Ok(address) = interp.stack.peek(1) {
+                    self.add_address(Address::from_word(B256::from(address)));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.initialize(context);
+        None
+    }
+
+    fn create(&mut self, context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.initialize(context);
+        None
+    }
+}
+
+/// Access list together with the execution result of the transaction using it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult<HaltReasonTy = HaltReason> {
+    /// Generated access list.
+    pub access_list: AccessList,
+    /// Gas used by the transaction with the access list.
+    pub gas_used: u64,
+    /// Execution result of the transaction with the access list.
+    pub result: ExecutionResult<HaltReasonTy>,
+}
+
+/// Creates the access list of the transaction.
+///
+/// Accessed slots can change with the access list as it changes the gas available to the
+/// execution, so the transaction is executed with the collected access list until it stops
+/// changing. Every run starts from the access list of the previous one so the list only
+/// grows and the loop terminates. The access list of the transaction is used as the
+/// starting point.
+///
+/// State changes are not committed.
+pub fn create_access_list<EVM, HaltReasonTy, ERROR>(
+    evm: &mut EVM,
+    mut tx: TxEnv,
+) -> Result<AccessListResult<HaltReasonTy>, ERROR>
+where
+    EVM: InspectEvm<
+            Tx = TxEnv,
+            Inspector = AccessListInspector,
+            Output = Result<ResultAndState<HaltReasonTy>, ERROR>,
+        > + InspectorEvmTr<Inspector = AccessListInspector>,
+{
+    let mut access_list = mem::take(&mut tx.access_list);
+    loop {
+        tx.access_list = access_list.clone();
+        let ResultAndState { result, .. } =
+            evm.inspect(tx.clone(), AccessListInspector::new(access_list.clone()))?;
+        let collected = mem::take(evm.inspector()).access_list();
+        if collected == access_list {
+            return Ok(AccessListResult {
+                access_list,
+                gas_used: result.gas_used(),
+                result,
+            });
+        }
+        access_list = collected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context::Context;
+    use database::{CacheDB, EmptyDB};
+    use handler::{ExecuteEvm, MainBuilder, MainContext};
+    use primitives::{address, Bytes, U256};
+    use state::{AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    const CALLEE: Address = address!("0x3000000000000000000000000000000000000003");
+    const QUERIED: Address = address!("0x4000000000000000000000000000000000000004");
+
+    fn db() -> CacheDB<EmptyDB> {
+        let target = [
+            // SLOAD(1)
+            &[opcode::PUSH1, 0x01, opcode::SLOAD, opcode::POP][..],
+            // BALANCE(QUERIED)
+            &[opcode::PUSH20],
+            QUERIED.as_slice(),
+            &[opcode::BALANCE, opcode::POP],
+            // Balances of the beneficiary, the caller and the ecrecover precompile.
+            &[opcode::COINBASE, opcode::BALANCE, opcode::POP],
+            &[opcode::CALLER, opcode::BALANCE, opcode::POP],
+            &[opcode::PUSH1, 0x01, opcode::BALANCE, opcode::POP],
+            // CALL(gas, CALLEE, 0, 0, 0, 0, 0)
+            &[opcode::PUSH1, 0, opcode::PUSH1, 0, opcode::PUSH1, 0],
+            &[opcode::PUSH1, 0, opcode::PUSH1, 0, opcode::PUSH20],
+            CALLEE.as_slice(),
+            &[opcode::GAS, opcode::CALL, opcode::STOP],
+        ]
+        .concat();
+        // SSTORE(3, 2)
+        let callee = [
+            opcode::PUSH1,
+            0x02,
+            opcode::PUSH1,
+            0x03,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(target))),
+        );
+        db.insert_account_info(
+            CALLEE,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(callee.to_vec()))),
+        );
+        db
+    }
+
+    #[test]
+    fn collects_access_list() {
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 1_000_000,
+            ..Default::default()
+        };
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(AccessListInspector::default());
+
+        let created = create_access_list(&mut evm, tx.clone()).unwrap();
+        let expected = AccessList(vec![
+            AccessListItem {
+                address: TARGET,
+                storage_keys: vec![B256::with_last_byte(1)],
+            },
+            AccessListItem {
+                address: CALLEE,
+                storage_keys: vec![B256::with_last_byte(3)],
+            },
+            AccessListItem {
+                address: QUERIED,
+                storage_keys: vec![],
+            },
+        ]);
+        assert_eq!(created.access_list, expected);
+        assert!(created.result.is_success());
+
+        // Gas is reported for the transaction that uses the access list.
+        let result = evm
+            .transact(TxEnv {
+                access_list: expected,
+                ..tx
+            })
+            .unwrap();
+        assert_eq!(created.gas_used, result.result.gas_used());
+    }
+}
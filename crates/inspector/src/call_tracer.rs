@@ -0,0 +1,503 @@
+//! Call tracer that records the tree of calls of a transaction.
+//!
+//! [`CallTracer`] records a [`CallFrame`] for every call, create and selfdestruct together with
+//! the logs emitted by it. With the `serde-json` feature the recorded tree can be converted into
+//! the output of Geth's `callTracer` with [`GethCallFrame`] and into the flat traces of Parity's
+//! `trace_*` methods with [`ParityTrace`].
+#[cfg(feature = "serde-json")]
+mod geth;
+#[cfg(feature = "serde-json")]
+mod parity;
+
+#[cfg(feature = "serde-json")]
+pub use geth::{CallTracerConfig, GethCallFrame, GethCallLog};
+#[cfg(feature = "serde-json")]
+pub use parity::{
+    CallAction, CallOutput, CreateAction, CreateOutput, ParityTrace, SelfdestructAction,
+    TraceAction, TraceOutput,
+};
+
+use crate::Inspector;
+use context::{ContextTr, Transaction};
+use interpreter::{
+    CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+    EOFCreateInputs, EOFCreateKind, InstructionResult, Interpreter, InterpreterResult,
+    InterpreterTypes,
+};
+use primitives::{Address, Bytes, Log, U256};
+use std::vec::Vec;
+
+/// Kind of a [`CallFrame`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// `CALL` or `EXTCALL`.
+    Call,
+    /// `CALLCODE`.
+    CallCode,
+    /// `DELEGATECALL` or `EXTDELEGATECALL`.
+    DelegateCall,
+    /// `STATICCALL` or `EXTSTATICCALL`.
+    StaticCall,
+    /// `CREATE` or a contract creation transaction.
+    Create,
+    /// `CREATE2`.
+    Create2,
+    /// `EOFCREATE` or an EOF contract creation transaction.
+    EofCreate,
+    /// `SELFDESTRUCT`.
+    SelfDestruct,
+}
+
+impl CallKind {
+    /// Returns `true` if the frame creates a contract.
+    pub const fn is_create(self) -> bool {
+        matches!(self, Self::Create | Self::Create2 | Self::EofCreate)
+    }
+
+    /// Returns `true` if the frame runs the code of `to` in the context of `from`.
+    pub const fn is_delegate(self) -> bool {
+        matches!(self, Self::DelegateCall | Self::CallCode)
+    }
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call | CallScheme::ExtCall => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall | CallScheme::ExtDelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall | CallScheme::ExtStaticCall => Self::StaticCall,
+        }
+    }
+}
+
+impl From<CreateScheme> for CallKind {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create2 { .. } => Self::Create2,
+            CreateScheme::Create | CreateScheme::Custom { .. } => Self::Create,
+        }
+    }
+}
+
+/// Log emitted by a [`CallFrame`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallLog {
+    /// Emitted log.
+    pub log: Log,
+    /// Number of subcalls of the frame made before the log was emitted.
+    pub position: usize,
+}
+
+/// Call, create or selfdestruct recorded by the [`CallTracer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    /// Kind of the frame.
+    pub kind: CallKind,
+    /// Address of the account that executes the call.
+    ///
+    /// For `DELEGATECALL` and `CALLCODE` this is the account the code runs for.
+    pub from: Address,
+    /// Address of the called account or the created contract.
+    ///
+    /// For `DELEGATECALL` and `CALLCODE` this is the account the code is loaded from, for a
+    /// creation that failed before the address was derived it is zero.
+    pub to: Address,
+    /// Transferred value, the apparent value for `DELEGATECALL`.
+    pub value: U256,
+    /// Gas available to the frame.
+    pub gas: u64,
+    /// Gas used by the frame.
+    pub gas_used: u64,
+    /// Call input or init code.
+    pub input: Bytes,
+    /// Returned data or deployed code.
+    pub output: Bytes,
+    /// Result the frame ended with.
+    pub result: InstructionResult,
+    /// Logs emitted by the frame.
+    pub logs: Vec<CallLog>,
+    /// Subcalls of the frame in execution order.
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// Returns `true` if the frame ended successfully.
+    pub const fn is_success(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns `true` if the frame reverted.
+    pub const fn is_revert(&self) -> bool {
+        self.result.is_revert()
+    }
+
+    /// Returns the number of frames in the tree starting at this frame.
+    pub fn len(&self) -> usize {
+        1 + self.calls.iter().map(CallFrame::len).sum::<usize>()
+    }
+
+    /// Always returns `false` as the tree contains at least this frame.
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn end(&mut self, result: &InterpreterResult) {
+        self.gas_used = self.gas.saturating_sub(result.gas.remaining());
+        self.output = result.output.clone();
+        self.result = result.result;
+    }
+}
+
+/// Inspector that records the tree of calls of a transaction.
+///
+/// Like Geth's `callTracer` the tracer records every frame, including the calls to precompiles,
+/// and the logs emitted by it. Subcalls and logs of reverted frames are kept, they can be
+/// dropped when the output is built.
+///
+/// The tracer records a single transaction, the previous trace is discarded when a new
+/// transaction starts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallTracer {
+    /// Frames that have not ended yet.
+    stack: Vec<CallFrame>,
+    /// Top level frame of the last transaction.
+    root: Option<CallFrame>,
+    /// Gas limit of the last transaction.
+    gas_limit: u64,
+}
+
+impl CallTracer {
+    /// Creates a new call tracer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the top level frame of the traced transaction.
+    ///
+    /// Returns `None` if no transaction was executed or the execution has not ended.
+    pub fn root(&self) -> Option<&CallFrame> {
+        self.root.as_ref()
+    }
+
+    /// Consumes the tracer and returns the top level frame of the traced transaction.
+    pub fn into_root(self) -> Option<CallFrame> {
+        self.root
+    }
+
+    /// Returns the gas limit of the traced transaction.
+    pub fn gas_limit(&self) -> u64 {
+        self.gas_limit
+    }
+
+    /// Returns the trace of the transaction as Geth's `callTracer` does.
+    ///
+    /// Gas of the top level frame is the gas limit of the transaction and the gas used is
+    /// `gas_used`, the gas used by the transaction as reported in the execution result.
+    #[cfg(feature = "serde-json")]
+    pub fn geth_call_frame(
+        &self,
+        config: CallTracerConfig,
+        gas_used: u64,
+    ) -> Option<GethCallFrame> {
+        let mut frame = GethCallFrame::new(self.root.as_ref()?, config, false);
+        frame.gas = self.gas_limit;
+        frame.gas_used = gas_used;
+        Some(frame)
+    }
+
+    /// Returns the trace of the transaction as the flat list of Parity's `trace_*` methods.
+    ///
+    /// Frames are listed in depth-first order, gas of the top level frame does not include the
+    /// intrinsic gas of the transaction.
+    #[cfg(feature = "serde-json")]
+    pub fn parity_traces(&self) -> Vec<ParityTrace> {
+        let mut traces = Vec::new();
+        if let Some(root) = &self.root {
+            parity::flatten(root, &mut Vec::new(), &mut traces);
+        }
+        traces
+    }
+
+    fn start<CTX: ContextTr>(&mut self, context: &mut CTX, frame: CallFrame) {
+        if self.stack.is_empty() {
+            self.root = None;
+            self.gas_limit = context.tx().gas_limit();
+        }
+        self.stack.push(frame);
+    }
+
+    fn end(&mut self, result: &InterpreterResult, address: Option<Address>) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        frame.end(result);
+        if let Some(address) = address {
+            frame.to = address;
+        }
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer {
+    fn log(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX, log: Log) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.logs.push(CallLog {
+                log,
+                position: frame.calls.len(),
+            });
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let kind = CallKind::from(inputs.scheme);
+        let (from, to) = if kind.is_delegate() {
+            (inputs.target_address, inputs.bytecode_address)
+        } else {
+            (inputs.caller, inputs.target_address)
+        };
+        let frame = CallFrame {
+            kind,
+            from,
+            to,
+            value: inputs.call_value(),
+            gas: inputs.gas_limit,
+            gas_used: 0,
+            input: inputs.input.bytes(context),
+            output: Bytes::new(),
+            result: InstructionResult::Continue,
+            logs: Vec::new(),
+            calls: Vec::new(),
+        };
+        self.start(context, frame);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.end(&outcome.result, None);
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let frame = CallFrame {
+            kind: inputs.scheme.into(),
+            from: inputs.caller,
+            to: Address::ZERO,
+            value: inputs.value,
+            gas: inputs.gas_limit,
+            gas_used: 0,
+            input: inputs.init_code.clone(),
+            output: Bytes::new(),
+            result: InstructionResult::Continue,
+            logs: Vec::new(),
+            calls: Vec::new(),
+        };
+        self.start(context, frame);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end(&outcome.result, outcome.address);
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        let input = match &inputs.kind {
+            EOFCreateKind::Tx { initdata } => initdata.clone(),
+            EOFCreateKind::Opcode { initcode, .. } => initcode.raw.clone(),
+        };
+        let frame = CallFrame {
+            kind: CallKind::EofCreate,
+            from: inputs.caller,
+            to: inputs.kind.created_address().copied().unwrap_or_default(),
+            value: inputs.value,
+            gas: inputs.gas_limit,
+            gas_used: 0,
+            input,
+            output: Bytes::new(),
+            result: InstructionResult::Continue,
+            logs: Vec::new(),
+            calls: Vec::new(),
+        };
+        self.start(context, frame);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end(&outcome.result, outcome.address);
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.calls.push(CallFrame {
+                kind: CallKind::SelfDestruct,
+                from: contract,
+                to: target,
+                value,
+                gas: 0,
+                gas_used: 0,
+                input: Bytes::new(),
+                output: Bytes::new(),
+                result: InstructionResult::SelfDestruct,
+                logs: Vec::new(),
+                calls: Vec::new(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{CacheDB, EmptyDB};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    pub(super) const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    pub(super) const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    pub(super) const CALLEE: Address = address!("0x3000000000000000000000000000000000000003");
+    pub(super) const BENEFICIARY: Address = address!("0x4000000000000000000000000000000000000004");
+
+    /// Traces a call to `TARGET` that logs, calls `CALLEE` which reverts with
+    /// `Error("boom")`, and selfdestructs to `BENEFICIARY`.
+    pub(super) fn trace() -> (CallTracer, u64) {
+        let target = [
+            // LOG1(0, 0, topic 0xaa)
+            &[
+                opcode::PUSH1,
+                0xaa,
+                opcode::PUSH1,
+                0,
+                opcode::PUSH1,
+                0,
+                opcode::LOG1,
+            ][..],
+            // CALL(gas, CALLEE, 1, 0, 4, 0, 0) with input 0x11223344
+            &[
+                opcode::PUSH4,
+                0x11,
+                0x22,
+                0x33,
+                0x44,
+                opcode::PUSH1,
+                0xe0,
+                opcode::SHL,
+            ],
+            &[opcode::PUSH1, 0, opcode::MSTORE],
+            &[
+                opcode::PUSH1,
+                0,
+                opcode::PUSH1,
+                0,
+                opcode::PUSH1,
+                4,
+                opcode::PUSH1,
+                0,
+            ],
+            &[opcode::PUSH1, 1, opcode::PUSH20],
+            CALLEE.as_slice(),
+            &[opcode::GAS, opcode::CALL, opcode::POP],
+            // SELFDESTRUCT(BENEFICIARY)
+            &[opcode::PUSH20],
+            BENEFICIARY.as_slice(),
+            &[opcode::SELFDESTRUCT],
+        ]
+        .concat();
+        // Error("boom")
+        let mut revert_data = vec![0x08, 0xc3, 0x79, 0xa0];
+        revert_data.extend(U256::from(0x20).to_be_bytes::<32>());
+        revert_data.extend(U256::from(4).to_be_bytes::<32>());
+        revert_data.extend(b"boom");
+        revert_data.resize(4 + 32 * 3, 0);
+        // LOG0 and REVERT with the data copied from the code.
+        let offset = 17u8;
+        let callee = [
+            &[opcode::PUSH1, 0, opcode::PUSH1, 0, opcode::LOG0][..],
+            &[
+                opcode::PUSH1,
+                revert_data.len() as u8,
+                opcode::PUSH1,
+                offset,
+            ],
+            &[opcode::PUSH1, 0, opcode::CODECOPY],
+            &[opcode::PUSH1, revert_data.len() as u8, opcode::PUSH1, 0],
+            &[opcode::REVERT],
+            &revert_data,
+        ]
+        .concat();
+        assert_eq!(callee[offset as usize..], revert_data[..]);
+
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::from_balance(U256::from(100))
+                .with_code(Bytecode::new_legacy(Bytes::from(target))),
+        );
+        db.insert_account_info(
+            CALLEE,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(callee))),
+        );
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(CallTracer::new());
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 1_000_000,
+            data: Bytes::from_static(&[0xab]),
+            ..Default::default()
+        };
+        let result = evm.inspect(tx, CallTracer::new()).unwrap();
+        assert!(result.result.is_success());
+        (evm.inspector.clone(), result.result.gas_used())
+    }
+
+    #[test]
+    fn records_call_tree() {
+        let (tracer, _) = trace();
+        assert_eq!(tracer.gas_limit(), 1_000_000);
+        let root = tracer.root().unwrap();
+        assert_eq!(root.len(), 3);
+        assert_eq!(root.kind, CallKind::Call);
+        assert_eq!((root.from, root.to), (CALLER, TARGET));
+        assert_eq!(root.input, Bytes::from_static(&[0xab]));
+        assert_eq!(root.gas, 1_000_000 - 21_016);
+        assert!(root.is_success());
+        assert_eq!(root.logs.len(), 1);
+        assert_eq!(root.logs[0].position, 0);
+
+        let call = &root.calls[0];
+        assert_eq!(call.kind, CallKind::Call);
+        assert_eq!((call.from, call.to), (TARGET, CALLEE));
+        assert_eq!(call.value, U256::from(1));
+        assert_eq!(call.input, Bytes::from_static(&[0x11, 0x22, 0x33, 0x44]));
+        assert!(call.is_revert());
+        assert_eq!(call.output.len(), 100);
+        assert_eq!(call.logs.len(), 1);
+        assert!(call.gas_used > 0 && call.gas_used < call.gas);
+
+        let selfdestruct = &root.calls[1];
+        assert_eq!(selfdestruct.kind, CallKind::SelfDestruct);
+        assert_eq!((selfdestruct.from, selfdestruct.to), (TARGET, BENEFICIARY));
+        assert_eq!(selfdestruct.value, U256::from(100));
+    }
+}
@@ -0,0 +1,287 @@
+//! Output of Geth's `callTracer`.
+use super::{CallFrame, CallKind};
+use interpreter::InstructionResult;
+use primitives::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Options of Geth's `callTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CallTracerConfig {
+    /// Output only the top level call, without the subcalls.
+    pub only_top_call: bool,
+    /// Include the logs emitted by the calls.
+    ///
+    /// Only the logs of `LOG` instructions are included, the synthetic transfer logs of
+    /// `CfgEnv::transfer_logs` are not passed to inspectors.
+    pub with_log: bool,
+}
+
+impl CallTracerConfig {
+    /// Sets [`CallTracerConfig::only_top_call`].
+    pub fn with_only_top_call(mut self, only_top_call: bool) -> Self {
+        self.only_top_call = only_top_call;
+        self
+    }
+
+    /// Sets [`CallTracerConfig::with_log`].
+    pub fn with_log(mut self, with_log: bool) -> Self {
+        self.with_log = with_log;
+        self
+    }
+}
+
+/// Log of a [`GethCallFrame`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GethCallLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+    /// Number of subcalls of the frame made before the log was emitted.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub position: u64,
+}
+
+/// Call frame in the format of Geth's `callTracer`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethCallFrame {
+    /// Type of the frame, the name of the opcode.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Called account or created contract, missing if the creation failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Transferred value, missing for `DELEGATECALL` and `STATICCALL`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Gas available to the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas: u64,
+    /// Gas used by the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas_used: u64,
+    /// Call input or init code.
+    pub input: Bytes,
+    /// Returned data, missing if the frame failed without reverting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// Error the frame failed with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Decoded `Error(string)` revert reason.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// Logs emitted by the frame, empty if a frame in the path to it failed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<GethCallLog>,
+    /// Subcalls of the frame.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<GethCallFrame>,
+}
+
+impl GethCallFrame {
+    /// Converts the recorded frame and its subcalls.
+    ///
+    /// Logs of failed frames and all their subcalls are dropped as they are reverted.
+    pub(super) fn new(frame: &CallFrame, config: CallTracerConfig, parent_failed: bool) -> Self {
+        let failed = parent_failed || !frame.is_success();
+        let (output, error, revert_reason) = if frame.is_success() {
+            (Some(frame.output.clone()), None, None)
+        } else if frame.is_revert() && !frame.output.is_empty() {
+            (
+                Some(frame.output.clone()),
+                Some(error_message(frame.result)),
+                decode_revert_reason(&frame.output),
+            )
+        } else {
+            (None, Some(error_message(frame.result)), None)
+        };
+        let output = output.filter(|output| !output.is_empty());
+
+        let logs = if config.with_log && !failed {
+            frame
+                .logs
+                .iter()
+                .map(|log| GethCallLog {
+                    address: log.log.address,
+                    topics: log.log.topics().to_vec(),
+                    data: log.log.data.data.clone(),
+                    position: log.position as u64,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let calls = if config.only_top_call {
+            Vec::new()
+        } else {
+            frame
+                .calls
+                .iter()
+                .map(|call| Self::new(call, config, failed))
+                .collect()
+        };
+
+        Self {
+            kind: kind_name(frame.kind).to_owned(),
+            from: frame.from,
+            to: (!frame.kind.is_create() || frame.is_success()).then_some(frame.to),
+            value: (!matches!(frame.kind, CallKind::DelegateCall | CallKind::StaticCall))
+                .then_some(frame.value),
+            gas: frame.gas,
+            gas_used: frame.gas_used,
+            input: frame.input.clone(),
+            output,
+            error,
+            revert_reason,
+            logs,
+            calls,
+        }
+    }
+}
+
+/// Returns the opcode name of the frame kind.
+fn kind_name(kind: CallKind) -> &'static str {
+    match kind {
+        CallKind::Call => "CALL",
+        CallKind::CallCode => "CALLCODE",
+        CallKind::DelegateCall => "DELEGATECALL",
+        CallKind::StaticCall => "STATICCALL",
+        CallKind::Create => "CREATE",
+        CallKind::Create2 => "CREATE2",
+        CallKind::EofCreate => "EOFCREATE",
+        CallKind::SelfDestruct => "SELFDESTRUCT",
+    }
+}
+
+/// Returns the error message Geth uses for the result.
+pub(super) fn error_message(result: InstructionResult) -> String {
+    let message = match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "out of gas",
+        InstructionResult::OpcodeNotFound
+        | InstructionResult::InvalidFEOpcode
+        | InstructionResult::NotActivated => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached 1024",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::PrecompileError => "precompile failed",
+        result => return format!("{result:?}"),
+    };
+    message.to_string()
+}
+
+/// Decodes the ABI encoded `Error(string)` revert reason.
+fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    const SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+    let data = output.strip_prefix(&SELECTOR)?;
+    let offset = usize::try_from(U256::try_from_be_slice(data.get(..32)?)?).ok()?;
+    let len_end = offset.checked_add(32)?;
+    let len = usize::try_from(U256::try_from_be_slice(data.get(offset..len_end)?)?).ok()?;
+    let reason = data.get(len_end..len_end.checked_add(len)?)?;
+    String::from_utf8(reason.to_vec()).ok()
+}
+
+fn serde_hex_u64<S: serde::Serializer>(n: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", *n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{trace, BENEFICIARY, CALLEE, CALLER, TARGET};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn geth_call_tracer_output() {
+        let (tracer, gas_used) = trace();
+        let config = CallTracerConfig::default().with_log(true);
+        let frame = tracer.geth_call_frame(config, gas_used).unwrap();
+        assert_eq!(frame.gas, 1_000_000);
+        assert_eq!(frame.gas_used, gas_used);
+        assert_eq!(frame.calls[0].revert_reason.as_deref(), Some("boom"));
+
+        let callee = &tracer.root().unwrap().calls[0];
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "type": "CALL",
+                "from": CALLER,
+                "to": TARGET,
+                "value": "0x0",
+                "gas": "0xf4240",
+                "gasUsed": format!("{gas_used:#x}"),
+                "input": "0xab",
+                "logs": [{
+                    "address": TARGET,
+                    "topics": [B256::with_last_byte(0xaa)],
+                    "data": "0x",
+                    "position": "0x0",
+                }],
+                "calls": [
+                    {
+                        "type": "CALL",
+                        "from": TARGET,
+                        "to": CALLEE,
+                        "value": "0x1",
+                        "gas": format!("{:#x}", callee.gas),
+                        "gasUsed": format!("{:#x}", callee.gas_used),
+                        "input": "0x11223344",
+                        "output": callee.output,
+                        "error": "execution reverted",
+                        "revertReason": "boom",
+                    },
+                    {
+                        "type": "SELFDESTRUCT",
+                        "from": TARGET,
+                        "to": BENEFICIARY,
+                        "value": "0x64",
+                        "gas": "0x0",
+                        "gasUsed": "0x0",
+                        "input": "0x",
+                    }
+                ]
+            })
+        );
+
+        let config = CallTracerConfig::default().with_only_top_call(true);
+        let frame = tracer.geth_call_frame(config, gas_used).unwrap();
+        assert!(frame.calls.is_empty() && frame.logs.is_empty());
+    }
+
+    #[test]
+    fn config_from_json() {
+        let config: CallTracerConfig =
+            serde_json::from_value(json!({ "onlyTopCall": true })).unwrap();
+        assert_eq!(config, CallTracerConfig::default().with_only_top_call(true));
+    }
+}
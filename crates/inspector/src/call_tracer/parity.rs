@@ -0,0 +1,286 @@
+//! Flat traces of Parity's `trace_*` methods.
+use super::{geth::error_message, CallFrame, CallKind};
+use interpreter::InstructionResult;
+use primitives::{Address, Bytes, U256};
+use serde::Serialize;
+use std::{format, string::String, vec::Vec};
+
+/// Trace of a single frame in the format of Parity's `trace_*` methods.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTrace {
+    /// Action of the frame, serialized together with its `type`.
+    #[serde(flatten)]
+    pub action: TraceAction,
+    /// Error the frame failed with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Result of the frame, `None` if it failed or is a selfdestruct.
+    pub result: Option<TraceOutput>,
+    /// Number of direct subcalls of the frame.
+    pub subtraces: usize,
+    /// Indices of the subcalls leading from the top level frame to this one.
+    pub trace_address: Vec<usize>,
+}
+
+/// Action of a [`ParityTrace`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "action", rename_all = "lowercase")]
+pub enum TraceAction {
+    /// Call of any kind.
+    Call(CallAction),
+    /// Contract creation.
+    Create(CreateAction),
+    /// Selfdestruct.
+    #[serde(rename = "suicide")]
+    Selfdestruct(SelfdestructAction),
+}
+
+/// Call of a [`ParityTrace`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+    /// Caller of the frame.
+    pub from: Address,
+    /// Kind of the call, `call`, `callcode`, `delegatecall` or `staticcall`.
+    pub call_type: String,
+    /// Gas available to the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas: u64,
+    /// Call input.
+    pub input: Bytes,
+    /// Called account.
+    pub to: Address,
+    /// Transferred value, the apparent value for `delegatecall`.
+    pub value: U256,
+}
+
+/// Contract creation of a [`ParityTrace`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+    /// Creator of the contract.
+    pub from: Address,
+    /// Gas available to the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas: u64,
+    /// Init code.
+    pub init: Bytes,
+    /// Transferred value.
+    pub value: U256,
+    /// Kind of the creation, `create`, `create2` or `eofcreate`.
+    pub creation_method: String,
+}
+
+/// Selfdestruct of a [`ParityTrace`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfdestructAction {
+    /// Destroyed contract.
+    pub address: Address,
+    /// Account that receives the balance.
+    pub refund_address: Address,
+    /// Transferred balance.
+    pub balance: U256,
+}
+
+/// Result of a [`ParityTrace`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum TraceOutput {
+    /// Result of a call.
+    Call(CallOutput),
+    /// Result of a contract creation.
+    Create(CreateOutput),
+}
+
+/// Result of a call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallOutput {
+    /// Gas used by the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas_used: u64,
+    /// Returned data.
+    pub output: Bytes,
+}
+
+/// Result of a contract creation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOutput {
+    /// Address of the created contract.
+    pub address: Address,
+    /// Deployed code.
+    pub code: Bytes,
+    /// Gas used by the frame.
+    #[serde(serialize_with = "serde_hex_u64")]
+    pub gas_used: u64,
+}
+
+/// Appends the traces of the frame and its subcalls in depth-first order.
+pub(super) fn flatten(
+    frame: &CallFrame,
+    trace_address: &mut Vec<usize>,
+    traces: &mut Vec<ParityTrace>,
+) {
+    let action = match frame.kind {
+        CallKind::SelfDestruct => TraceAction::Selfdestruct(SelfdestructAction {
+            address: frame.from,
+            refund_address: frame.to,
+            balance: frame.value,
+        }),
+        kind if kind.is_create() => TraceAction::Create(CreateAction {
+            from: frame.from,
+            gas: frame.gas,
+            init: frame.input.clone(),
+            value: frame.value,
+            creation_method: match kind {
+                CallKind::Create2 => "create2",
+                CallKind::EofCreate => "eofcreate",
+                _ => "create",
+            }
+            .into(),
+        }),
+        kind => TraceAction::Call(CallAction {
+            from: frame.from,
+            call_type: match kind {
+                CallKind::CallCode => "callcode",
+                CallKind::DelegateCall => "delegatecall",
+                CallKind::StaticCall => "staticcall",
+                _ => "call",
+            }
+            .into(),
+            gas: frame.gas,
+            input: frame.input.clone(),
+            to: frame.to,
+            value: frame.value,
+        }),
+    };
+    let (result, error) = match &action {
+        _ if !frame.is_success() => (None, Some(parity_error(frame.result))),
+        TraceAction::Selfdestruct(_) => (None, None),
+        TraceAction::Create(_) => (
+            Some(TraceOutput::Create(CreateOutput {
+                address: frame.to,
+                code: frame.output.clone(),
+                gas_used: frame.gas_used,
+            })),
+            None,
+        ),
+        TraceAction::Call(_) => (
+            Some(TraceOutput::Call(CallOutput {
+                gas_used: frame.gas_used,
+                output: frame.output.clone(),
+            })),
+            None,
+        ),
+    };
+
+    traces.push(ParityTrace {
+        action,
+        error,
+        result,
+        subtraces: frame.calls.len(),
+        trace_address: trace_address.clone(),
+    });
+    for (index, call) in frame.calls.iter().enumerate() {
+        trace_address.push(index);
+        flatten(call, trace_address, traces);
+        trace_address.pop();
+    }
+}
+
+/// Returns the error message Parity uses for the result.
+fn parity_error(result: InstructionResult) -> String {
+    let message = match result {
+        InstructionResult::Revert => "Reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "Out of gas",
+        InstructionResult::OpcodeNotFound
+        | InstructionResult::InvalidFEOpcode
+        | InstructionResult::NotActivated => "Bad instruction",
+        InstructionResult::InvalidJump => "Bad jump destination",
+        InstructionResult::StackUnderflow => "Stack underflow",
+        InstructionResult::StackOverflow => "Out of stack",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "Mutable Call In Static Context",
+        InstructionResult::OutOfOffset => "Out of bounds",
+        InstructionResult::PrecompileError => "Built-in failed",
+        result => return error_message(result),
+    };
+    message.into()
+}
+
+fn serde_hex_u64<S: serde::Serializer>(n: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:#x}", *n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{trace, BENEFICIARY, CALLEE, CALLER, TARGET};
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parity_flat_traces() {
+        let (tracer, _) = trace();
+        let traces = tracer.parity_traces();
+        assert_eq!(traces.len(), 3);
+
+        let root = tracer.root().unwrap();
+        let json = serde_json::to_value(&traces).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                {
+                    "type": "call",
+                    "action": {
+                        "from": CALLER,
+                        "callType": "call",
+                        "gas": format!("{:#x}", root.gas),
+                        "input": "0xab",
+                        "to": TARGET,
+                        "value": "0x0",
+                    },
+                    "result": {
+                        "gasUsed": format!("{:#x}", root.gas_used),
+                        "output": "0x",
+                    },
+                    "subtraces": 2,
+                    "traceAddress": [],
+                },
+                {
+                    "type": "call",
+                    "action": {
+                        "from": TARGET,
+                        "callType": "call",
+                        "gas": format!("{:#x}", root.calls[0].gas),
+                        "input": "0x11223344",
+                        "to": CALLEE,
+                        "value": "0x1",
+                    },
+                    "error": "Reverted",
+                    "result": null,
+                    "subtraces": 0,
+                    "traceAddress": [0],
+                },
+                {
+                    "type": "suicide",
+                    "action": {
+                        "address": TARGET,
+                        "refundAddress": BENEFICIARY,
+                        "balance": "0x64",
+                    },
+                    "result": null,
+                    "subtraces": 0,
+                    "traceAddress": [1],
+                },
+            ])
+        );
+    }
+}
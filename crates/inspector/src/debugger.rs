@@ -0,0 +1,548 @@
+//! Debugger that records the execution of a transaction and steps through it.
+//!
+//! [`Debugger`] records the pc, opcode, stack and memory of every executed instruction together
+//! with the storage slots it reads and writes. After the execution the trace can be stepped
+//! forward and backward and run until a [`Breakpoint`] is hit. With [`ContractSources`] the
+//! program counters of a contract are mapped to its Solidity sources through the solc source map.
+mod source_map;
+
+pub use source_map::{
+    ContractSources, Jump, SourceFile, SourceLocation, SourceMap, SourceMapEntry, SourceMapError,
+};
+
+use crate::Inspector;
+use context::{ContextTr, JournalTr};
+use core::ops::Range;
+use interpreter::{
+    interpreter::EthInterpreter,
+    interpreter_types::{InputsTr, Jumps, LoopControl, MemoryTr},
+    CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InstructionResult,
+    Interpreter,
+};
+use primitives::{Address, Bytes, HashMap, StorageKey, StorageValue, U256};
+use state::bytecode::opcode;
+use std::vec::Vec;
+
+/// Storage slot read by `SLOAD` or written by `SSTORE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StorageAccess {
+    /// Key of the slot.
+    pub key: StorageKey,
+    /// Value that was read or written.
+    pub value: StorageValue,
+    /// Whether the slot was written.
+    pub is_write: bool,
+}
+
+/// State of the interpreter before an instruction is executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugStep {
+    /// Call depth of the frame.
+    pub depth: usize,
+    /// Address the frame executes in the context of.
+    pub address: Address,
+    /// Address of the executed code, equal to `address` for creates.
+    pub code_address: Address,
+    /// Whether the frame runs init code.
+    pub is_create: bool,
+    /// Whether this is the first instruction of the frame.
+    pub is_frame_start: bool,
+    /// Program counter.
+    pub pc: usize,
+    /// Opcode of the instruction.
+    pub opcode: u8,
+    /// Remaining gas of the frame.
+    pub gas_remaining: u64,
+    /// Stack, the last element is the top of the stack.
+    pub stack: Vec<U256>,
+    /// Memory of the frame.
+    pub memory: Bytes,
+    /// Storage slot accessed by the instruction.
+    pub storage: Option<StorageAccess>,
+}
+
+/// Condition that stops [`Debugger::continue_forward`] and [`Debugger::continue_back`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Breakpoint {
+    /// Instruction at the program counter of the code of the address.
+    Pc {
+        /// Address of the code.
+        address: Address,
+        /// Program counter.
+        pc: usize,
+    },
+    /// Any execution of the opcode.
+    Opcode(u8),
+    /// Start of a frame that runs the code of, or runs in the context of, the address.
+    Address(Address),
+    /// Write to the storage slot of the address.
+    StorageWrite {
+        /// Address of the storage.
+        address: Address,
+        /// Key of the slot.
+        key: StorageKey,
+    },
+}
+
+impl Breakpoint {
+    /// Returns `true` if the execution stops at the step.
+    pub fn matches(&self, step: &DebugStep) -> bool {
+        match *self {
+            Self::Pc { address, pc } => step.code_address == address && step.pc == pc,
+            Self::Opcode(opcode) => step.opcode == opcode,
+            Self::Address(address) => {
+                step.is_frame_start && (step.address == address || step.code_address == address)
+            }
+            Self::StorageWrite { address, key } => {
+                step.address == address
+                    && step
+                        .storage
+                        .is_some_and(|access| access.is_write && access.key == key)
+            }
+        }
+    }
+}
+
+/// Inspector that records the execution and steps through it, see the [module](self)
+/// documentation.
+///
+/// The debugger records a single transaction, the previous trace is discarded when a new
+/// transaction starts. Every step holds a copy of the stack and memory, so the trace of long
+/// executions can take a lot of memory.
+#[derive(Clone, Debug, Default)]
+pub struct Debugger {
+    steps: Vec<DebugStep>,
+    cursor: usize,
+    breakpoints: Vec<Breakpoint>,
+    sources: HashMap<Address, ContractSources>,
+    frame_start: bool,
+    /// Index of the first step of every active frame.
+    frames: Vec<usize>,
+    /// Step ranges of the reverted frames.
+    reverted: Vec<Range<usize>>,
+}
+
+impl Debugger {
+    /// Creates a new debugger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the sources of the contract deployed at the address.
+    pub fn with_sources(mut self, address: Address, sources: ContractSources) -> Self {
+        self.sources.insert(address, sources);
+        self
+    }
+
+    /// Adds a breakpoint.
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.add_breakpoint(breakpoint);
+        self
+    }
+
+    /// Adds a breakpoint.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Removes a breakpoint, returns `false` if it was not set.
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|b| b != breakpoint);
+        self.breakpoints.len() != len
+    }
+
+    /// Returns the breakpoints.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Returns the recorded steps.
+    pub fn steps(&self) -> &[DebugStep] {
+        &self.steps
+    }
+
+    /// Returns the index of the current step.
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns the current step, `None` if nothing was recorded.
+    pub fn current(&self) -> Option<&DebugStep> {
+        self.steps.get(self.cursor)
+    }
+
+    /// Moves to the step at the index.
+    pub fn jump_to(&mut self, index: usize) -> Option<&DebugStep> {
+        if index >= self.steps.len() {
+            return None;
+        }
+        self.cursor = index;
+        self.current()
+    }
+
+    /// Moves to the next step, `None` if the current step is the last one.
+    pub fn step_forward(&mut self) -> Option<&DebugStep> {
+        self.jump_to(self.cursor + 1)
+    }
+
+    /// Moves to the previous step, `None` if the current step is the first one.
+    pub fn step_back(&mut self) -> Option<&DebugStep> {
+        self.jump_to(self.cursor.checked_sub(1)?)
+    }
+
+    /// Moves forward to the next step that hits a breakpoint.
+    ///
+    /// Returns `None` and moves to the last step if no breakpoint is hit.
+    pub fn continue_forward(&mut self) -> Option<&DebugStep> {
+        let hit = (self.cursor + 1..self.steps.len()).find(|index| self.is_breakpoint(*index));
+        self.cursor = hit.unwrap_or(self.steps.len().saturating_sub(1));
+        hit.and_then(|index| self.steps.get(index))
+    }
+
+    /// Moves back to the previous step that hits a breakpoint.
+    ///
+    /// Returns `None` and moves to the first step if no breakpoint is hit.
+    pub fn continue_back(&mut self) -> Option<&DebugStep> {
+        let hit = (0..self.cursor)
+            .rev()
+            .find(|index| self.is_breakpoint(*index));
+        self.cursor = hit.unwrap_or_default();
+        hit.and_then(|index| self.steps.get(index))
+    }
+
+    /// Returns the value of the storage slot at the step, before its instruction is executed.
+    ///
+    /// The value is known if the slot was read or written by an earlier step. Accesses of frames
+    /// that reverted before the step are skipped, so `None` is returned if the slot was only
+    /// accessed in reverted frames even though the database has a value for it.
+    pub fn storage_at(
+        &self,
+        index: usize,
+        address: Address,
+        key: StorageKey,
+    ) -> Option<StorageValue> {
+        let index = index.min(self.steps.len());
+        let is_reverted = |step: usize| {
+            self.reverted
+                .iter()
+                .any(|range| range.end <= index && range.contains(&step))
+        };
+        self.steps[..index]
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(step_index, step)| step.address == address && !is_reverted(*step_index))
+            .find_map(|(_, step)| step.storage.filter(|access| access.key == key))
+            .map(|access| access.value)
+    }
+
+    /// Returns the source location of the step, if the sources of its code were added.
+    pub fn source_location(&self, step: &DebugStep) -> Option<SourceLocation> {
+        if step.is_create {
+            return None;
+        }
+        self.sources.get(&step.code_address)?.location(step.pc)
+    }
+
+    /// Returns the sources of the contract deployed at the address.
+    pub fn sources(&self, address: &Address) -> Option<&ContractSources> {
+        self.sources.get(address)
+    }
+
+    /// Discards the recorded steps.
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.cursor = 0;
+        self.frames.clear();
+        self.reverted.clear();
+    }
+
+    fn is_breakpoint(&self, index: usize) -> bool {
+        let step = &self.steps[index];
+        self.breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.matches(step))
+    }
+
+    fn start_frame<CTX: ContextTr>(&mut self, context: &mut CTX) {
+        if context.journal().depth() == 0 {
+            self.clear();
+        }
+        self.frames.push(self.steps.len());
+    }
+
+    fn end_frame(&mut self, result: &InstructionResult) {
+        if let Some(start) = self.frames.pop() {
+            if !result.is_ok() {
+                self.reverted.push(start..self.steps.len());
+            }
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for Debugger {
+    fn initialize_interp(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.frame_start = true;
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        let opcode = interp.bytecode.opcode();
+        let memory = interp.memory.slice(0..interp.memory.size());
+        // Consecutive steps usually have the same memory, share it.
+        let memory = match self.steps.last() {
+            Some(last) if last.memory[..] == memory[..] => last.memory.clone(),
+            _ => Bytes::copy_from_slice(&memory),
+        };
+        let storage = match (opcode, interp.stack.peek(0), interp.stack.peek(1)) {
+            (opcode::SSTORE, Ok(key), Ok(value)) => Some(StorageAccess {
+                key,
+                value,
+                is_write: true,
+            }),
+            _ => None,
+        };
+        let address = interp.input.target_address();
+        self.steps.push(DebugStep {
+            depth: context.journal().depth(),
+            address,
+            code_address: interp.input.bytecode_address().copied().unwrap_or(address),
+            is_create: interp.input.bytecode_address().is_none(),
+            is_frame_start: core::mem::take(&mut self.frame_start),
+            pc: interp.bytecode.pc(),
+            opcode,
+            gas_remaining: interp.control.gas().remaining(),
+            stack: interp.stack.data().clone(),
+            memory,
+            storage,
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(step) = self.steps.last_mut() else {
+            return;
+        };
+        if step.opcode == opcode::SLOAD {
+            if let (Some(key), Ok(value)) = (step.stack.last(), interp.stack.peek(0)) {
+                step.storage = Some(StorageAccess {
+                    key: *key,
+                    value,
+                    is_write: false,
+                });
+            }
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.start_frame(context);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.end_frame(outcome.instruction_result());
+    }
+
+    fn create(&mut self, context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.start_frame(context);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end_frame(outcome.instruction_result());
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.start_frame(context);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end_frame(outcome.instruction_result());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{CacheDB, EmptyDB};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+
+    #[test]
+    fn step_back_and_breakpoints() {
+        // SSTORE(1, 2), SSTORE(1, 3), MSTORE(0, SLOAD(1))
+        let code = [
+            opcode::PUSH1,
+            2,
+            opcode::PUSH1,
+            1,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            3,
+            opcode::PUSH1,
+            1,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            1,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0,
+            opcode::MSTORE,
+            opcode::STOP,
+        ];
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(code.to_vec()))),
+        );
+        let mut evm = Context::mainnet().with_db(db).build_mainnet_with_inspector(
+            Debugger::new().with_breakpoint(Breakpoint::StorageWrite {
+                address: TARGET,
+                key: U256::from(1),
+            }),
+        );
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        assert!(evm.inspect_with_tx(tx).unwrap().result.is_success());
+        let debugger = &mut evm.inspector;
+        assert_eq!(debugger.steps().len(), 11);
+        assert!(debugger.current().unwrap().is_frame_start);
+
+        let hit = debugger.continue_forward().unwrap();
+        assert_eq!(
+            (hit.pc, hit.stack.clone()),
+            (4, vec![U256::from(2), U256::from(1)])
+        );
+        let hit = debugger.continue_forward().unwrap();
+        assert_eq!(hit.pc, 9);
+        assert_eq!(
+            debugger.storage_at(debugger.position(), TARGET, U256::from(1)),
+            Some(U256::from(2))
+        );
+        assert!(debugger.continue_forward().is_none());
+        assert_eq!(debugger.current().unwrap().opcode, opcode::STOP);
+        assert_eq!(
+            debugger.current().unwrap().memory[..],
+            U256::from(3).to_be_bytes::<32>()
+        );
+
+        // The SLOAD reads the last written value.
+        debugger.step_back();
+        debugger.step_back();
+        let sload = debugger.step_back().unwrap();
+        assert_eq!(sload.opcode, opcode::SLOAD);
+        assert_eq!(
+            sload.storage,
+            Some(StorageAccess {
+                key: U256::from(1),
+                value: U256::from(3),
+                is_write: false,
+            })
+        );
+        assert_eq!(debugger.continue_back().unwrap().pc, 9);
+
+        debugger.add_breakpoint(Breakpoint::Pc {
+            address: TARGET,
+            pc: 0,
+        });
+        assert_eq!(debugger.continue_back().unwrap().pc, 4);
+        assert_eq!(debugger.continue_back().unwrap().pc, 0);
+        assert!(debugger.step_back().is_none());
+    }
+
+    #[test]
+    fn storage_of_reverted_frames() {
+        const REVERTER: Address = address!("0x3000000000000000000000000000000000000003");
+        // SSTORE(1, 2), DELEGATECALL(REVERTER)
+        let mut code = vec![
+            opcode::PUSH1,
+            2,
+            opcode::PUSH1,
+            1,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::DUP1,
+            opcode::PUSH20,
+        ];
+        code.extend_from_slice(REVERTER.as_slice());
+        code.extend_from_slice(&[opcode::GAS, opcode::DELEGATECALL, opcode::STOP]);
+        // SSTORE(1, 5), REVERT(0, 0)
+        let reverter = [
+            opcode::PUSH1,
+            5,
+            opcode::PUSH1,
+            1,
+            opcode::SSTORE,
+            opcode::PUSH1,
+            0,
+            opcode::DUP1,
+            opcode::REVERT,
+        ];
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(code))),
+        );
+        db.insert_account_info(
+            REVERTER,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(reverter.to_vec()))),
+        );
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(Debugger::new());
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        assert!(evm.inspect_with_tx(tx).unwrap().result.is_success());
+        let debugger = &evm.inspector;
+        let revert = debugger
+            .steps()
+            .iter()
+            .position(|step| step.opcode == opcode::REVERT)
+            .unwrap();
+
+        // The write is visible inside the frame and undone after it reverts.
+        assert_eq!(
+            debugger.storage_at(revert, TARGET, U256::from(1)),
+            Some(U256::from(5))
+        );
+        assert_eq!(
+            debugger.storage_at(debugger.steps().len(), TARGET, U256::from(1)),
+            Some(U256::from(2))
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Solidity source maps, see the [solc documentation](https://docs.soliditylang.org/en/latest/internals/source_mappings.html).
+use core::fmt;
+use primitives::HashMap;
+use state::bytecode::opcode;
+use std::{string::String, vec, vec::Vec};
+
+/// Kind of jump of an instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Jump {
+    /// Jump into a function.
+    In,
+    /// Return from a function.
+    Out,
+    /// Regular jump or no jump.
+    #[default]
+    Regular,
+}
+
+/// Source range of an instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SourceMapEntry {
+    /// Byte offset of the range in the source file, `None` if the instruction is not mapped.
+    pub offset: Option<usize>,
+    /// Byte length of the range, `None` if the instruction is not mapped.
+    pub length: Option<usize>,
+    /// Index of the source file, `None` if the instruction is not mapped to a file.
+    pub file: Option<u32>,
+    /// Kind of jump of the instruction.
+    pub jump: Jump,
+    /// Modifier depth of the instruction.
+    pub modifier_depth: u32,
+}
+
+/// Compressed source map entry that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceMapError {
+    /// Index of the entry.
+    pub index: usize,
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid source map entry {}", self.index)
+    }
+}
+
+impl core::error::Error for SourceMapError {}
+
+/// Source map of a contract, one [`SourceMapEntry`] per instruction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Entries in the order of the instructions.
+    pub entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    /// Parses the compressed `s:l:f:j:m;...` source map emitted by solc.
+    ///
+    /// Empty fields take the value of the previous entry and `-1` marks an unmapped field.
+    pub fn parse(source_map: &str) -> Result<Self, SourceMapError> {
+        let mut entries = Vec::new();
+        let mut entry = SourceMapEntry::default();
+        for (index, compressed) in source_map.split(';').enumerate() {
+            let error = || SourceMapError { index };
+            for (field, value) in compressed.split(':').enumerate() {
+                if value.is_empty() {
+                    continue;
+                }
+                match field {
+                    0 => entry.offset = parse_field(value).ok_or_else(error)?,
+                    1 => entry.length = parse_field(value).ok_or_else(error)?,
+                    2 => entry.file = parse_field(value).ok_or_else(error)?,
+                    3 => {
+                        entry.jump = match value {
+                            "i" => Jump::In,
+                            "o" => Jump::Out,
+                            "-" => Jump::Regular,
+                            _ => return Err(error()),
+                        }
+                    }
+                    4 => entry.modifier_depth = value.parse().map_err(|_| error())?,
+                    _ => return Err(error()),
+                }
+            }
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Parses a numeric field of an entry, `-1` marks an unmapped field.
+fn parse_field<T: TryFrom<i64>>(value: &str) -> Option<Option<T>> {
+    match value.parse::<i64>().ok()? {
+        -1 => Some(None),
+        value => T::try_from(value).ok().map(Some),
+    }
+}
+
+/// Source file of a contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SourceFile {
+    /// Name of the file.
+    pub name: String,
+    /// Content of the file.
+    pub content: String,
+}
+
+/// Source location of a program counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// Index of the source file.
+    pub file: u32,
+    /// Byte offset of the range in the source file.
+    pub offset: usize,
+    /// Byte length of the range.
+    pub length: usize,
+    /// Kind of jump of the instruction.
+    pub jump: Jump,
+}
+
+/// Source map and sources of a deployed contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContractSources {
+    source_map: SourceMap,
+    /// Instruction index of every program counter, `None` for push data.
+    instructions: Vec<Option<usize>>,
+    files: HashMap<u32, SourceFile>,
+}
+
+impl ContractSources {
+    /// Creates the sources of the legacy runtime bytecode with its deployed source map.
+    pub fn new(bytecode: &[u8], source_map: SourceMap) -> Self {
+        let mut instructions = vec![None; bytecode.len()];
+        let mut pc = 0;
+        let mut index = 0;
+        while pc < bytecode.len() {
+            instructions[pc] = Some(index);
+            let op = bytecode[pc];
+            if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+                pc += (op - opcode::PUSH0) as usize;
+            }
+            pc += 1;
+            index += 1;
+        }
+        Self {
+            source_map,
+            instructions,
+            files: HashMap::default(),
+        }
+    }
+
+    /// Adds the source file with the index used by the source map.
+    pub fn with_file(mut self, index: u32, file: SourceFile) -> Self {
+        self.files.insert(index, file);
+        self
+    }
+
+    /// Returns the source file with the index.
+    pub fn file(&self, index: u32) -> Option<&SourceFile> {
+        self.files.get(&index)
+    }
+
+    /// Returns the source location of the instruction at the program counter, `None` if the
+    /// program counter points into push data.
+    pub fn location(&self, pc: usize) -> Option<SourceLocation> {
+        let entry = self
+            .source_map
+            .entries
+            .get((*self.instructions.get(pc)?)?)?;
+        Some(SourceLocation {
+            file: entry.file?,
+            offset: entry.offset?,
+            length: entry.length?,
+            jump: entry.jump,
+        })
+    }
+
+    /// Returns the source text of the location, `None` if its file was not added.
+    pub fn text(&self, location: &SourceLocation) -> Option<&str> {
+        self.file(location.file)?
+            .content
+            .get(location.offset..location.offset + location.length)
+    }
+
+    /// Returns the one-based line and column of the start of the location.
+    pub fn line_column(&self, location: &SourceLocation) -> Option<(usize, usize)> {
+        let content = self.file(location.file)?.content.as_bytes();
+        let before = content.get(..location.offset)?;
+        let line = before.iter().filter(|byte| **byte == b'\n').count() + 1;
+        let line_start = before
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        Some((line, location.offset - line_start + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_locate() {
+        let source_map = SourceMap::parse("0:10:0:-:0;13:4::i;;-1:-1:-1:o").unwrap();
+        assert_eq!(source_map.entries.len(), 4);
+        assert_eq!(
+            source_map.entries[2],
+            SourceMapEntry {
+                offset: Some(13),
+                length: Some(4),
+                file: Some(0),
+                jump: Jump::In,
+                modifier_depth: 0,
+            }
+        );
+        assert_eq!(
+            (source_map.entries[3].offset, source_map.entries[3].length),
+            (None, None)
+        );
+        assert_eq!(source_map.entries[3].file, None);
+        assert_eq!(SourceMap::parse("0:1;x"), Err(SourceMapError { index: 1 }));
+        assert_eq!(SourceMap::parse("0:1;-2"), Err(SourceMapError { index: 1 }));
+
+        // PUSH1 0x80 PUSH2 0x0102 JUMP STOP
+        let bytecode = [
+            opcode::PUSH1,
+            0x80,
+            opcode::PUSH2,
+            1,
+            2,
+            opcode::JUMP,
+            opcode::STOP,
+        ];
+        let sources = ContractSources::new(&bytecode, source_map).with_file(
+            0,
+            SourceFile {
+                name: "A.sol".into(),
+                content: "contract A {\n  f();\n}".into(),
+            },
+        );
+        let location = sources.location(2).unwrap();
+        assert_eq!((location.offset, location.jump), (13, Jump::In));
+        assert_eq!(sources.text(&location), Some("  f("));
+        assert_eq!(sources.line_column(&location), Some((2, 1)));
+        assert_eq!(sources.location(3), None);
+        assert_eq!(sources.location(5).unwrap().offset, 13);
+        assert_eq!(sources.location(6), None);
+    }
+}
@@ -8,6 +8,9 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+mod access_list;
+pub mod call_tracer;
+pub mod debugger;
 #[cfg(all(feature = "std", feature = "serde-json"))]
 mod eip3155;
 mod gas;
@@ -16,15 +19,24 @@ mod inspect;
 mod inspector;
 mod mainnet_inspect;
 mod noop;
+pub mod prestate;
+#[cfg(feature = "std")]
+pub mod profiler;
 mod traits;
 
 /// Inspector implementations.
 pub mod inspectors {
+    pub use super::access_list::AccessListInspector;
+    pub use super::call_tracer::CallTracer;
+    pub use super::debugger::Debugger;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
+    #[cfg(feature = "std")]
+    pub use super::profiler::Profiler;
 }
 
+pub use access_list::{create_access_list, AccessListResult};
 pub use handler::{inspect_instructions, InspectorHandler};
 pub use inspect::{InspectCommitEvm, InspectEvm};
 pub use inspector::*;
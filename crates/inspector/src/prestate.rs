@@ -0,0 +1,401 @@
+//! Prestate tracer, the equivalent of Geth's `prestateTracer`.
+//!
+//! The state of the accounts touched by the transaction is built from the [`EvmState`] returned
+//! by the execution. Storage values before the execution are the original values tracked by
+//! [`EvmStorageSlot`](state::EvmStorageSlot), while balance, nonce and code are read from the
+//! database the transaction was executed on, so it has to be traced before the state is
+//! committed.
+use database_interface::DatabaseRef;
+use primitives::{Address, Bytes, B256, KECCAK_EMPTY, U256};
+use state::{Account, AccountInfo, EvmState};
+use std::collections::BTreeMap;
+
+/// Options of the prestate tracer, the same as Geth's `prestateTracer` ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase", default)
+)]
+pub struct PrestateConfig {
+    /// Return the state before and after the transaction, only with the changed values.
+    pub diff_mode: bool,
+    /// Leave out the code of the accounts.
+    pub disable_code: bool,
+    /// Leave out the storage of the accounts.
+    pub disable_storage: bool,
+    /// Include accounts that are empty before the transaction, used only without the diff mode.
+    pub include_empty: bool,
+}
+
+impl PrestateConfig {
+    /// Sets [`PrestateConfig::diff_mode`].
+    pub fn with_diff_mode(mut self, diff_mode: bool) -> Self {
+        self.diff_mode = diff_mode;
+        self
+    }
+
+    /// Sets [`PrestateConfig::disable_code`].
+    pub fn with_disable_code(mut self, disable_code: bool) -> Self {
+        self.disable_code = disable_code;
+        self
+    }
+
+    /// Sets [`PrestateConfig::disable_storage`].
+    pub fn with_disable_storage(mut self, disable_storage: bool) -> Self {
+        self.disable_storage = disable_storage;
+        self
+    }
+
+    /// Sets [`PrestateConfig::include_empty`].
+    pub fn with_include_empty(mut self, include_empty: bool) -> Self {
+        self.include_empty = include_empty;
+        self
+    }
+}
+
+/// State of an account in the prestate trace.
+///
+/// Fields that are `None` or empty are left out, in diff mode the post state contains only
+/// the values changed by the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct AccountState {
+    /// Balance of the account.
+    #[cfg_attr(feature = "serde-json", serde(skip_serializing_if = "Option::is_none"))]
+    pub balance: Option<U256>,
+    /// Nonce of the account, `None` if it is zero.
+    #[cfg_attr(feature = "serde-json", serde(skip_serializing_if = "Option::is_none"))]
+    pub nonce: Option<u64>,
+    /// Code of the account, `None` if it has no code.
+    #[cfg_attr(feature = "serde-json", serde(skip_serializing_if = "Option::is_none"))]
+    pub code: Option<Bytes>,
+    /// Accessed storage slots.
+    #[cfg_attr(
+        feature = "serde-json",
+        serde(skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+/// State of the accounts before and after the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-json", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffMode {
+    /// Changed accounts before the transaction.
+    pub pre: BTreeMap<Address, AccountState>,
+    /// Changed accounts after the transaction.
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+/// Output of the prestate tracer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum PrestateFrame {
+    /// Output in diff mode.
+    Diff(DiffMode),
+    /// State of all accessed accounts before the transaction.
+    Prestate(BTreeMap<Address, AccountState>),
+}
+
+/// Returns the prestate trace of the transaction.
+///
+/// `state` is the state returned by the execution of the transaction and `db` the database it
+/// was executed on, before the state is committed.
+///
+/// Without the diff mode all accounts loaded by the transaction are returned with the values
+/// they had before the transaction. In diff mode, as in Geth, only the changed accounts are
+/// returned. The pre state contains the changed storage slots, the post state contains the
+/// changed values and leaves out the cleared storage slots. Accounts created by the
+/// transaction are only in the post state and accounts destroyed by it only in the pre state.
+pub fn prestate_trace<DB: DatabaseRef>(
+    state: &EvmState,
+    db: &DB,
+    config: PrestateConfig,
+) -> Result<PrestateFrame, DB::Error> {
+    let mut prestate = BTreeMap::new();
+    let mut diff = DiffMode::default();
+    for (address, account) in state {
+        let info = db.basic_ref(*address)?;
+        let existed = info.as_ref().is_some_and(|info| !info.is_empty());
+        let info = info.unwrap_or_default();
+
+        if !config.diff_mode {
+            if existed || config.include_empty {
+                let mut pre = account_state(db, &info, config)?;
+                if !config.disable_storage {
+                    pre.storage = account
+                        .storage
+                        .iter()
+                        .map(|(key, slot)| (B256::from(*key), B256::from(slot.original_value)))
+                        .collect();
+                }
+                prestate.insert(*address, pre);
+            }
+            continue;
+        }
+
+        let destroyed = account.is_selfdestructed() || (account.is_touched() && account.is_empty());
+        if existed {
+            let mut pre = account_state(db, &info, config)?;
+            if !config.disable_storage {
+                pre.storage = account
+                    .changed_storage_slots()
+                    .map(|(key, slot)| (B256::from(*key), B256::from(slot.original_value)))
+                    .collect();
+            }
+            if destroyed {
+                diff.pre.insert(*address, pre);
+                continue;
+            }
+            if let Some(post) = changed_state(db, &info, account, config)? {
+                diff.pre.insert(*address, pre);
+                diff.post.insert(*address, post);
+            }
+        } else if !destroyed {
+            if let Some(post) = changed_state(db, &AccountInfo::default(), account, config)? {
+                diff.post.insert(*address, post);
+            }
+        }
+    }
+
+    Ok(if config.diff_mode {
+        PrestateFrame::Diff(diff)
+    } else {
+        PrestateFrame::Prestate(prestate)
+    })
+}
+
+/// Returns the balance, nonce and code of the account.
+fn account_state<DB: DatabaseRef>(
+    db: &DB,
+    info: &AccountInfo,
+    config: PrestateConfig,
+) -> Result<AccountState, DB::Error> {
+    Ok(AccountState {
+        balance: Some(info.balance),
+        nonce: (info.nonce != 0).then_some(info.nonce),
+        code: if config.disable_code {
+            None
+        } else {
+            code(db, info)?
+        },
+        storage: BTreeMap::new(),
+    })
+}
+
+/// Returns the values of the account changed from `pre`, `None` if nothing changed.
+fn changed_state<DB: DatabaseRef>(
+    db: &DB,
+    pre: &AccountInfo,
+    account: &Account,
+    config: PrestateConfig,
+) -> Result<Option<AccountState>, DB::Error> {
+    let post = &account.info;
+    let mut changed = false;
+    let mut state = AccountState::default();
+    if post.balance != pre.balance {
+        changed = true;
+        state.balance = Some(post.balance);
+    }
+    if post.nonce != pre.nonce {
+        changed = true;
+        state.nonce = Some(post.nonce);
+    }
+    if post.code_hash != pre.code_hash {
+        changed = true;
+        if !config.disable_code {
+            state.code = code(db, post)?;
+        }
+    }
+    for (key, slot) in account.changed_storage_slots() {
+        changed = true;
+        if !config.disable_storage && !slot.present_value.is_zero() {
+            state
+                .storage
+                .insert(B256::from(*key), B256::from(slot.present_value));
+        }
+    }
+    Ok(changed.then_some(state))
+}
+
+/// Returns the code of the account, `None` if it has no code.
+fn code<DB: DatabaseRef>(db: &DB, info: &AccountInfo) -> Result<Option<Bytes>, DB::Error> {
+    if info.code_hash == KECCAK_EMPTY {
+        return Ok(None);
+    }
+    let code = match &info.code {
+        Some(code) => code.original_bytes(),
+        None => db.code_by_hash_ref(info.code_hash)?.original_bytes(),
+    };
+    Ok(Some(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context::{Context, ContextTr, TxEnv};
+    use database::{CacheDB, EmptyDB};
+    use handler::{ExecuteEvm, MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{bytecode::opcode, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    const QUERIED: Address = address!("0x3000000000000000000000000000000000000003");
+
+    fn slot(value: u64) -> B256 {
+        B256::from(U256::from(value))
+    }
+
+    /// Executes a call to `TARGET` that reads slot 2 and the balance of `QUERIED`, sets slot 1
+    /// from 3 to 5 and clears slot 4.
+    fn trace(config: PrestateConfig) -> (PrestateFrame, Bytes) {
+        let code = Bytes::from(
+            [
+                &[opcode::PUSH1, 2, opcode::SLOAD, opcode::POP][..],
+                &[opcode::PUSH20],
+                QUERIED.as_slice(),
+                &[opcode::BALANCE, opcode::POP],
+                &[opcode::PUSH1, 5, opcode::PUSH1, 1, opcode::SSTORE],
+                &[opcode::PUSH1, 0, opcode::PUSH1, 4, opcode::SSTORE],
+            ]
+            .concat(),
+        );
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(1000)));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(code.clone())),
+        );
+        db.insert_account_storage(TARGET, U256::from(1), U256::from(3))
+            .unwrap();
+        db.insert_account_storage(TARGET, U256::from(4), U256::from(6))
+            .unwrap();
+
+        let mut evm = Context::mainnet().with_db(db).build_mainnet();
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            value: U256::from(10),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        let result = evm.transact(tx).unwrap();
+        assert!(result.result.is_success());
+        let frame = prestate_trace(&result.state, &*evm.ctx.db(), config).unwrap();
+        (frame, code)
+    }
+
+    #[test]
+    fn prestate() {
+        let (frame, code) = trace(PrestateConfig::default());
+        let expected = BTreeMap::from([
+            (
+                CALLER,
+                AccountState {
+                    balance: Some(U256::from(1000)),
+                    ..Default::default()
+                },
+            ),
+            (
+                TARGET,
+                AccountState {
+                    balance: Some(U256::ZERO),
+                    code: Some(code),
+                    storage: BTreeMap::from([
+                        (slot(1), slot(3)),
+                        (slot(2), slot(0)),
+                        (slot(4), slot(6)),
+                    ]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        assert_eq!(frame, PrestateFrame::Prestate(expected));
+
+        let config = PrestateConfig::default()
+            .with_disable_code(true)
+            .with_disable_storage(true)
+            .with_include_empty(true);
+        let PrestateFrame::Prestate(prestate) = trace(config).0 else {
+            panic!("prestate expected");
+        };
+        let empty = AccountState {
+            balance: Some(U256::ZERO),
+            ..Default::default()
+        };
+        assert_eq!(prestate[&QUERIED], empty);
+        assert_eq!(prestate[&TARGET].code, None);
+        assert!(prestate[&TARGET].storage.is_empty());
+    }
+
+    #[test]
+    fn diff_mode() {
+        let (frame, code) = trace(PrestateConfig::default().with_diff_mode(true));
+        let pre = BTreeMap::from([
+            (
+                CALLER,
+                AccountState {
+                    balance: Some(U256::from(1000)),
+                    ..Default::default()
+                },
+            ),
+            (
+                TARGET,
+                AccountState {
+                    balance: Some(U256::ZERO),
+                    code: Some(code),
+                    storage: BTreeMap::from([(slot(1), slot(3)), (slot(4), slot(6))]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let post = BTreeMap::from([
+            (
+                CALLER,
+                AccountState {
+                    balance: Some(U256::from(990)),
+                    nonce: Some(1),
+                    ..Default::default()
+                },
+            ),
+            (
+                TARGET,
+                AccountState {
+                    balance: Some(U256::from(10)),
+                    storage: BTreeMap::from([(slot(1), slot(5))]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        assert_eq!(frame, PrestateFrame::Diff(DiffMode { pre, post }));
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn serde_output() {
+        let (frame, _) = trace(PrestateConfig::default().with_diff_mode(true));
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(
+            json["post"][CALLER.to_string()],
+            serde_json::json!({ "balance": "0x3de", "nonce": 1 })
+        );
+        assert_eq!(
+            serde_json::from_value::<PrestateFrame>(json).unwrap(),
+            frame
+        );
+
+        let config: PrestateConfig =
+            serde_json::from_value(serde_json::json!({ "diffMode": true })).unwrap();
+        assert_eq!(config, PrestateConfig::default().with_diff_mode(true));
+    }
+}
@@ -0,0 +1,471 @@
+//! Profiler that attributes gas and wall-clock time to opcodes, call frames and precompiles.
+//!
+//! [`Profiler`] builds on [`Inspector::step`] and [`Inspector::step_end`] and uses the
+//! [`GasInspector`] to measure the gas of every executed opcode. The profile can be written as
+//! a per-opcode histogram and as collapsed stacks that tools like `inferno` and `flamegraph.pl`
+//! turn into flamegraphs.
+use crate::{inspectors::GasInspector, Inspector};
+use context::{ContextTr, JournalTr};
+use interpreter::{
+    gas::CALL_STIPEND,
+    interpreter_types::{Jumps, LoopControl},
+    CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, EOFCreateInputs, Interpreter,
+    InterpreterResult, InterpreterTypes,
+};
+use primitives::{hex, Address, HashMap};
+use state::bytecode::opcode::OpCode;
+use std::{
+    fmt,
+    io::{self, Write},
+    string::String,
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+/// Gas and wall-clock time spent by an opcode, a frame or a precompile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProfileStats {
+    /// Number of executions.
+    pub count: u64,
+    /// Gas spent.
+    pub gas: u64,
+    /// Wall-clock time spent.
+    pub time: Duration,
+}
+
+impl ProfileStats {
+    /// Adds one execution.
+    pub fn add(&mut self, gas: u64, time: Duration) {
+        self.count += 1;
+        self.gas += gas;
+        self.time += time;
+    }
+
+    /// Adds the executions of `other`.
+    pub fn merge(&mut self, other: &ProfileStats) {
+        self.count += other.count;
+        self.gas += other.gas;
+        self.time += other.time;
+    }
+}
+
+/// Call frame of the profile.
+///
+/// Calls are identified by the address of the executed code and the function selector, the
+/// first four bytes of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FrameId {
+    /// Address of the executed code, or the created contract.
+    pub address: Address,
+    /// Function selector, `None` if the input is shorter than four bytes or for creates.
+    pub selector: Option<[u8; 4]>,
+    /// Whether the frame creates a contract.
+    pub is_create: bool,
+}
+
+impl fmt::Display for FrameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.address)?;
+        if self.is_create {
+            f.write_str(":create")
+        } else if let Some(selector) = self.selector {
+            write!(f, ":{}", hex::encode_prefixed(selector))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Value of the collapsed stacks written by [`Profiler::write_collapsed`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ProfileMetric {
+    /// Gas spent.
+    #[default]
+    Gas,
+    /// Wall-clock time spent, in nanoseconds.
+    Time,
+}
+
+/// Frame that has not ended yet.
+#[derive(Clone, Debug)]
+struct ActiveFrame {
+    id: FrameId,
+    is_precompile: bool,
+    start: Instant,
+    /// Gas spent by the opcodes of the frame, without the gas of the subcalls.
+    self_gas: u64,
+    /// Time spent in the subcalls.
+    children_time: Duration,
+}
+
+/// Inspector that profiles the execution, see the [module](self) documentation.
+///
+/// Frames and precompiles are profiled inclusively, with the gas and time of their subcalls.
+/// Gas forwarded to a subcall is attributed to the subcall and not to the opcode that made it.
+/// Collapsed stacks hold the exclusive gas and time of every call path.
+///
+/// The profile accumulates across transactions.
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    gas_inspector: GasInspector,
+    opcode: u8,
+    step_start: Option<Instant>,
+    stack: Vec<ActiveFrame>,
+    opcodes: Vec<ProfileStats>,
+    frames: HashMap<FrameId, ProfileStats>,
+    precompiles: HashMap<Address, ProfileStats>,
+    collapsed: HashMap<String, ProfileStats>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    /// Creates a new profiler.
+    pub fn new() -> Self {
+        Self {
+            gas_inspector: GasInspector::new(),
+            opcode: 0,
+            step_start: None,
+            stack: Vec::new(),
+            opcodes: vec![ProfileStats::default(); 256],
+            frames: HashMap::default(),
+            precompiles: HashMap::default(),
+            collapsed: HashMap::default(),
+        }
+    }
+
+    /// Returns the profile of the executed opcodes.
+    pub fn opcodes(&self) -> impl Iterator<Item = (u8, &ProfileStats)> {
+        self.opcodes
+            .iter()
+            .enumerate()
+            .filter(|(_, stats)| stats.count > 0)
+            .map(|(opcode, stats)| (opcode as u8, stats))
+    }
+
+    /// Returns the profile of the call frames.
+    pub fn frames(&self) -> &HashMap<FrameId, ProfileStats> {
+        &self.frames
+    }
+
+    /// Returns the profile of the called precompiles.
+    pub fn precompiles(&self) -> &HashMap<Address, ProfileStats> {
+        &self.precompiles
+    }
+
+    /// Returns the exclusive profile of the call paths, frames are separated by `;`.
+    pub fn collapsed(&self) -> &HashMap<String, ProfileStats> {
+        &self.collapsed
+    }
+
+    /// Adds the profile of `other`, for example of a profiler used by another thread.
+    pub fn merge(&mut self, other: &Profiler) {
+        for (stats, other) in self.opcodes.iter_mut().zip(&other.opcodes) {
+            stats.merge(other);
+        }
+        for (id, other) in &other.frames {
+            self.frames.entry(*id).or_default().merge(other);
+        }
+        for (address, other) in &other.precompiles {
+            self.precompiles.entry(*address).or_default().merge(other);
+        }
+        for (path, other) in &other.collapsed {
+            self.collapsed.entry(path.clone()).or_default().merge(other);
+        }
+    }
+
+    /// Writes the collapsed stacks, one `frame;frame;frame value` line per call path.
+    pub fn write_collapsed<W: Write>(&self, mut out: W, metric: ProfileMetric) -> io::Result<()> {
+        let mut paths: Vec<_> = self.collapsed.iter().collect();
+        paths.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        for (path, stats) in paths {
+            let value = match metric {
+                ProfileMetric::Gas => stats.gas as u128,
+                ProfileMetric::Time => stats.time.as_nanos(),
+            };
+            writeln!(out, "{path} {value}")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the per-opcode histogram sorted by the spent gas, followed by the precompiles.
+    pub fn write_opcode_histogram<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let mut opcodes: Vec<_> = self.opcodes().collect();
+        opcodes.sort_unstable_by(|a, b| b.1.gas.cmp(&a.1.gas).then(a.0.cmp(&b.0)));
+        writeln!(
+            out,
+            "{:<16} {:>12} {:>14} {:>14} {:>10}",
+            "opcode", "count", "gas", "time (ns)", "avg (ns)"
+        )?;
+        for (opcode, stats) in opcodes {
+            let name = OpCode::new(opcode).map_or("UNKNOWN", OpCode::as_str);
+            write_row(&mut out, name, stats)?;
+        }
+
+        if !self.precompiles.is_empty() {
+            let mut precompiles: Vec<_> = self.precompiles.iter().collect();
+            precompiles.sort_unstable_by(|a, b| b.1.gas.cmp(&a.1.gas).then(a.0.cmp(b.0)));
+            writeln!(out)?;
+            writeln!(
+                out,
+                "{:<42} {:>12} {:>14} {:>14} {:>10}",
+                "precompile", "count", "gas", "time (ns)", "avg (ns)"
+            )?;
+            for (address, stats) in precompiles {
+                write_row(&mut out, &format!("{address:<42}"), stats)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn start(&mut self, id: FrameId, is_precompile: bool, forwarded_gas: u64) {
+        // Gas forwarded to the frame is spent by the frame, not by the opcode of the parent.
+        if let Some(parent) = self.stack.last_mut() {
+            parent.self_gas = parent.self_gas.saturating_sub(forwarded_gas);
+            let stats = &mut self.opcodes[self.opcode as usize];
+            stats.gas = stats.gas.saturating_sub(forwarded_gas);
+        }
+        self.stack.push(ActiveFrame {
+            id,
+            is_precompile,
+            start: Instant::now(),
+            self_gas: 0,
+            children_time: Duration::ZERO,
+        });
+    }
+
+    fn end(&mut self, result: &InterpreterResult, address: Option<Address>) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        let time = frame.start.elapsed();
+        let gas = result.gas.spent();
+        if let Some(address) = address {
+            frame.id.address = address;
+        }
+
+        self.frames.entry(frame.id).or_default().add(gas, time);
+        if frame.is_precompile {
+            frame.self_gas = gas;
+            self.precompiles
+                .entry(frame.id.address)
+                .or_default()
+                .add(gas, time);
+        }
+
+        let mut path = String::new();
+        for parent in &self.stack {
+            path.push_str(&parent.id.to_string());
+            path.push(';');
+        }
+        path.push_str(&frame.id.to_string());
+        self.collapsed
+            .entry(path)
+            .or_default()
+            .add(frame.self_gas, time.saturating_sub(frame.children_time));
+
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children_time += time;
+        }
+    }
+}
+
+fn write_row<W: Write>(out: &mut W, name: &str, stats: &ProfileStats) -> io::Result<()> {
+    let time = stats.time.as_nanos();
+    writeln!(
+        out,
+        "{:<16} {:>12} {:>14} {:>14} {:>10}",
+        name,
+        stats.count,
+        stats.gas,
+        time,
+        time / stats.count.max(1) as u128
+    )
+}
+
+impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for Profiler {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        self.gas_inspector.initialize_interp(interp.control.gas());
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        self.gas_inspector.step(interp.control.gas());
+        self.opcode = interp.bytecode.opcode();
+        self.step_start = Some(Instant::now());
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        let time = self
+            .step_start
+            .take()
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+        self.gas_inspector.step_end(interp.control.gas_mut());
+        let gas = self.gas_inspector.last_gas_cost();
+        self.opcodes[self.opcode as usize].add(gas, time);
+        if let Some(frame) = self.stack.last_mut() {
+            frame.self_gas += gas;
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let input = inputs.input.bytes(context);
+        let id = FrameId {
+            address: inputs.bytecode_address,
+            selector: input.get(..4).map(|selector| selector.try_into().unwrap()),
+            is_create: false,
+        };
+        let is_precompile = context
+            .journal()
+            .precompile_addresses()
+            .contains(&inputs.bytecode_address);
+        // The stipend is given on top of the gas charged to the caller.
+        let stipend = if matches!(inputs.scheme, CallScheme::Call | CallScheme::CallCode)
+            && inputs.transfers_value()
+        {
+            CALL_STIPEND
+        } else {
+            0
+        };
+        self.start(id, is_precompile, inputs.gas_limit.saturating_sub(stipend));
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.end(&outcome.result, None);
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let id = FrameId {
+            address: Address::ZERO,
+            selector: None,
+            is_create: true,
+        };
+        self.start(id, false, inputs.gas_limit);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end(&outcome.result, outcome.address);
+    }
+
+    fn eofcreate(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        let id = FrameId {
+            address: inputs.kind.created_address().copied().unwrap_or_default(),
+            selector: None,
+            is_create: true,
+        };
+        self.start(id, false, inputs.gas_limit);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end(&outcome.result, outcome.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{CacheDB, EmptyDB};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, Bytes, TxKind, U256};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const TARGET: Address = address!("0x2000000000000000000000000000000000000002");
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+
+    #[test]
+    fn profile_opcodes_frames_and_precompiles() {
+        // STATICCALL(gas, IDENTITY, 0, 0, 0, 0)
+        let target = [
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            4,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::POP,
+            opcode::STOP,
+        ];
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        db.insert_account_info(
+            TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(Bytes::from(target.to_vec()))),
+        );
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(Profiler::new());
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(TARGET),
+            gas_limit: 100_000,
+            data: Bytes::from_static(&[0x11, 0x22, 0x33, 0x44, 0x55]),
+            ..Default::default()
+        };
+        let result = evm.inspect_with_tx(tx).unwrap();
+        assert!(result.result.is_success());
+        let profiler = &evm.inspector;
+
+        let opcodes: HashMap<u8, ProfileStats> = profiler
+            .opcodes()
+            .map(|(opcode, stats)| (opcode, *stats))
+            .collect();
+        assert_eq!(opcodes[&opcode::PUSH1].count, 5);
+        assert_eq!(opcodes[&opcode::PUSH1].gas, 15);
+        // Warm access of the precompile, the forwarded gas is attributed to the precompile.
+        assert_eq!(opcodes[&opcode::STATICCALL].gas, 100);
+
+        let identity = profiler.precompiles()[&IDENTITY];
+        assert_eq!((identity.count, identity.gas), (1, 15));
+
+        let target = FrameId {
+            address: TARGET,
+            selector: Some([0x11, 0x22, 0x33, 0x44]),
+            is_create: false,
+        };
+        assert_eq!(profiler.frames()[&target].gas, 119 + 15);
+
+        let mut collapsed = Vec::new();
+        profiler
+            .write_collapsed(&mut collapsed, ProfileMetric::Gas)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(collapsed).unwrap(),
+            format!("{target} 119\n{target};{IDENTITY} 15\n")
+        );
+
+        let mut histogram = Vec::new();
+        profiler.write_opcode_histogram(&mut histogram).unwrap();
+        let histogram = String::from_utf8(histogram).unwrap();
+        assert!(histogram.lines().nth(1).unwrap().starts_with("STATICCALL"));
+    }
+}
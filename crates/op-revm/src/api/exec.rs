@@ -1,6 +1,6 @@
 use crate::{
-    evm::OpEvm, handler::OpHandler, transaction::OpTxTr, L1BlockInfo, OpHaltReason, OpSpecId,
-    OpTransactionError,
+    evm::OpEvm, handler::OpHandler, interop::MessageOracleProvider, l1block::OpFeeEstimate,
+    transaction::OpTxTr, L1BlockInfo, OpHaltReason, OpSpecId, OpTransactionError,
 };
 use revm::{
     context::{ContextSetters, JournalOutput},
@@ -9,14 +9,16 @@ use revm::{
         Cfg, ContextTr, Database, JournalTr,
     },
     handler::{
-        instructions::EthInstructions, system_call::SystemCallEvm, EthFrame, EvmTr, Handler,
-        PrecompileProvider, SystemCallTx,
+        instructions::EthInstructions,
+        system_call::{SystemCallCommitEvm, SystemCallEvm},
+        EthFrame, EvmTr, Handler, PrecompileProvider, SystemCallTx,
     },
     inspector::{InspectCommitEvm, InspectEvm, Inspector, InspectorHandler, JournalExt},
     interpreter::{interpreter::EthInterpreter, InterpreterResult},
     primitives::{Address, Bytes},
     DatabaseCommit, ExecuteCommitEvm, ExecuteEvm,
 };
+use std::string::ToString;
 
 // Type alias for Optimism context
 pub trait OpContextTr:
@@ -46,7 +48,7 @@ impl<CTX, INSP, PRECOMPILE> ExecuteEvm
     for OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
 where
     CTX: OpContextTr + ContextSetters,
-    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
 {
     type Output = Result<ResultAndState<OpHaltReason>, OpError<CTX>>;
 
@@ -68,11 +70,53 @@ where
     }
 }
 
+impl<CTX, INSP, PRECOMPILE> OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: OpContextTr + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
+{
+    /// Executes an unsigned transaction without committing and estimates its fees.
+    ///
+    /// The enveloped transaction of `tx` is the EIP-2718 encoding of the transaction without
+    /// signature, see [`L1BlockInfo::estimate_unsigned_tx_fee`]. The operator fee is estimated
+    /// for the gas used by the transaction.
+    ///
+    /// The execution and the estimate are separate: the transaction is executed as given, so the
+    /// L1 fee charged in the returned state is the fee of the unsigned bytes. Only the estimate
+    /// accounts for the signature padding, see
+    /// [`UNSIGNED_TX_SIGNATURE_PADDING`](crate::constants::UNSIGNED_TX_SIGNATURE_PADDING), and
+    /// can be higher. Use the estimate and not the balance changes of the result for the fees of
+    /// the signed transaction.
+    pub fn transact_with_fee_estimate(
+        &mut self,
+        tx: <CTX as ContextTr>::Tx,
+    ) -> Result<(ResultAndState<OpHaltReason>, OpFeeEstimate), OpError<CTX>> {
+        let unsigned_tx = (!tx.is_deposit())
+            .then(|| tx.enveloped_tx().cloned())
+            .flatten();
+        let result = self.transact(tx)?;
+
+        let estimate = match unsigned_tx {
+            Some(unsigned_tx) => {
+                let spec = self.0.ctx.cfg().spec();
+                let gas_used = result.result.gas_used();
+                self.0
+                    .ctx
+                    .chain_ref()
+                    .estimate_unsigned_tx_fee(&unsigned_tx, gas_used, spec)
+                    .map_err(|error| EVMError::Custom(error.to_string()))?
+            }
+            None => OpFeeEstimate::default(),
+        };
+        Ok((result, estimate))
+    }
+}
+
 impl<CTX, INSP, PRECOMPILE> ExecuteCommitEvm
     for OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
 where
     CTX: OpContextTr<Db: DatabaseCommit> + ContextSetters,
-    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
 {
     type CommitOutput = Result<ExecutionResult<OpHaltReason>, OpError<CTX>>;
 
@@ -89,7 +133,7 @@ impl<CTX, INSP, PRECOMPILE> InspectEvm
 where
     CTX: OpContextTr<Journal: JournalExt> + ContextSetters,
     INSP: Inspector<CTX, EthInterpreter>,
-    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
 {
     type Inspector = INSP;
 
@@ -108,7 +152,7 @@ impl<CTX, INSP, PRECOMPILE> InspectCommitEvm
 where
     CTX: OpContextTr<Journal: JournalExt, Db: DatabaseCommit> + ContextSetters,
     INSP: Inspector<CTX, EthInterpreter>,
-    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
 {
     fn inspect_replay_commit(&mut self) -> Self::CommitOutput {
         self.inspect_replay().map(|r| {
@@ -122,7 +166,7 @@ impl<CTX, INSP, PRECOMPILE> SystemCallEvm
     for OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
 where
     CTX: OpContextTr<Tx: SystemCallTx> + ContextSetters,
-    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
 {
     fn transact_system_call(
         &mut self,
@@ -134,3 +178,73 @@ where
         h.run_system_call(self)
     }
 }
+
+impl<CTX, INSP, PRECOMPILE> SystemCallCommitEvm
+    for OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: OpContextTr<Tx: SystemCallTx, Db: DatabaseCommit> + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult> + MessageOracleProvider,
+{
+    fn transact_system_call_commit(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Self::CommitOutput {
+        self.transact_system_call(system_contract_address, data)
+            .map(|r| {
+                self.ctx().db().commit(r.state);
+                r.result
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultOp, OpBuilder, OpTransaction};
+    use revm::{
+        context::{Context, TxEnv},
+        database::InMemoryDB,
+        primitives::{address, bytes, TxKind, U256},
+        state::AccountInfo,
+    };
+
+    #[test]
+    fn transact_with_fee_estimate() {
+        let caller = address!("0x1000000000000000000000000000000000000001");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        let mut evm = Context::op()
+            .with_db(db)
+            .with_chain(L1BlockInfo {
+                l1_base_fee: U256::from(1_000),
+                l1_base_fee_scalar: U256::from(1_000),
+                l1_blob_base_fee: Some(U256::from(1_000)),
+                l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+                operator_fee_scalar: Some(U256::from(2000)),
+                operator_fee_constant: Some(U256::from(5)),
+                ..Default::default()
+            })
+            .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::ISTHMUS)
+            .build_op();
+
+        let tx = OpTransaction {
+            base: TxEnv {
+                caller,
+                kind: TxKind::Call(Address::ZERO),
+                gas_limit: 100_000,
+                ..Default::default()
+            },
+            enveloped_tx: Some(bytes!("FACADE")),
+            ..Default::default()
+        };
+
+        let (result, estimate) = evm.transact_with_fee_estimate(tx).unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(result.result.gas_used(), 21_000);
+        assert_eq!(estimate.fastlz_size, 72);
+        assert_eq!(estimate.l1_fee, U256::from(1700));
+        // operatorFee = 21000 * 2000 / 1e6 + 5
+        assert_eq!(estimate.operator_fee, U256::from(47));
+    }
+}
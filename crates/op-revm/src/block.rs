@@ -0,0 +1,657 @@
+//! Block level execution of OP Stack blocks, see [`OpBlockExecutor`].
+use crate::{
+    api::exec::OpContextTr,
+    constants::{BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT},
+    transaction::OpTxTr,
+    L1BlockInfo, OpHaltReason, OpSpecId,
+};
+use core::fmt;
+use revm::{
+    context_interface::{result::ExecutionResult, Block, Cfg, ContextTr, Database},
+    database::BundleState,
+    handler::{
+        block::{BlockExecutorDb, BEACON_ROOTS_ADDRESS, HISTORY_STORAGE_ADDRESS},
+        system_call::SystemCallCommitEvm,
+        BlockExecutionError, BlockInput, BlockStateDb, EvmTr, ExecuteCommitEvm, Receipt,
+    },
+    primitives::{address, Address, Bytes, HashMap, TxKind, B256, U256},
+    state::{Account, Bytecode},
+    DatabaseCommit,
+};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Caller of the L1 attributes deposit transaction that starts every block.
+pub const L1_ATTRIBUTES_DEPOSITOR: Address = address!("0xDeaDDEaDDeAdDeAdDEAdDEaddeAddEAdDEAd0001");
+
+/// Address of the create2deployer contract whose code is set by the Canyon upgrade.
+pub const CREATE2_DEPLOYER: Address = address!("0x13b0D85CcB8bf860b6b79AF3029fCA081AE9beF2");
+
+/// Version of the deposit receipts from Canyon.
+pub const DEPOSIT_RECEIPT_VERSION: u64 = 1;
+
+/// Receipt of a transaction of an OP Stack block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpReceipt {
+    /// Receipt fields shared with Ethereum.
+    pub receipt: Receipt,
+    /// Nonce of the deposit caller before the deposit, from Regolith.
+    pub deposit_nonce: Option<u64>,
+    /// Version of the deposit receipt, from Canyon.
+    pub deposit_receipt_version: Option<u64>,
+}
+
+/// Increase of the fee vault balances over the block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeVaults {
+    /// Increase of the [`BASE_FEE_RECIPIENT`] balance.
+    pub base_fee: U256,
+    /// Increase of the [`L1_FEE_RECIPIENT`] balance.
+    pub l1_fee: U256,
+    /// Increase of the [`OPERATOR_FEE_RECIPIENT`] balance.
+    pub operator_fee: U256,
+}
+
+/// Output of the OP Stack block execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpBlockExecutionOutput {
+    /// Execution result of each transaction, network upgrade transactions included.
+    pub results: Vec<ExecutionResult<OpHaltReason>>,
+    /// Receipt of each transaction, network upgrade transactions included.
+    pub receipts: Vec<OpReceipt>,
+    /// Gas used by all transactions.
+    pub gas_used: u64,
+    /// DA footprint of the sequenced transactions, from Jovian.
+    ///
+    /// It is bounded by the block gas limit and stored as the blob gas used of the block header.
+    pub da_footprint_gas_used: u64,
+    /// Fees collected by the fee vaults.
+    pub fee_vaults: FeeVaults,
+    /// State changes of the block together with reverts.
+    pub bundle_state: BundleState,
+}
+
+/// OP Stack block execution error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpBlockExecutionError<E> {
+    /// Error shared with the Ethereum block execution.
+    ///
+    /// Transaction indexes count the network upgrade transactions.
+    Block(BlockExecutionError<E>),
+    /// First transaction of the block is not the L1 attributes deposit.
+    MissingL1AttributesDeposit,
+    /// Deposit transaction follows a sequenced transaction.
+    UnorderedDeposit {
+        /// Index of the deposit in the block input.
+        index: usize,
+    },
+    /// DA footprint of the transaction exceeds the DA footprint left in the block, from Jovian.
+    DaFootprintMoreThanAvailable {
+        /// Index of the transaction.
+        index: usize,
+        /// DA footprint of the transaction.
+        transaction_da_footprint: u64,
+        /// DA footprint left in the block.
+        block_available_da_footprint: u64,
+    },
+}
+
+impl<E> From<BlockExecutionError<E>> for OpBlockExecutionError<E> {
+    fn from(error: BlockExecutionError<E>) -> Self {
+        Self::Block(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for OpBlockExecutionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Block(error) => fmt::Display::fmt(error, f),
+            Self::MissingL1AttributesDeposit => {
+                f.write_str("first transaction is not the L1 attributes deposit")
+            }
+            Self::UnorderedDeposit { index } => {
+                write!(f, "deposit {index} follows a sequenced transaction")
+            }
+            Self::DaFootprintMoreThanAvailable {
+                index,
+                transaction_da_footprint,
+                block_available_da_footprint,
+            } => write!(
+                f,
+                "transaction {index} DA footprint {transaction_da_footprint} is more than \
+                 the available block DA footprint {block_available_da_footprint}"
+            ),
+        }
+    }
+}
+
+impl<E: core::error::Error + 'static> core::error::Error for OpBlockExecutionError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Block(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// OP Stack block executor.
+///
+/// [`OpBlockExecutor::execute_block`] runs the block in the order of the derivation pipeline:
+///   * Pre-block - the Canyon create2deployer upgrade, the [EIP-4788] beacon root system call
+///     from Ecotone and the [EIP-2935] block hash system call from Isthmus.
+///   * L1 attributes deposit - updates the L1Block predeploy, the fee parameters of the block
+///     are reloaded from it.
+///   * User deposits.
+///   * Network upgrade transactions of the hardforks activated by the block.
+///   * Sequenced transactions.
+///
+/// The spec of the EVM has to be set for the block timestamp, hardfork activation timestamps
+/// only decide the blocks the upgrades are applied in. The code of the create2deployer and the
+/// upgrade transactions are chain configuration and are not applied unless set.
+///
+/// [EIP-2935]: https://eips.ethereum.org/EIPS/eip-2935
+/// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+#[derive(Clone, Debug)]
+pub struct OpBlockExecutor<TX> {
+    /// Activation timestamps of the hardforks.
+    pub activations: BTreeMap<OpSpecId, u64>,
+    /// Seconds between two blocks.
+    pub block_time: u64,
+    /// Code of the create2deployer set in the Canyon activation block.
+    pub create2_deployer_code: Option<Bytecode>,
+    /// Deposit transactions run after the user deposits of the activation block of a hardfork.
+    pub upgrade_transactions: BTreeMap<OpSpecId, Vec<TX>>,
+}
+
+impl<TX> Default for OpBlockExecutor<TX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TX> OpBlockExecutor<TX> {
+    /// Creates an executor without hardfork activations and a two second block time.
+    pub fn new() -> Self {
+        Self {
+            activations: BTreeMap::new(),
+            block_time: 2,
+            create2_deployer_code: None,
+            upgrade_transactions: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the activation timestamp of the hardfork.
+    pub fn with_activation(mut self, spec: OpSpecId, timestamp: u64) -> Self {
+        self.activations.insert(spec, timestamp);
+        self
+    }
+
+    /// Sets the seconds between two blocks.
+    pub fn with_block_time(mut self, block_time: u64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Sets the code of the create2deployer set by the Canyon upgrade.
+    pub fn with_create2_deployer_code(mut self, code: Bytecode) -> Self {
+        self.create2_deployer_code = Some(code);
+        self
+    }
+
+    /// Sets the network upgrade transactions of the hardfork.
+    pub fn with_upgrade_transactions(mut self, spec: OpSpecId, transactions: Vec<TX>) -> Self {
+        self.upgrade_transactions.insert(spec, transactions);
+        self
+    }
+
+    /// Returns `true` if the block with the timestamp is the first block of the hardfork.
+    ///
+    /// The genesis block, with timestamp zero, is never an activation block, hardforks active at
+    /// genesis have no upgrade transactions.
+    pub fn is_activation_block(&self, spec: OpSpecId, timestamp: u64) -> bool {
+        timestamp != 0
+            && self.activations.get(&spec).is_some_and(|activation| {
+                timestamp >= *activation && timestamp.saturating_sub(self.block_time) < *activation
+            })
+    }
+}
+
+impl<TX: OpTxTr + Clone> OpBlockExecutor<TX> {
+    /// Executes the block and returns the receipts, the fee vault balances and the merged
+    /// [`BundleState`].
+    ///
+    /// Transactions of the input are the transactions of the L2 block, starting with the L1
+    /// attributes deposit. Ommers, withdrawals and the block access list of the input are
+    /// ignored.
+    pub fn execute_block<EVM, ERROR>(
+        &self,
+        evm: &mut EVM,
+        input: BlockInput<EVM::Block, TX>,
+    ) -> Result<OpBlockExecutionOutput, OpBlockExecutionError<ERROR>>
+    where
+        EVM: EvmTr<Context: OpContextTr<Db: BlockStateDb<Bundle = BundleState>>>
+            + SystemCallCommitEvm<
+                Tx = TX,
+                CommitOutput = Result<ExecutionResult<OpHaltReason>, ERROR>,
+            >,
+        ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+    {
+        let BlockInput {
+            block,
+            transactions,
+            parent_hash,
+            parent_beacon_block_root,
+            ..
+        } = input;
+        let is_l1_attributes = transactions.first().is_some_and(|tx| {
+            tx.is_deposit()
+                && tx.caller() == L1_ATTRIBUTES_DEPOSITOR
+                && tx.kind() == TxKind::Call(L1_BLOCK_CONTRACT)
+        });
+        if !is_l1_attributes {
+            return Err(OpBlockExecutionError::MissingL1AttributesDeposit);
+        }
+        let deposits = transactions.iter().take_while(|tx| tx.is_deposit()).count();
+        if let Some(index) = transactions[deposits..]
+            .iter()
+            .position(|tx| tx.is_deposit())
+        {
+            return Err(OpBlockExecutionError::UnorderedDeposit {
+                index: deposits + index,
+            });
+        }
+
+        evm.set_block(block);
+        self.apply_pre_block(evm, parent_hash, parent_beacon_block_root)?;
+        let vaults_before = fee_vault_balances(evm)?;
+
+        // Upgrade transactions follow the user deposits.
+        let timestamp = evm.ctx_ref().block().timestamp();
+        let mut transactions = transactions;
+        let sequenced = transactions.split_off(deposits);
+        for (spec, upgrade) in &self.upgrade_transactions {
+            if self.is_activation_block(*spec, timestamp) {
+                transactions.extend(upgrade.iter().cloned());
+            }
+        }
+        transactions.extend(sequenced);
+
+        let mut output = OpBlockExecutionOutput::default();
+        for (index, tx) in transactions.into_iter().enumerate() {
+            self.execute_transaction(evm, index, tx, &mut output)?;
+        }
+
+        let vaults_after = fee_vault_balances(evm)?;
+        output.fee_vaults = FeeVaults {
+            base_fee: vaults_after.base_fee.saturating_sub(vaults_before.base_fee),
+            l1_fee: vaults_after.l1_fee.saturating_sub(vaults_before.l1_fee),
+            operator_fee: vaults_after
+                .operator_fee
+                .saturating_sub(vaults_before.operator_fee),
+        };
+
+        let db = evm.ctx().db();
+        db.merge_transitions();
+        output.bundle_state = db.take_bundle();
+        Ok(output)
+    }
+
+    /// Applies the Canyon create2deployer upgrade and runs the system calls of the block.
+    fn apply_pre_block<EVM, ERROR>(
+        &self,
+        evm: &mut EVM,
+        parent_hash: Option<B256>,
+        parent_beacon_block_root: Option<B256>,
+    ) -> Result<(), OpBlockExecutionError<ERROR>>
+    where
+        EVM: EvmTr<Context: OpContextTr<Db: BlockStateDb<Bundle = BundleState>>>
+            + SystemCallCommitEvm<CommitOutput = Result<ExecutionResult<OpHaltReason>, ERROR>>,
+        ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+    {
+        let spec = evm.ctx_ref().cfg().spec();
+        let block = evm.ctx_ref().block();
+        let (number, timestamp) = (block.number(), block.timestamp());
+
+        if let Some(code) = &self.create2_deployer_code {
+            if self.is_activation_block(OpSpecId::CANYON, timestamp) {
+                let db = evm.ctx().db();
+                let mut info = db
+                    .basic(CREATE2_DEPLOYER)
+                    .map_err(|e| BlockExecutionError::Other(e.into()))?
+                    .unwrap_or_default();
+                info.code_hash = code.hash_slow();
+                info.code = Some(code.clone());
+                let mut account = Account::from(info);
+                account.mark_touch();
+                db.commit(HashMap::from_iter([(CREATE2_DEPLOYER, account)]));
+            }
+        }
+
+        if number == 0 {
+            return Ok(());
+        }
+        if let Some(parent_hash) = parent_hash {
+            evm.ctx().db().insert_block_hash(number - 1, parent_hash);
+        }
+        let system_calls = [
+            (
+                OpSpecId::ECOTONE,
+                BEACON_ROOTS_ADDRESS,
+                parent_beacon_block_root,
+            ),
+            (OpSpecId::ISTHMUS, HISTORY_STORAGE_ADDRESS, parent_hash),
+        ];
+        for (fork, address, data) in system_calls {
+            let Some(data) = data.filter(|_| spec.is_enabled_in(fork)) else {
+                continue;
+            };
+            let has_code = evm
+                .ctx()
+                .db()
+                .basic(address)
+                .map_err(|e| BlockExecutionError::Other(e.into()))?
+                .is_some_and(|info| !info.is_empty_code_hash());
+            if !has_code {
+                continue;
+            }
+            let result = evm
+                .transact_system_call_commit(address, Bytes::copy_from_slice(data.as_slice()))
+                .map_err(|error| BlockExecutionError::SystemCall { address, error })?;
+            if !result.is_success() {
+                return Err(BlockExecutionError::SystemCallFailed { address }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes and commits the transaction and appends its result and receipt.
+    fn execute_transaction<EVM, ERROR>(
+        &self,
+        evm: &mut EVM,
+        index: usize,
+        tx: TX,
+        output: &mut OpBlockExecutionOutput,
+    ) -> Result<(), OpBlockExecutionError<ERROR>>
+    where
+        EVM: EvmTr<Context: OpContextTr<Db: BlockStateDb<Bundle = BundleState>>>
+            + ExecuteCommitEvm<Tx = TX, CommitOutput = Result<ExecutionResult<OpHaltReason>, ERROR>>,
+        ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+    {
+        let spec = evm.ctx_ref().cfg().spec();
+        let block_available_gas = evm
+            .ctx_ref()
+            .block()
+            .gas_limit()
+            .saturating_sub(output.gas_used);
+        let transaction_gas_limit = tx.gas_limit();
+        if transaction_gas_limit > block_available_gas {
+            return Err(
+                BlockExecutionError::TransactionGasLimitMoreThanAvailableBlockGas {
+                    index,
+                    transaction_gas_limit,
+                    block_available_gas,
+                }
+                .into(),
+            );
+        }
+
+        let is_deposit = tx.is_deposit();
+        let is_l1_attributes = index == 0;
+        let transaction_da_footprint = match tx.enveloped_tx() {
+            Some(enveloped_tx) if !is_deposit => evm
+                .ctx_ref()
+                .chain_ref()
+                .tx_da_footprint(enveloped_tx, spec),
+            _ => 0,
+        };
+        let block_available_da_footprint = evm
+            .ctx_ref()
+            .block()
+            .gas_limit()
+            .saturating_sub(output.da_footprint_gas_used);
+        if transaction_da_footprint > block_available_da_footprint {
+            return Err(OpBlockExecutionError::DaFootprintMoreThanAvailable {
+                index,
+                transaction_da_footprint,
+                block_available_da_footprint,
+            });
+        }
+
+        let tx_type = tx.tx_type();
+        let deposit_nonce = if is_deposit && spec.is_enabled_in(OpSpecId::REGOLITH) {
+            let caller = evm
+                .ctx()
+                .db()
+                .basic(tx.caller())
+                .map_err(|e| BlockExecutionError::Other(e.into()))?;
+            Some(caller.map(|info| info.nonce).unwrap_or_default())
+        } else {
+            None
+        };
+
+        let result = evm
+            .transact_commit(tx)
+            .map_err(|error| BlockExecutionError::Transaction { index, error })?;
+
+        // The L1 attributes deposit updated the fee parameters of the block.
+        if is_l1_attributes {
+            let number = evm.ctx_ref().block().number();
+            let ctx = evm.ctx();
+            *ctx.chain() = L1BlockInfo::try_fetch(ctx.db(), number, spec)
+                .map_err(|e| BlockExecutionError::Other(e.into()))?;
+        }
+
+        output.gas_used += result.gas_used();
+        output.da_footprint_gas_used += transaction_da_footprint;
+        output.receipts.push(OpReceipt {
+            receipt: Receipt {
+                tx_type,
+                success: result.is_success(),
+                cumulative_gas_used: output.gas_used,
+                logs: result.logs().to_vec(),
+            },
+            deposit_nonce,
+            deposit_receipt_version: (is_deposit && spec.is_enabled_in(OpSpecId::CANYON))
+                .then_some(DEPOSIT_RECEIPT_VERSION),
+        });
+        output.results.push(result);
+        Ok(())
+    }
+}
+
+/// Returns the balances of the fee vaults.
+fn fee_vault_balances<EVM, ERROR>(evm: &mut EVM) -> Result<FeeVaults, OpBlockExecutionError<ERROR>>
+where
+    EVM: EvmTr,
+    ERROR: From<<BlockExecutorDb<EVM> as Database>::Error>,
+{
+    let db = evm.ctx().db();
+    let mut balance = |address: Address| {
+        db.basic(address)
+            .map(|info| info.map(|info| info.balance).unwrap_or_default())
+            .map_err(|e| OpBlockExecutionError::Block(BlockExecutionError::Other(e.into())))
+    };
+    Ok(FeeVaults {
+        base_fee: balance(BASE_FEE_RECIPIENT)?,
+        l1_fee: balance(L1_FEE_RECIPIENT)?,
+        operator_fee: balance(OPERATOR_FEE_RECIPIENT)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::{DA_FOOTPRINT_GAS_SCALAR_OFFSET, OPERATOR_FEE_SCALARS_SLOT},
+        transaction::deposit::DEPOSIT_TRANSACTION_TYPE,
+        DefaultOp, OpBuilder, OpTransaction,
+    };
+    use revm::{
+        context::{BlockEnv, Context, TxEnv},
+        database::{InMemoryDB, State},
+        state::AccountInfo,
+    };
+    use std::vec;
+
+    const CALLER: Address = address!("0x1000000000000000000000000000000000000001");
+    const RECIPIENT: Address = address!("0x2000000000000000000000000000000000000002");
+    const UPGRADER: Address = address!("0x3000000000000000000000000000000000000003");
+
+    fn deposit(caller: Address, to: Address, mint: u128) -> OpTransaction<TxEnv> {
+        let mut tx = OpTransaction::new(TxEnv {
+            tx_type: DEPOSIT_TRANSACTION_TYPE,
+            caller,
+            kind: TxKind::Call(to),
+            gas_limit: 100_000,
+            ..Default::default()
+        });
+        tx.deposit.mint = Some(mint);
+        tx
+    }
+
+    #[test]
+    fn execute_block() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+        let mut state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::op()
+            .with_db(&mut state)
+            .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::ISTHMUS)
+            .build_op();
+
+        let create2_deployer_code = Bytecode::new_legacy(Bytes::from_static(&[0x00]));
+        let executor = OpBlockExecutor::new()
+            .with_activation(OpSpecId::CANYON, 10)
+            .with_activation(OpSpecId::ISTHMUS, 0)
+            .with_create2_deployer_code(create2_deployer_code.clone())
+            .with_upgrade_transactions(OpSpecId::CANYON, vec![deposit(UPGRADER, RECIPIENT, 0)])
+            .with_upgrade_transactions(OpSpecId::ISTHMUS, vec![deposit(UPGRADER, CALLER, 0)]);
+        assert!(executor.is_activation_block(OpSpecId::CANYON, 10));
+        assert!(!executor.is_activation_block(OpSpecId::ISTHMUS, 0));
+
+        let sequenced = OpTransaction {
+            base: TxEnv {
+                caller: CALLER,
+                kind: TxKind::Call(RECIPIENT),
+                gas_limit: 100_000,
+                gas_price: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let block = BlockEnv {
+            number: 1,
+            timestamp: 10,
+            basefee: 10,
+            ..Default::default()
+        };
+        let transactions = vec![
+            deposit(L1_ATTRIBUTES_DEPOSITOR, L1_BLOCK_CONTRACT, 0),
+            deposit(RECIPIENT, RECIPIENT, 100),
+            sequenced.clone(),
+        ];
+        let output = executor
+            .execute_block(&mut evm, BlockInput::new(block.clone(), transactions))
+            .unwrap();
+
+        // Only the Canyon upgrade is inserted, after the user deposit.
+        assert_eq!(output.receipts.len(), 4);
+        assert_eq!(output.gas_used, 4 * 21_000);
+        let deposit_nonces: Vec<_> = output.receipts.iter().map(|r| r.deposit_nonce).collect();
+        assert_eq!(deposit_nonces, [Some(0), Some(0), Some(0), None]);
+        assert_eq!(
+            output.receipts[2].deposit_receipt_version,
+            Some(DEPOSIT_RECEIPT_VERSION)
+        );
+        assert_eq!(output.receipts[3].receipt.cumulative_gas_used, 84_000);
+        assert_eq!(output.fee_vaults.base_fee, U256::from(10 * 21_000));
+
+        let deployer = &output.bundle_state.state[&CREATE2_DEPLOYER];
+        assert_eq!(
+            deployer.info.as_ref().unwrap().code_hash,
+            create2_deployer_code.hash_slow()
+        );
+
+        let error = executor
+            .execute_block(&mut evm, BlockInput::new(block, vec![sequenced]))
+            .unwrap_err();
+        assert_eq!(error, OpBlockExecutionError::MissingL1AttributesDeposit);
+    }
+
+    #[test]
+    fn execute_block_da_footprint() {
+        let execute = |da_footprint_gas_scalar: u16| {
+            let mut operator_fee_scalars = [0u8; 32];
+            operator_fee_scalars
+                [DA_FOOTPRINT_GAS_SCALAR_OFFSET..DA_FOOTPRINT_GAS_SCALAR_OFFSET + 2]
+                .copy_from_slice(&da_footprint_gas_scalar.to_be_bytes());
+            let mut db = InMemoryDB::default();
+            db.insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10u64.pow(18))));
+            // A non-empty L1Block account, otherwise touching it with the L1 attributes deposit
+            // clears it together with the fee scalars.
+            db.insert_account_info(
+                L1_BLOCK_CONTRACT,
+                AccountInfo {
+                    nonce: 1,
+                    ..Default::default()
+                },
+            );
+            db.insert_account_storage(
+                L1_BLOCK_CONTRACT,
+                OPERATOR_FEE_SCALARS_SLOT,
+                U256::from_be_bytes(operator_fee_scalars),
+            )
+            .unwrap();
+            let mut state = State::builder()
+                .with_database(db)
+                .with_bundle_update()
+                .build();
+            let mut evm = Context::op()
+                .with_db(&mut state)
+                .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::JOVIAN)
+                .build_op();
+
+            // The estimated size of the enveloped transaction is the 100 bytes minimum.
+            let sequenced = OpTransaction {
+                base: TxEnv {
+                    caller: CALLER,
+                    kind: TxKind::Call(RECIPIENT),
+                    gas_limit: 100_000,
+                    ..Default::default()
+                },
+                enveloped_tx: Some(Bytes::from_static(&[0xFA, 0xCA, 0xDE])),
+                ..Default::default()
+            };
+            let block = BlockEnv {
+                number: 1,
+                timestamp: 10,
+                gas_limit: 1_000_000,
+                ..Default::default()
+            };
+            let transactions = vec![
+                deposit(L1_ATTRIBUTES_DEPOSITOR, L1_BLOCK_CONTRACT, 0),
+                sequenced,
+            ];
+            OpBlockExecutor::new().execute_block(&mut evm, BlockInput::new(block, transactions))
+        };
+
+        // daFootprint = 100 * 400, deposits do not count.
+        let output = execute(400).unwrap();
+        assert_eq!(output.da_footprint_gas_used, 40_000);
+
+        // daFootprint = 100 * 20_000 exceeds the block gas limit.
+        let error = execute(20_000).unwrap_err();
+        assert_eq!(
+            error,
+            OpBlockExecutionError::DaFootprintMoreThanAvailable {
+                index: 1,
+                transaction_da_footprint: 2_000_000,
+                block_available_da_footprint: 1_000_000,
+            }
+        );
+    }
+}
@@ -3,6 +3,9 @@ use revm::primitives::{address, Address, U256};
 pub const ZERO_BYTE_COST: u64 = 4;
 pub const NON_ZERO_BYTE_COST: u64 = 16;
 
+/// Number of bytes the `GasPriceOracle` adds to an unsigned transaction to account for the signature.
+pub const UNSIGNED_TX_SIGNATURE_PADDING: u64 = 68;
+
 /// The two 4-byte Ecotone fee scalar values are packed into the same storage slot as the 8-byte sequence number.
 /// Byte offset within the storage slot of the 4-byte baseFeeScalar attribute.
 pub const BASE_FEE_SCALAR_OFFSET: usize = 16;
@@ -17,11 +20,23 @@ pub const OPERATOR_FEE_SCALAR_OFFSET: usize = 20;
 /// the storage slot of the 8-byte operatorFeeConstant attribute.
 pub const OPERATOR_FEE_CONSTANT_OFFSET: usize = 24;
 
+/// The Jovian DA footprint gas scalar is packed into the operator fee slot. Byte offset within
+/// the storage slot of the 2-byte daFootprintGasScalar attribute.
+pub const DA_FOOTPRINT_GAS_SCALAR_OFFSET: usize = 18;
+
+/// DA footprint gas scalar used when the daFootprintGasScalar attribute is zero, see the
+/// [Jovian specs](https://specs.optimism.io/protocol/jovian/exec-engine.html#da-footprint-block-limit).
+pub const DEFAULT_DA_FOOTPRINT_GAS_SCALAR: u16 = 400;
+
 /// The fixed point decimal scaling factor associated with the operator fee scalar.
 ///
 /// Allows users to use 6 decimal points of precision when specifying the operator_fee_scalar.
 pub const OPERATOR_FEE_SCALAR_DECIMAL: u64 = 1_000_000;
 
+/// The multiplier applied to the operator fee scalar as of Jovian, replacing the
+/// [OPERATOR_FEE_SCALAR_DECIMAL] division of Isthmus.
+pub const OPERATOR_FEE_JOVIAN_MULTIPLIER: u64 = 100;
+
 pub const L1_BASE_FEE_SLOT: U256 = U256::from_limbs([1u64, 0, 0, 0]);
 pub const L1_OVERHEAD_SLOT: U256 = U256::from_limbs([5u64, 0, 0, 0]);
 pub const L1_SCALAR_SLOT: U256 = U256::from_limbs([6u64, 0, 0, 0]);
@@ -35,6 +50,8 @@ pub const ECOTONE_L1_FEE_SCALARS_SLOT: U256 = U256::from_limbs([3u64, 0, 0, 0]);
 
 /// This storage slot stores the 32-bit operatorFeeScalar and operatorFeeConstant attributes at
 /// offsets [OPERATOR_FEE_SCALAR_OFFSET] and [OPERATOR_FEE_CONSTANT_OFFSET] respectively.
+///
+/// As of Jovian, it also stores the 16-bit daFootprintGasScalar at [DA_FOOTPRINT_GAS_SCALAR_OFFSET].
 pub const OPERATOR_FEE_SCALARS_SLOT: U256 = U256::from_limbs([8u64, 0, 0, 0]);
 
 /// An empty 64-bit set of scalar values.
@@ -51,3 +68,9 @@ pub const BASE_FEE_RECIPIENT: Address = address!("0x4200000000000000000000000000
 
 /// The address of the L1Block contract.
 pub const L1_BLOCK_CONTRACT: Address = address!("0x4200000000000000000000000000000000000015");
+
+/// The address of the CrossL2Inbox predeploy that validates executing messages of interop.
+pub const CROSS_L2_INBOX: Address = address!("0x4200000000000000000000000000000000000022");
+
+/// Time in seconds after which an initiating message can no longer be executed.
+pub const MESSAGE_EXPIRY_WINDOW: u64 = 604_800;
@@ -3,7 +3,7 @@
 ///
 /// The u32s match op-geth's Go port:
 /// <https://github.com/ethereum-optimism/op-geth/blob/647c346e2bef36219cc7b47d76b1cb87e7ca29e4/core/types/rollup_cost.go#L411>
-pub(crate) fn flz_compress_len(input: &[u8]) -> u32 {
+pub fn flz_compress_len(input: &[u8]) -> u32 {
     let mut idx: u32 = 2;
 
     let idx_limit: u32 = if input.len() < 13 {
@@ -2,6 +2,7 @@
 use crate::{
     api::exec::OpContextTr,
     constants::{BASE_FEE_RECIPIENT, L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT},
+    interop::{validate_executing_messages, MessageOracleProvider},
     transaction::{deposit::DEPOSIT_TRANSACTION_TYPE, OpTransactionError, OpTxTr},
     L1BlockInfo, OpHaltReason, OpSpecId,
 };
@@ -21,7 +22,7 @@ use revm::{
     state::Account,
     Database,
 };
-use std::boxed::Box;
+use std::{boxed::Box, string::ToString};
 
 pub struct OpHandler<EVM, ERROR, FRAME> {
     pub mainnet: MainnetHandler<EVM, ERROR, FRAME>,
@@ -55,7 +56,7 @@ impl<DB, TX> IsTxError for EVMError<DB, TX> {
 
 impl<EVM, ERROR, FRAME> Handler for OpHandler<EVM, ERROR, FRAME>
 where
-    EVM: EvmTr<Context: OpContextTr>,
+    EVM: EvmTr<Context: OpContextTr, Precompiles: MessageOracleProvider>,
     ERROR: EvmTrError<EVM> + From<OpTransactionError> + FromStringError + IsTxError,
     // TODO `FrameResult` should be a generic trait.
     // TODO `FrameInit` should be a generic.
@@ -80,7 +81,19 @@ where
             }
             return Ok(());
         }
-        self.mainnet.validate_env(evm)
+        self.mainnet.validate_env(evm)?;
+
+        // Executing messages of interop are checked against the message oracle.
+        if evm.ctx().cfg().spec().is_enabled_in(OpSpecId::INTEROP) {
+            let (ctx, precompiles) = evm.ctx_precompiles();
+            validate_executing_messages(
+                ctx.tx(),
+                ctx.block().timestamp(),
+                precompiles.message_oracle(),
+            )
+            .map_err(OpTransactionError::from)?;
+        }
+        Ok(())
     }
 
     fn validate_against_state_and_deduct_caller(
@@ -122,7 +135,10 @@ where
             // compute operator fee
             if spec.is_enabled_in(OpSpecId::ISTHMUS) {
                 let gas_limit = U256::from(ctx.tx().gas_limit());
-                let operator_fee_charge = ctx.chain().operator_fee_charge(&enveloped_tx, gas_limit);
+                let operator_fee_charge = ctx
+                    .chain()
+                    .operator_fee_charge(&enveloped_tx, gas_limit, spec)
+                    .map_err(|error| ERROR::from_string(error.to_string()))?;
                 additional_cost = additional_cost.saturating_add(operator_fee_charge);
             }
         }
@@ -273,7 +289,10 @@ where
         if context.tx().tx_type() != DEPOSIT_TRANSACTION_TYPE {
             let caller = context.tx().caller();
             let spec = context.cfg().spec();
-            let operator_fee_refund = context.chain().operator_fee_refund(exec_result.gas(), spec);
+            let operator_fee_refund = context
+                .chain()
+                .operator_fee_refund(exec_result.gas(), spec)
+                .map_err(|error| ERROR::from_string(error.to_string()))?;
 
             let caller_account = context.journal().load_account(caller)?;
 
@@ -341,10 +360,13 @@ where
             let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, spec);
             let mut operator_fee_cost = U256::ZERO;
             if spec.is_enabled_in(OpSpecId::ISTHMUS) {
-                operator_fee_cost = l1_block_info.operator_fee_charge(
-                    enveloped_tx,
-                    U256::from(exec_result.gas().spent() - exec_result.gas().refunded() as u64),
-                );
+                operator_fee_cost = l1_block_info
+                    .operator_fee_charge(
+                        enveloped_tx,
+                        U256::from(exec_result.gas().spent() - exec_result.gas().refunded() as u64),
+                        spec,
+                    )
+                    .map_err(|error| ERROR::from_string(error.to_string()))?;
             }
             // Send the L1 cost of the transaction to the L1 Fee Vault.
             let mut l1_fee_vault_account = ctx.journal().load_account(L1_FEE_RECIPIENT)?;
@@ -462,6 +484,7 @@ impl<EVM, ERROR, FRAME> InspectorHandler for OpHandler<EVM, ERROR, FRAME>
 where
     EVM: InspectorEvmTr<
         Context: OpContextTr,
+        Precompiles: MessageOracleProvider,
         Inspector: Inspector<<<Self as Handler>::Evm as EvmTr>::Context, EthInterpreter>,
     >,
     ERROR: EvmTrError<EVM> + From<OpTransactionError> + FromStringError + IsTxError,
@@ -767,6 +790,7 @@ mod tests {
                 l1_fee_overhead: None,
                 operator_fee_scalar: Some(U256::from(OPERATOR_FEE_SCALAR)),
                 operator_fee_constant: Some(U256::from(OPERATOR_FEE_CONST)),
+                da_footprint_gas_scalar: None,
                 tx_l1_cost: Some(U256::ZERO),
             }
         );
@@ -1029,7 +1053,8 @@ mod tests {
         let op_fee_refund = evm
             .ctx()
             .chain()
-            .operator_fee_refund(&gas, OpSpecId::ISTHMUS);
+            .operator_fee_refund(&gas, OpSpecId::ISTHMUS)
+            .unwrap();
         assert!(op_fee_refund > U256::ZERO);
 
         if !is_deposit {
@@ -0,0 +1,477 @@
+//! Superchain interop, see the [specs](https://specs.optimism.io/interop/overview.html).
+//!
+//! A transaction executes a message initiated on another chain by calling `validateMessage` of
+//! the [`CROSS_L2_INBOX`] predeploy. The checksum of every executed message has to be declared
+//! in the access list of the transaction, under the inbox address, and the declared messages are
+//! checked against a [`MessageOracle`] before the transaction is executed. Transactions with an
+//! invalid executing message are invalid, deposit transactions can not execute messages.
+//! Deposits are recognized by their transaction type, the deposit context of the `L1Block`
+//! predeploy, `isDeposit` and the `depositsComplete` system transaction, is not implemented.
+//!
+//! The inbox is run natively by [`OpPrecompiles`](crate::precompiles::OpPrecompiles) starting
+//! with [`OpSpecId::INTEROP`](crate::OpSpecId::INTEROP).
+use crate::{
+    constants::{CROSS_L2_INBOX, MESSAGE_EXPIRY_WINDOW},
+    transaction::deposit::DEPOSIT_TRANSACTION_TYPE,
+};
+use core::fmt;
+use revm::{
+    context_interface::{transaction::AccessListItemTr, ContextTr, JournalTr, Transaction},
+    interpreter::{Gas, InstructionResult, InterpreterResult},
+    primitives::{keccak256, Address, Bytes, HashMap, Log, B256, U256},
+};
+use std::{vec, vec::Vec};
+
+/// Signature of the `validateMessage` function of the inbox.
+pub const VALIDATE_MESSAGE_SIGNATURE: &str =
+    "validateMessage((address,uint256,uint256,uint256,uint256),bytes32)";
+
+/// Signature of the event emitted for every executed message.
+pub const EXECUTING_MESSAGE_SIGNATURE: &str =
+    "ExecutingMessage(bytes32,(address,uint256,uint256,uint256,uint256))";
+
+/// Gas of `validateMessage`, a warm storage read for the access list check and the `LOG2` of
+/// the [`EXECUTING_MESSAGE_SIGNATURE`] event with its 160 bytes of data.
+pub const VALIDATE_MESSAGE_GAS: u64 = 100 + 375 + 2 * 375 + 160 * 8;
+
+/// Length of the ABI encoded message identifier.
+const IDENTIFIER_LEN: usize = 5 * 32;
+
+/// Identifier of an initiating message, the log emitted on the source chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageIdentifier {
+    /// Address that emitted the log.
+    pub origin: Address,
+    /// Number of the block of the log.
+    pub block_number: u64,
+    /// Index of the log in the block.
+    pub log_index: u32,
+    /// Timestamp of the block of the log.
+    pub timestamp: u64,
+    /// Chain id of the source chain.
+    pub chain_id: U256,
+}
+
+impl MessageIdentifier {
+    /// Returns the access list checksum of the message with the hash of the log.
+    ///
+    /// The checksum is `keccak256(keccak256(logHash ++ idPacked) ++ chainId)` with the first byte
+    /// set to `0x03`, where `logHash` is `keccak256(origin ++ msgHash)`.
+    pub fn checksum(&self, msg_hash: B256) -> B256 {
+        let log_hash = keccak256([self.origin.as_slice(), msg_hash.as_slice()].concat());
+        let mut id_packed = [0u8; 32];
+        id_packed[12..20].copy_from_slice(&self.block_number.to_be_bytes());
+        id_packed[20..28].copy_from_slice(&self.timestamp.to_be_bytes());
+        id_packed[28..].copy_from_slice(&self.log_index.to_be_bytes());
+        let id_log_hash = keccak256([log_hash.as_slice(), &id_packed].concat());
+        let mut checksum =
+            keccak256([id_log_hash.as_slice(), &self.chain_id.to_be_bytes::<32>()].concat());
+        checksum[0] = 0x03;
+        checksum
+    }
+
+    /// Decodes the ABI encoded identifier, `None` if a field does not fit its type.
+    fn abi_decode(input: &[u8]) -> Option<Self> {
+        let word = |index: usize| U256::from_be_slice(&input[index * 32..(index + 1) * 32]);
+        if input[..12].iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        Some(Self {
+            origin: Address::from_slice(&input[12..32]),
+            block_number: word(1).try_into().ok()?,
+            log_index: word(2).try_into().ok()?,
+            timestamp: word(3).try_into().ok()?,
+            chain_id: word(4),
+        })
+    }
+}
+
+/// Executing message declared in the access list of a transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutingMessage {
+    /// Chain id of the source chain.
+    pub chain_id: U256,
+    /// Number of the block of the initiating message.
+    pub block_number: u64,
+    /// Timestamp of the block of the initiating message.
+    pub timestamp: u64,
+    /// Index of the log of the initiating message in the block.
+    pub log_index: u32,
+    /// Checksum of the identifier and hash of the message.
+    pub checksum: B256,
+}
+
+impl ExecutingMessage {
+    /// Creates the executing message of the initiating message with the hash.
+    pub fn new(identifier: &MessageIdentifier, msg_hash: B256) -> Self {
+        Self {
+            chain_id: identifier.chain_id,
+            block_number: identifier.block_number,
+            timestamp: identifier.timestamp,
+            log_index: identifier.log_index,
+            checksum: identifier.checksum(msg_hash),
+        }
+    }
+
+    /// Parses the messages of the [`CROSS_L2_INBOX`] storage keys of an access list.
+    ///
+    /// Every message is a lookup entry, an optional chain id extension entry when the chain id
+    /// does not fit 64 bits, and the checksum entry.
+    pub fn parse_access_list<'a>(
+        storage_keys: impl IntoIterator<Item = &'a B256>,
+    ) -> Result<Vec<Self>, InteropError> {
+        let mut keys = storage_keys.into_iter();
+        let mut messages = Vec::new();
+        while let Some(lookup) = keys.next() {
+            if lookup[0] != 0x01 || lookup[1..4] != [0; 3] {
+                return Err(InteropError::InvalidAccessList);
+            }
+            let mut chain_id = [0u8; 32];
+            chain_id[24..].copy_from_slice(&lookup[4..12]);
+
+            let mut next = keys.next().ok_or(InteropError::InvalidAccessList)?;
+            if next[0] == 0x02 {
+                if next[1..8] != [0; 7] {
+                    return Err(InteropError::InvalidAccessList);
+                }
+                chain_id[..24].copy_from_slice(&next[8..]);
+                next = keys.next().ok_or(InteropError::InvalidAccessList)?;
+            }
+            if next[0] != 0x03 {
+                return Err(InteropError::InvalidAccessList);
+            }
+            messages.push(Self {
+                chain_id: U256::from_be_bytes(chain_id),
+                block_number: u64::from_be_bytes(be_bytes(&lookup[12..20])),
+                timestamp: u64::from_be_bytes(be_bytes(&lookup[20..28])),
+                log_index: u32::from_be_bytes(be_bytes(&lookup[28..])),
+                checksum: *next,
+            });
+        }
+        Ok(messages)
+    }
+
+    /// Returns the [`CROSS_L2_INBOX`] storage keys that declare the message in an access list.
+    pub fn access_list_keys(&self) -> Vec<B256> {
+        let chain_id = self.chain_id.to_be_bytes::<32>();
+        let mut lookup = B256::ZERO;
+        lookup[0] = 0x01;
+        lookup[4..12].copy_from_slice(&chain_id[24..]);
+        lookup[12..20].copy_from_slice(&self.block_number.to_be_bytes());
+        lookup[20..28].copy_from_slice(&self.timestamp.to_be_bytes());
+        lookup[28..].copy_from_slice(&self.log_index.to_be_bytes());
+
+        let mut keys = vec![lookup];
+        if chain_id[..24].iter().any(|byte| *byte != 0) {
+            let mut extension = B256::ZERO;
+            extension[0] = 0x02;
+            extension[8..].copy_from_slice(&chain_id[..24]);
+            keys.push(extension);
+        }
+        keys.push(self.checksum);
+        keys
+    }
+}
+
+fn be_bytes<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut array = [0u8; N];
+    array.copy_from_slice(bytes);
+    array
+}
+
+/// Oracle that knows the initiating messages of the other chains, like the op-supervisor.
+pub trait MessageOracle: fmt::Debug + Send + Sync {
+    /// Returns `true` if the initiating message at the lookup of the executing message exists
+    /// and has the checksum of the executing message.
+    fn is_valid(&self, message: &ExecutingMessage) -> bool;
+}
+
+/// Provider of the [`MessageOracle`] used to validate the executing messages of transactions.
+pub trait MessageOracleProvider {
+    /// Returns the oracle, `None` if messages can not be validated.
+    fn message_oracle(&self) -> Option<&dyn MessageOracle>;
+}
+
+/// [`MessageOracle`] with a fixed set of initiating messages, used for testing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InMemoryMessageOracle {
+    messages: HashMap<B256, ExecutingMessage>,
+}
+
+impl InMemoryMessageOracle {
+    /// Creates an oracle without messages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the initiating message and returns its executing message.
+    pub fn insert(&mut self, identifier: &MessageIdentifier, msg_hash: B256) -> ExecutingMessage {
+        let message = ExecutingMessage::new(identifier, msg_hash);
+        self.messages.insert(message.checksum, message);
+        message
+    }
+}
+
+impl MessageOracle for InMemoryMessageOracle {
+    fn is_valid(&self, message: &ExecutingMessage) -> bool {
+        self.messages.get(&message.checksum) == Some(message)
+    }
+}
+
+/// Invalid executing message of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteropError {
+    /// Storage keys of the inbox in the access list are not valid message entries.
+    InvalidAccessList,
+    /// Transaction declares executing messages but no [`MessageOracle`] is set.
+    MissingOracle,
+    /// Initiating message is newer than the executing block.
+    FutureMessage(B256),
+    /// Initiating message is older than the [`MESSAGE_EXPIRY_WINDOW`].
+    ExpiredMessage(B256),
+    /// Initiating message does not exist or has another checksum.
+    InvalidMessage(B256),
+}
+
+impl fmt::Display for InteropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAccessList => write!(f, "invalid CrossL2Inbox access list entries"),
+            Self::MissingOracle => write!(f, "no oracle to validate executing messages"),
+            Self::FutureMessage(checksum) => {
+                write!(f, "executing message {checksum} is from the future")
+            }
+            Self::ExpiredMessage(checksum) => write!(f, "executing message {checksum} expired"),
+            Self::InvalidMessage(checksum) => write!(f, "executing message {checksum} is invalid"),
+        }
+    }
+}
+
+impl core::error::Error for InteropError {}
+
+/// Validates the executing messages declared in the access list of the transaction, executed in
+/// a block with the timestamp.
+pub fn validate_executing_messages(
+    tx: &impl Transaction,
+    timestamp: u64,
+    oracle: Option<&dyn MessageOracle>,
+) -> Result<(), InteropError> {
+    let Some(access_list) = tx.access_list() else {
+        return Ok(());
+    };
+    for item in access_list.filter(|item| *item.address() == CROSS_L2_INBOX) {
+        for message in ExecutingMessage::parse_access_list(item.storage_slots())? {
+            let oracle = oracle.ok_or(InteropError::MissingOracle)?;
+            if message.timestamp > timestamp {
+                return Err(InteropError::FutureMessage(message.checksum));
+            }
+            if message.timestamp.saturating_add(MESSAGE_EXPIRY_WINDOW) < timestamp {
+                return Err(InteropError::ExpiredMessage(message.checksum));
+            }
+            if !oracle.is_valid(&message) {
+                return Err(InteropError::InvalidMessage(message.checksum));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs `validateMessage` of the [`CROSS_L2_INBOX`].
+///
+/// Reverts if the checksum of the message is not declared in the access list of the transaction
+/// or the transaction is a deposit, otherwise emits the [`EXECUTING_MESSAGE_SIGNATURE`] event.
+pub(crate) fn run_cross_l2_inbox<CTX: ContextTr>(
+    context: &mut CTX,
+    input: &[u8],
+    is_static: bool,
+    gas_limit: u64,
+) -> InterpreterResult {
+    let mut result =
+        InterpreterResult::new(InstructionResult::Revert, Bytes::new(), Gas::new(gas_limit));
+    if !result.gas.record_cost(VALIDATE_MESSAGE_GAS) {
+        result.result = InstructionResult::PrecompileOOG;
+        return result;
+    }
+
+    let identifier = match input.split_first_chunk::<4>() {
+        Some((selector, args))
+            if !is_static
+                && *selector == keccak256(VALIDATE_MESSAGE_SIGNATURE)[..4]
+                && args.len() == IDENTIFIER_LEN + 32 =>
+        {
+            MessageIdentifier::abi_decode(&args[..IDENTIFIER_LEN])
+        }
+        _ => None,
+    };
+    let Some(identifier) = identifier else {
+        return result;
+    };
+    if context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE {
+        result.output = revert_selector("NoExecutingDeposits()");
+        return result;
+    }
+
+    let msg_hash = B256::from_slice(&input[4 + IDENTIFIER_LEN..]);
+    let checksum = identifier.checksum(msg_hash);
+    let declared = context.tx().access_list().is_some_and(|mut access_list| {
+        access_list.any(|item| {
+            *item.address() == CROSS_L2_INBOX && item.storage_slots().any(|key| *key == checksum)
+        })
+    });
+    if !declared {
+        result.output = revert_selector("NotInAccessList()");
+        return result;
+    }
+
+    context.journal().log(Log::new_unchecked(
+        CROSS_L2_INBOX,
+        vec![keccak256(EXECUTING_MESSAGE_SIGNATURE), msg_hash],
+        Bytes::copy_from_slice(&input[4..4 + IDENTIFIER_LEN]),
+    ));
+    result.result = InstructionResult::Return;
+    result
+}
+
+fn revert_selector(signature: &str) -> Bytes {
+    Bytes::copy_from_slice(&keccak256(signature)[..4])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        precompiles::OpPrecompiles, transaction::OpTransactionError, DefaultOp, L1BlockInfo,
+        OpBuilder, OpSpecId,
+    };
+    use revm::{
+        context::{
+            result::{EVMError, ExecutionResult},
+            transaction::{AccessList, AccessListItem},
+            Context,
+        },
+        primitives::{address, b256, TxKind},
+        ExecuteEvm,
+    };
+    use std::sync::Arc;
+
+    fn identifier(chain_id: U256) -> MessageIdentifier {
+        MessageIdentifier {
+            origin: address!("0x1000000000000000000000000000000000000001"),
+            block_number: 7,
+            log_index: 2,
+            timestamp: 1_000,
+            chain_id,
+        }
+    }
+
+    fn validate_message_input(identifier: &MessageIdentifier, msg_hash: B256) -> Bytes {
+        let mut input = keccak256(VALIDATE_MESSAGE_SIGNATURE)[..4].to_vec();
+        input.extend_from_slice(identifier.origin.into_word().as_slice());
+        input.extend_from_slice(&U256::from(identifier.block_number).to_be_bytes::<32>());
+        input.extend_from_slice(&U256::from(identifier.log_index).to_be_bytes::<32>());
+        input.extend_from_slice(&U256::from(identifier.timestamp).to_be_bytes::<32>());
+        input.extend_from_slice(&identifier.chain_id.to_be_bytes::<32>());
+        input.extend_from_slice(msg_hash.as_slice());
+        input.into()
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(
+            identifier(U256::from(10)).checksum(B256::repeat_byte(1)),
+            b256!("0x03636d0e13d79106d5adafaa962e7434e1162fc8db4ff7459d8d5035f9d2388d")
+        );
+    }
+
+    #[test]
+    fn access_list_roundtrip() {
+        for chain_id in [U256::from(10), U256::MAX] {
+            let message = ExecutingMessage::new(&identifier(chain_id), B256::repeat_byte(1));
+            let keys = message.access_list_keys();
+            assert_eq!(keys.len(), if chain_id == U256::MAX { 3 } else { 2 });
+            assert_eq!(
+                ExecutingMessage::parse_access_list(&keys),
+                Ok(vec![message])
+            );
+            assert_eq!(
+                ExecutingMessage::parse_access_list(&keys[..1]),
+                Err(InteropError::InvalidAccessList)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_message() {
+        let msg_hash = B256::repeat_byte(1);
+        let identifier = identifier(U256::from(10));
+        let mut oracle = InMemoryMessageOracle::new();
+        let message = oracle.insert(&identifier, msg_hash);
+        let unknown = ExecutingMessage::new(&identifier, B256::repeat_byte(2));
+
+        let transact = |keys: Vec<B256>, is_deposit: bool| {
+            let ctx = Context::op()
+                .modify_tx_chained(|tx| {
+                    tx.base.kind = TxKind::Call(CROSS_L2_INBOX);
+                    tx.base.data = validate_message_input(&identifier, msg_hash);
+                    tx.base.access_list = AccessList(vec![AccessListItem {
+                        address: CROSS_L2_INBOX,
+                        storage_keys: keys,
+                    }]);
+                    if is_deposit {
+                        tx.enveloped_tx = None;
+                        tx.base.tx_type = DEPOSIT_TRANSACTION_TYPE;
+                    }
+                })
+                .modify_block_chained(|block| block.timestamp = 2_000)
+                .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::INTEROP)
+                // Interop is past isthmus, so the operator fee params have to be set.
+                .with_chain(L1BlockInfo {
+                    operator_fee_scalar: Some(U256::ZERO),
+                    operator_fee_constant: Some(U256::ZERO),
+                    ..Default::default()
+                });
+            let precompiles = OpPrecompiles::new_with_spec(OpSpecId::INTEROP)
+                .with_message_oracle(Arc::new(oracle.clone()));
+            ctx.build_op().with_precompiles(precompiles).replay()
+        };
+
+        let output = transact(message.access_list_keys(), false).unwrap();
+        assert!(output.result.is_success());
+        let [log] = output.result.logs() else {
+            panic!("one ExecutingMessage log");
+        };
+        assert_eq!(
+            log.topics(),
+            [keccak256(EXECUTING_MESSAGE_SIGNATURE), msg_hash]
+        );
+
+        // The lookup entry is not followed by a checksum entry.
+        let mut keys = unknown.access_list_keys();
+        keys.truncate(1);
+        keys.push(B256::with_last_byte(3));
+        assert_eq!(
+            transact(keys, false).unwrap_err(),
+            EVMError::Transaction(OpTransactionError::Interop(InteropError::InvalidAccessList))
+        );
+        assert_eq!(
+            transact(unknown.access_list_keys(), false).unwrap_err(),
+            EVMError::Transaction(OpTransactionError::Interop(InteropError::InvalidMessage(
+                unknown.checksum
+            )))
+        );
+
+        // Messages have to be declared in the access list, deposits can not execute them.
+        assert!(matches!(
+            transact(Vec::new(), false).unwrap().result,
+            ExecutionResult::Revert { output, .. } if output == revert_selector("NotInAccessList()")
+        ));
+        assert!(matches!(
+            transact(message.access_list_keys(), true).unwrap().result,
+            ExecutionResult::Revert { output, .. }
+                if output == revert_selector("NoExecutingDeposits()")
+        ));
+    }
+}
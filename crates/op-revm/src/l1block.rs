@@ -1,18 +1,57 @@
 use crate::{
     constants::{
-        BASE_FEE_SCALAR_OFFSET, BLOB_BASE_FEE_SCALAR_OFFSET, ECOTONE_L1_BLOB_BASE_FEE_SLOT,
+        BASE_FEE_SCALAR_OFFSET, BLOB_BASE_FEE_SCALAR_OFFSET, DA_FOOTPRINT_GAS_SCALAR_OFFSET,
+        DEFAULT_DA_FOOTPRINT_GAS_SCALAR, ECOTONE_L1_BLOB_BASE_FEE_SLOT,
         ECOTONE_L1_FEE_SCALARS_SLOT, EMPTY_SCALARS, L1_BASE_FEE_SLOT, L1_BLOCK_CONTRACT,
         L1_OVERHEAD_SLOT, L1_SCALAR_SLOT, NON_ZERO_BYTE_COST, OPERATOR_FEE_CONSTANT_OFFSET,
-        OPERATOR_FEE_SCALARS_SLOT, OPERATOR_FEE_SCALAR_DECIMAL, OPERATOR_FEE_SCALAR_OFFSET,
-        ZERO_BYTE_COST,
+        OPERATOR_FEE_JOVIAN_MULTIPLIER, OPERATOR_FEE_SCALARS_SLOT, OPERATOR_FEE_SCALAR_DECIMAL,
+        OPERATOR_FEE_SCALAR_OFFSET, UNSIGNED_TX_SIGNATURE_PADDING, ZERO_BYTE_COST,
     },
-    transaction::estimate_tx_compressed_size,
+    fast_lz::flz_compress_len,
+    transaction::{estimate_compressed_size_from_fastlz, estimate_tx_compressed_size},
     OpSpecId,
 };
-use core::ops::Mul;
+use core::{fmt, ops::Mul};
 use revm::{
     database_interface::Database, interpreter::Gas, primitives::hardfork::SpecId, primitives::U256,
 };
+use std::vec::Vec;
+
+/// Fee breakdown of an unsigned transaction, see [`L1BlockInfo::estimate_unsigned_tx_fee`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpFeeEstimate {
+    /// FastLZ-compressed size of the transaction, signature padding included.
+    pub fastlz_size: u64,
+    /// Estimated L1 data fee of the signed transaction.
+    pub l1_fee: U256,
+    /// Upper bound of the L1 data fee, as returned by `GasPriceOracle.getL1FeeUpperBound`.
+    ///
+    /// Equal to [`OpFeeEstimate::l1_fee`] prior to fjord.
+    pub l1_fee_upper_bound: U256,
+    /// Operator fee for the given gas. Zero prior to isthmus.
+    pub operator_fee: U256,
+}
+
+/// Error returned when the L1 block info lacks parameters required by the spec.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum L1BlockInfoError {
+    /// Operator fee scalar or constant is missing from the L1 block info of an isthmus block.
+    MissingOperatorFeeParams,
+}
+
+impl fmt::Display for L1BlockInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingOperatorFeeParams => {
+                write!(f, "missing operator fee params for isthmus L1 block info")
+            }
+        }
+    }
+}
+
+impl core::error::Error for L1BlockInfoError {}
 
 /// L1 block info
 ///
@@ -44,6 +83,8 @@ pub struct L1BlockInfo {
     pub operator_fee_scalar: Option<U256>,
     /// The current L1 blob base fee scalar. None if Isthmus is not activated.
     pub operator_fee_constant: Option<U256>,
+    /// The current DA footprint gas scalar. None if Jovian is not activated.
+    pub da_footprint_gas_scalar: Option<u16>,
     /// True if Ecotone is activated, but the L1 fee scalars have not yet been set.
     pub(crate) empty_ecotone_scalars: bool,
     /// Last calculated l1 fee cost. Uses as a cache between validation and pre execution stages.
@@ -118,6 +159,14 @@ impl L1BlockInfo {
                         [OPERATOR_FEE_CONSTANT_OFFSET..OPERATOR_FEE_CONSTANT_OFFSET + 8]
                         .as_ref(),
                 );
+                // Post-jovian, the `da_footprint_gas_scalar` is stored as a big endian u16 at
+                // DA_FOOTPRINT_GAS_SCALAR_OFFSET.
+                let da_footprint_gas_scalar = spec_id.is_enabled_in(OpSpecId::JOVIAN).then(|| {
+                    u16::from_be_bytes([
+                        operator_fee_scalars[DA_FOOTPRINT_GAS_SCALAR_OFFSET],
+                        operator_fee_scalars[DA_FOOTPRINT_GAS_SCALAR_OFFSET + 1],
+                    ])
+                });
                 Ok(L1BlockInfo {
                     l2_block,
                     l1_base_fee,
@@ -128,6 +177,7 @@ impl L1BlockInfo {
                     l1_fee_overhead,
                     operator_fee_scalar: Some(operator_fee_scalar),
                     operator_fee_constant: Some(operator_fee_constant),
+                    da_footprint_gas_scalar,
                     tx_l1_cost: None,
                 })
             } else {
@@ -148,44 +198,71 @@ impl L1BlockInfo {
     /// Calculate the operator fee for executing this transaction.
     ///
     /// Introduced in isthmus. Prior to isthmus, the operator fee is always zero.
-    pub fn operator_fee_charge(&self, input: &[u8], gas_limit: U256) -> U256 {
+    ///
+    /// Returns [`L1BlockInfoError::MissingOperatorFeeParams`] if the operator fee params are not
+    /// set.
+    pub fn operator_fee_charge(
+        &self,
+        input: &[u8],
+        gas_limit: U256,
+        spec_id: OpSpecId,
+    ) -> Result<U256, L1BlockInfoError> {
         // If the input is a deposit transaction or empty, the default value is zero.
         if input.first() == Some(&0x7E) {
-            return U256::ZERO;
+            return Ok(U256::ZERO);
         }
 
-        self.operator_fee_charge_inner(gas_limit)
+        self.operator_fee_charge_inner(gas_limit, spec_id)
     }
 
     /// Calculate the operator fee for the given `gas`.
-    fn operator_fee_charge_inner(&self, gas: U256) -> U256 {
-        let operator_fee_scalar = self
-            .operator_fee_scalar
-            .expect("Missing operator fee scalar for isthmus L1 Block");
-        let operator_fee_constant = self
-            .operator_fee_constant
-            .expect("Missing operator fee constant for isthmus L1 Block");
-
-        let product =
-            gas.saturating_mul(operator_fee_scalar) / (U256::from(OPERATOR_FEE_SCALAR_DECIMAL));
-
-        product.saturating_add(operator_fee_constant)
+    ///
+    /// [OpSpecId::ISTHMUS] operator fee function:
+    /// `gas * operatorFeeScalar / 1e6 + operatorFeeConstant`
+    ///
+    /// [OpSpecId::JOVIAN] operator fee function:
+    /// `gas * operatorFeeScalar * 100 + operatorFeeConstant`
+    fn operator_fee_charge_inner(
+        &self,
+        gas: U256,
+        spec_id: OpSpecId,
+    ) -> Result<U256, L1BlockInfoError> {
+        let (Some(operator_fee_scalar), Some(operator_fee_constant)) =
+            (self.operator_fee_scalar, self.operator_fee_constant)
+        else {
+            return Err(L1BlockInfoError::MissingOperatorFeeParams);
+        };
+
+        let product = if spec_id.is_enabled_in(OpSpecId::JOVIAN) {
+            gas.saturating_mul(operator_fee_scalar)
+                .saturating_mul(U256::from(OPERATOR_FEE_JOVIAN_MULTIPLIER))
+        } else {
+            gas.saturating_mul(operator_fee_scalar) / (U256::from(OPERATOR_FEE_SCALAR_DECIMAL))
+        };
+
+        Ok(product.saturating_add(operator_fee_constant))
     }
 
     /// Calculate the operator fee for executing this transaction.
     ///
     /// Introduced in isthmus. Prior to isthmus, the operator fee is always zero.
-    pub fn operator_fee_refund(&self, gas: &Gas, spec_id: OpSpecId) -> U256 {
+    pub fn operator_fee_refund(
+        &self,
+        gas: &Gas,
+        spec_id: OpSpecId,
+    ) -> Result<U256, L1BlockInfoError> {
         if !spec_id.is_enabled_in(OpSpecId::ISTHMUS) {
-            return U256::ZERO;
+            return Ok(U256::ZERO);
         }
 
-        let operator_cost_gas_limit = self.operator_fee_charge_inner(U256::from(gas.limit()));
-        let operator_cost_gas_used = self.operator_fee_charge_inner(U256::from(
-            gas.limit() - (gas.remaining() + gas.refunded() as u64),
-        ));
+        let operator_cost_gas_limit =
+            self.operator_fee_charge_inner(U256::from(gas.limit()), spec_id)?;
+        let operator_cost_gas_used = self.operator_fee_charge_inner(
+            U256::from(gas.limit() - (gas.remaining() + gas.refunded() as u64)),
+            spec_id,
+        )?;
 
-        operator_cost_gas_limit.saturating_sub(operator_cost_gas_used)
+        Ok(operator_cost_gas_limit.saturating_sub(operator_cost_gas_used))
     }
 
     /// Calculate the data gas for posting the transaction on L1. Calldata costs 16 gas per byte
@@ -227,6 +304,91 @@ impl L1BlockInfo {
         U256::from(estimate_tx_compressed_size(input))
     }
 
+    /// Calculate the DA footprint of the transaction, counted against the block gas limit.
+    ///
+    /// Introduced in jovian. Prior to jovian, and for deposit transactions, the DA footprint is
+    /// always zero. A zero or unset `daFootprintGasScalar` is replaced by the
+    /// [DEFAULT_DA_FOOTPRINT_GAS_SCALAR], as specified by the
+    /// [Jovian specs](https://specs.optimism.io/protocol/jovian/exec-engine.html#da-footprint-block-limit).
+    ///
+    /// [OpSpecId::JOVIAN] DA footprint function:
+    /// `estimatedSize / 1e6 * daFootprintGasScalar`
+    pub fn tx_da_footprint(&self, input: &[u8], spec_id: OpSpecId) -> u64 {
+        if !spec_id.is_enabled_in(OpSpecId::JOVIAN)
+            || input.is_empty()
+            || input.first() == Some(&0x7E)
+        {
+            return 0;
+        }
+
+        let da_usage_estimate = estimate_tx_compressed_size(input) / 1_000_000;
+        let da_footprint_gas_scalar = match self.da_footprint_gas_scalar {
+            None | Some(0) => DEFAULT_DA_FOOTPRINT_GAS_SCALAR,
+            Some(scalar) => scalar,
+        };
+        da_usage_estimate.saturating_mul(da_footprint_gas_scalar as u64)
+    }
+
+    /// Estimate the fees of an unsigned transaction.
+    ///
+    /// `unsigned_tx` is the EIP-2718 encoding of the transaction without signature. It is padded
+    /// by [UNSIGNED_TX_SIGNATURE_PADDING] bytes for the signature, as the `GasPriceOracle`
+    /// predeploy does. Prior to regolith, [`L1BlockInfo::data_gas`] already accounts for the
+    /// signature. The operator fee is charged on `gas`.
+    ///
+    /// Deposit transactions have no fees and return the default estimate. Returns
+    /// [`L1BlockInfoError::MissingOperatorFeeParams`] if the operator fee params of an isthmus
+    /// block are not set.
+    pub fn estimate_unsigned_tx_fee(
+        &self,
+        unsigned_tx: &[u8],
+        gas: u64,
+        spec_id: OpSpecId,
+    ) -> Result<OpFeeEstimate, L1BlockInfoError> {
+        if unsigned_tx.first() == Some(&0x7E) {
+            return Ok(OpFeeEstimate::default());
+        }
+
+        let fastlz_size = flz_compress_len(unsigned_tx) as u64 + UNSIGNED_TX_SIGNATURE_PADDING;
+        let (l1_fee, l1_fee_upper_bound) = if spec_id.is_enabled_in(OpSpecId::FJORD) {
+            let l1_fee = self.calculate_l1_cost_fjord(fastlz_size);
+
+            // txSize / 255 + 16 is the practical FastLZ upper bound, covering 99.99% of transactions.
+            let tx_size = unsigned_tx.len() as u64 + UNSIGNED_TX_SIGNATURE_PADDING;
+            let l1_fee_upper_bound = self.calculate_l1_cost_fjord(tx_size + tx_size / 255 + 16);
+
+            (l1_fee, l1_fee_upper_bound)
+        } else {
+            let mut signed_tx = Vec::from(unsigned_tx);
+            if spec_id.is_enabled_in(OpSpecId::REGOLITH) {
+                signed_tx.resize(
+                    unsigned_tx.len() + UNSIGNED_TX_SIGNATURE_PADDING as usize,
+                    0xFF,
+                );
+            }
+            let l1_fee = if spec_id.is_enabled_in(OpSpecId::ECOTONE) {
+                self.calculate_tx_l1_cost_ecotone(&signed_tx, spec_id)
+            } else {
+                self.calculate_tx_l1_cost_bedrock(&signed_tx, spec_id)
+            };
+
+            (l1_fee, l1_fee)
+        };
+
+        let operator_fee = if spec_id.is_enabled_in(OpSpecId::ISTHMUS) {
+            self.operator_fee_charge_inner(U256::from(gas), spec_id)?
+        } else {
+            U256::ZERO
+        };
+
+        Ok(OpFeeEstimate {
+            fastlz_size,
+            l1_fee,
+            l1_fee_upper_bound,
+            operator_fee,
+        })
+    }
+
     /// Clears the cached L1 cost of the transaction.
     pub fn clear_tx_l1_cost(&mut self) {
         self.tx_l1_cost = None;
@@ -293,8 +455,13 @@ impl L1BlockInfo {
     /// [OpSpecId::FJORD] L1 cost function:
     /// `estimatedSize*(baseFeeScalar*l1BaseFee*16 + blobFeeScalar*l1BlobBaseFee)/1e12`
     fn calculate_tx_l1_cost_fjord(&self, input: &[u8]) -> U256 {
+        self.calculate_l1_cost_fjord(flz_compress_len(input) as u64)
+    }
+
+    /// Calculate the [OpSpecId::FJORD] L1 cost function for the given FastLZ-compressed size.
+    fn calculate_l1_cost_fjord(&self, fastlz_size: u64) -> U256 {
         let l1_fee_scaled = self.calculate_l1_fee_scaled_ecotone();
-        let estimated_size = self.tx_estimated_size_fjord(input);
+        let estimated_size = U256::from(estimate_compressed_size_from_fastlz(fastlz_size));
 
         estimated_size
             .saturating_mul(l1_fee_scaled)
@@ -319,7 +486,11 @@ impl L1BlockInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use revm::primitives::{bytes, hex};
+    use revm::{
+        database::InMemoryDB,
+        primitives::{bytes, hex, Bytes},
+    };
+    use rstest::rstest;
 
     #[test]
     fn test_data_gas_non_zero_bytes() {
@@ -586,8 +757,228 @@ mod tests {
             ..Default::default()
         };
 
-        let refunded = l1_block_info.operator_fee_refund(&gas, OpSpecId::ISTHMUS);
+        let refunded = l1_block_info
+            .operator_fee_refund(&gas, OpSpecId::ISTHMUS)
+            .unwrap();
 
         assert_eq!(refunded, U256::from(100))
     }
+
+    #[test]
+    fn test_operator_fee_missing_params() {
+        let l1_block_info = L1BlockInfo::default();
+        let input = bytes!("FACADE");
+
+        assert_eq!(
+            l1_block_info.operator_fee_charge(&input, U256::from(50000), OpSpecId::ISTHMUS),
+            Err(L1BlockInfoError::MissingOperatorFeeParams)
+        );
+        assert_eq!(
+            l1_block_info.operator_fee_refund(&Gas::new(50000), OpSpecId::ISTHMUS),
+            Err(L1BlockInfoError::MissingOperatorFeeParams)
+        );
+        // No operator fee prior to isthmus.
+        assert_eq!(
+            l1_block_info.operator_fee_refund(&Gas::new(50000), OpSpecId::HOLOCENE),
+            Ok(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_operator_fee_charge_jovian() {
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(2000)),
+            operator_fee_constant: Some(U256::from(5)),
+            ..Default::default()
+        };
+        let input = bytes!("FACADE");
+        let gas_limit = U256::from(50000);
+
+        // operatorFee = gas * operatorFeeScalar / 1e6 + operatorFeeConstant
+        //             = 50000 * 2000 / 1e6 + 5
+        let isthmus_fee = l1_block_info
+            .operator_fee_charge(&input, gas_limit, OpSpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(isthmus_fee, U256::from(105));
+
+        // operatorFee = gas * operatorFeeScalar * 100 + operatorFeeConstant
+        //             = 50000 * 2000 * 100 + 5
+        let jovian_fee = l1_block_info
+            .operator_fee_charge(&input, gas_limit, OpSpecId::JOVIAN)
+            .unwrap();
+        assert_eq!(jovian_fee, U256::from(10_000_000_005u64));
+
+        // Deposit transactions with the EIP-2718 type of 0x7E should result in zero
+        let input = bytes!("7EFACADE");
+        let deposit_fee = l1_block_info
+            .operator_fee_charge(&input, gas_limit, OpSpecId::JOVIAN)
+            .unwrap();
+        assert_eq!(deposit_fee, U256::ZERO);
+    }
+
+    #[test]
+    fn test_operator_fee_refund_jovian() {
+        let gas = Gas::new(50000);
+
+        let l1_block_info = L1BlockInfo {
+            operator_fee_scalar: Some(U256::from(2000)),
+            operator_fee_constant: Some(U256::from(5)),
+            ..Default::default()
+        };
+
+        let refunded = l1_block_info
+            .operator_fee_refund(&gas, OpSpecId::JOVIAN)
+            .unwrap();
+
+        assert_eq!(refunded, U256::from(10_000_000_000u64))
+    }
+
+    // estimatedSize = max(minTransactionSize, intercept + fastlzCoef*fastlzSize)
+    // daFootprint = estimatedSize / 1e6 * daFootprintGasScalar
+    #[rstest]
+    #[case::empty(Bytes::new(), OpSpecId::JOVIAN, Some(400), 0)]
+    #[case::deposit(bytes!("7EFACADE"), OpSpecId::JOVIAN, Some(400), 0)]
+    #[case::pre_jovian(bytes!("FACADE"), OpSpecId::ISTHMUS, Some(400), 0)]
+    #[case::zero_scalar(bytes!("FACADE"), OpSpecId::JOVIAN, Some(0), 40_000)]
+    #[case::no_scalar(bytes!("FACADE"), OpSpecId::JOVIAN, None, 40_000)]
+    // estimatedSize = max(100e6, 836500*4 - 42585600) = 100e6
+    #[case::short_hex(bytes!("FACADE"), OpSpecId::JOVIAN, Some(400), 40_000)]
+    // estimatedSize = max(100e6, 836500*202 - 42585600) = 126387400
+    #[case::sample_contract_call(bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e"), OpSpecId::JOVIAN, Some(400), 50_400)]
+    fn test_tx_da_footprint(
+        #[case] input: Bytes,
+        #[case] spec_id: OpSpecId,
+        #[case] da_footprint_gas_scalar: Option<u16>,
+        #[case] expected: u64,
+    ) {
+        let l1_block_info = L1BlockInfo {
+            da_footprint_gas_scalar,
+            ..Default::default()
+        };
+
+        assert_eq!(l1_block_info.tx_da_footprint(&input, spec_id), expected);
+    }
+
+    #[test]
+    fn test_try_fetch_jovian() {
+        // daFootprintGasScalar = 400, operatorFeeScalar = 2000, operatorFeeConstant = 5
+        let mut operator_fee_scalars = [0u8; 32];
+        operator_fee_scalars[DA_FOOTPRINT_GAS_SCALAR_OFFSET..DA_FOOTPRINT_GAS_SCALAR_OFFSET + 2]
+            .copy_from_slice(&400u16.to_be_bytes());
+        operator_fee_scalars[OPERATOR_FEE_SCALAR_OFFSET..OPERATOR_FEE_SCALAR_OFFSET + 4]
+            .copy_from_slice(&2000u32.to_be_bytes());
+        operator_fee_scalars[OPERATOR_FEE_CONSTANT_OFFSET..OPERATOR_FEE_CONSTANT_OFFSET + 8]
+            .copy_from_slice(&5u64.to_be_bytes());
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_storage(
+            L1_BLOCK_CONTRACT,
+            OPERATOR_FEE_SCALARS_SLOT,
+            U256::from_be_bytes(operator_fee_scalars),
+        )
+        .unwrap();
+
+        let isthmus = L1BlockInfo::try_fetch(&mut db, 1, OpSpecId::ISTHMUS).unwrap();
+        assert_eq!(isthmus.operator_fee_scalar, Some(U256::from(2000)));
+        assert_eq!(isthmus.operator_fee_constant, Some(U256::from(5)));
+        assert_eq!(isthmus.da_footprint_gas_scalar, None);
+
+        let jovian = L1BlockInfo::try_fetch(&mut db, 1, OpSpecId::JOVIAN).unwrap();
+        assert_eq!(jovian.operator_fee_scalar, Some(U256::from(2000)));
+        assert_eq!(jovian.operator_fee_constant, Some(U256::from(5)));
+        assert_eq!(jovian.da_footprint_gas_scalar, Some(400));
+    }
+
+    #[test]
+    fn test_estimate_unsigned_tx_fee_fjord() {
+        // l1FeeScaled = 17e6, see `test_calculate_tx_l1_cost_fjord`
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            operator_fee_scalar: Some(U256::from(2000)),
+            operator_fee_constant: Some(U256::from(5)),
+            ..Default::default()
+        };
+
+        // fastLzSize = 4 + 68 = 72
+        // estimatedSize = max(100e6, 836500*72 - 42585600) = 100e6
+        // txSize = 3 + 68 = 71, flzUpperBound = 71 + 71/255 + 16 = 87
+        // estimatedSizeUpperBound = max(100e6, 836500*87 - 42585600) = 100e6
+        let input = bytes!("FACADE");
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 50000, OpSpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(
+            estimate,
+            OpFeeEstimate {
+                fastlz_size: 72,
+                l1_fee: U256::from(1700),
+                l1_fee_upper_bound: U256::from(1700),
+                operator_fee: U256::from(105),
+            }
+        );
+
+        // fastLzSize = 202 + 68 = 270
+        // estimatedSize = max(100e6, 836500*270 - 42585600) = 183269400
+        // l1Fee = 183269400 * 17e6 / 1e12 = 3115
+        // txSize = 345 + 68 = 413, flzUpperBound = 413 + 413/255 + 16 = 430
+        // estimatedSizeUpperBound = max(100e6, 836500*430 - 42585600) = 317109400
+        // l1FeeUpperBound = 317109400 * 17e6 / 1e12 = 5390
+        let input = bytes!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e");
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 0, OpSpecId::FJORD)
+            .unwrap();
+        assert_eq!(
+            estimate,
+            OpFeeEstimate {
+                fastlz_size: 270,
+                l1_fee: U256::from(3115),
+                l1_fee_upper_bound: U256::from(5390),
+                operator_fee: U256::ZERO,
+            }
+        );
+
+        // Deposit transactions with the EIP-2718 type of 0x7E should result in zero
+        let input = bytes!("7EFACADE");
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 50000, OpSpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(estimate, OpFeeEstimate::default());
+    }
+
+    #[test]
+    fn test_estimate_unsigned_tx_fee_pre_fjord() {
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            l1_fee_overhead: Some(U256::from(1_000)),
+            ..Default::default()
+        };
+        let input = bytes!("FACADE");
+
+        // dataGas = (3 + 68) * 16 = 1136
+        // l1Fee = (1136 + 1000) * 1000 * 1000 / 1e6 = 2136
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 0, OpSpecId::REGOLITH)
+            .unwrap();
+        assert_eq!(estimate.l1_fee, U256::from(2136));
+        assert_eq!(estimate.l1_fee_upper_bound, estimate.l1_fee);
+
+        // Bedrock data gas already includes the 68 bytes of the signature.
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 0, OpSpecId::BEDROCK)
+            .unwrap();
+        assert_eq!(estimate.l1_fee, U256::from(2136));
+
+        // l1Fee = 1136 * (1000 * 16 * 1000 + 1000 * 1000) / 16e6 = 1207
+        let estimate = l1_block_info
+            .estimate_unsigned_tx_fee(&input, 0, OpSpecId::ECOTONE)
+            .unwrap();
+        assert_eq!(estimate.l1_fee, U256::from(1207));
+        assert_eq!(estimate.operator_fee, U256::ZERO);
+    }
 }
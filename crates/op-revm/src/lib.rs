@@ -6,10 +6,12 @@
 extern crate alloc as std;
 
 pub mod api;
+pub mod block;
 pub mod constants;
 pub mod evm;
 pub mod fast_lz;
 pub mod handler;
+pub mod interop;
 pub mod l1block;
 pub mod precompiles;
 pub mod result;
@@ -21,7 +23,7 @@ pub use api::{
     default_ctx::{DefaultOp, OpContext},
 };
 pub use evm::OpEvm;
-pub use l1block::L1BlockInfo;
+pub use l1block::{L1BlockInfo, L1BlockInfoError, OpFeeEstimate};
 pub use result::OpHaltReason;
 pub use spec::*;
 pub use transaction::{error::OpTransactionError, estimate_tx_compressed_size, OpTransaction};
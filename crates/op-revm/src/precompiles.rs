@@ -1,10 +1,14 @@
-use crate::OpSpecId;
+use crate::{
+    constants::CROSS_L2_INBOX,
+    interop::{run_cross_l2_inbox, MessageOracle, MessageOracleProvider},
+    OpSpecId,
+};
 use once_cell::race::OnceBox;
 use revm::{
-    context::Cfg,
+    context::{Cfg, LocalContextTr},
     context_interface::ContextTr,
     handler::{EthPrecompiles, PrecompileProvider},
-    interpreter::{InputsImpl, InterpreterResult},
+    interpreter::{CallInput, InputsImpl, InterpreterResult},
     precompile::{
         self, bn128, secp256r1, PrecompileError, PrecompileResult, PrecompileWithAddress,
         Precompiles,
@@ -13,6 +17,7 @@ use revm::{
 };
 use std::boxed::Box;
 use std::string::String;
+use std::sync::Arc;
 
 // Optimism precompile provider
 #[derive(Debug, Clone)]
@@ -20,6 +25,8 @@ pub struct OpPrecompiles {
     /// Inner precompile provider is same as Ethereums.
     inner: EthPrecompiles,
     spec: OpSpecId,
+    /// Oracle that validates the executing messages of interop transactions.
+    message_oracle: Option<Arc<dyn MessageOracle>>,
 }
 
 impl OpPrecompiles {
@@ -33,7 +40,7 @@ impl OpPrecompiles {
             | OpSpecId::ECOTONE) => Precompiles::new(spec.into_eth_spec().into()),
             OpSpecId::FJORD => fjord(),
             OpSpecId::GRANITE | OpSpecId::HOLOCENE => granite(),
-            OpSpecId::ISTHMUS | OpSpecId::INTEROP | OpSpecId::OSAKA => isthmus(),
+            OpSpecId::ISTHMUS | OpSpecId::JOVIAN | OpSpecId::INTEROP | OpSpecId::OSAKA => isthmus(),
         };
 
         Self {
@@ -42,9 +49,22 @@ impl OpPrecompiles {
                 spec: SpecId::default(),
             },
             spec,
+            message_oracle: None,
         }
     }
 
+    /// Sets the oracle that validates the executing messages of interop transactions.
+    pub fn with_message_oracle(mut self, oracle: Arc<dyn MessageOracle>) -> Self {
+        self.message_oracle = Some(oracle);
+        self
+    }
+
+    /// Returns `true` if the CrossL2Inbox predeploy is run natively.
+    #[inline]
+    fn runs_cross_l2_inbox(&self, address: &Address) -> bool {
+        *address == CROSS_L2_INBOX && self.spec.is_enabled_in(OpSpecId::INTEROP)
+    }
+
     // Precompiles getter.
     #[inline]
     pub fn precompiles(&self) -> &'static Precompiles {
@@ -102,7 +122,9 @@ where
         if spec == self.spec {
             return false;
         }
+        let message_oracle = self.message_oracle.take();
         *self = Self::new_with_spec(spec);
+        self.message_oracle = message_oracle;
         true
     }
 
@@ -115,6 +137,19 @@ where
         is_static: bool,
         gas_limit: u64,
     ) -> Result<Option<Self::Output>, String> {
+        if self.runs_cross_l2_inbox(address) {
+            let input = match &inputs.input {
+                CallInput::SharedBuffer(range) => context
+                    .local()
+                    .shared_memory_buffer_slice(range.clone())
+                    .map(|slice| slice.to_vec())
+                    .unwrap_or_default(),
+                CallInput::Bytes(bytes) => bytes.to_vec(),
+            };
+            return Ok(Some(run_cross_l2_inbox(
+                context, &input, is_static, gas_limit,
+            )));
+        }
         self.inner
             .run(context, address, inputs, is_static, gas_limit)
     }
@@ -126,7 +161,13 @@ where
 
     #[inline]
     fn contains(&self, address: &Address) -> bool {
-        self.inner.contains(address)
+        self.runs_cross_l2_inbox(address) || self.inner.contains(address)
+    }
+}
+
+impl MessageOracleProvider for OpPrecompiles {
+    fn message_oracle(&self) -> Option<&dyn MessageOracle> {
+        self.message_oracle.as_deref()
     }
 }
 
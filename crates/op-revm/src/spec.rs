@@ -15,6 +15,7 @@ pub enum OpSpecId {
     HOLOCENE,
     #[default]
     ISTHMUS,
+    JOVIAN,
     INTEROP,
     OSAKA,
 }
@@ -26,7 +27,7 @@ impl OpSpecId {
             Self::BEDROCK | Self::REGOLITH => SpecId::MERGE,
             Self::CANYON => SpecId::SHANGHAI,
             Self::ECOTONE | Self::FJORD | Self::GRANITE | Self::HOLOCENE => SpecId::CANCUN,
-            Self::ISTHMUS | Self::INTEROP => SpecId::PRAGUE,
+            Self::ISTHMUS | Self::JOVIAN | Self::INTEROP => SpecId::PRAGUE,
             Self::OSAKA => SpecId::OSAKA,
         }
     }
@@ -55,6 +56,7 @@ impl FromStr for OpSpecId {
             name::GRANITE => Ok(OpSpecId::GRANITE),
             name::HOLOCENE => Ok(OpSpecId::HOLOCENE),
             name::ISTHMUS => Ok(OpSpecId::ISTHMUS),
+            name::JOVIAN => Ok(OpSpecId::JOVIAN),
             name::INTEROP => Ok(OpSpecId::INTEROP),
             eth_name::OSAKA => Ok(OpSpecId::OSAKA),
             _ => Err(UnknownHardfork),
@@ -73,6 +75,7 @@ impl From<OpSpecId> for &'static str {
             OpSpecId::GRANITE => name::GRANITE,
             OpSpecId::HOLOCENE => name::HOLOCENE,
             OpSpecId::ISTHMUS => name::ISTHMUS,
+            OpSpecId::JOVIAN => name::JOVIAN,
             OpSpecId::INTEROP => name::INTEROP,
             OpSpecId::OSAKA => eth_name::OSAKA,
         }
@@ -89,6 +92,7 @@ pub mod name {
     pub const GRANITE: &str = "Granite";
     pub const HOLOCENE: &str = "Holocene";
     pub const ISTHMUS: &str = "Isthmus";
+    pub const JOVIAN: &str = "Jovian";
     pub const INTEROP: &str = "Interop";
 }
 
@@ -195,6 +199,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn jovian_ordering_and_name() {
+        assert!(OpSpecId::JOVIAN.is_enabled_in(OpSpecId::ISTHMUS));
+        assert!(!OpSpecId::ISTHMUS.is_enabled_in(OpSpecId::JOVIAN));
+        assert!(OpSpecId::INTEROP.is_enabled_in(OpSpecId::JOVIAN));
+        assert_eq!(OpSpecId::JOVIAN.into_eth_spec(), SpecId::PRAGUE);
+        assert_eq!(OpSpecId::from_str("Jovian"), Ok(OpSpecId::JOVIAN));
+        assert_eq!(<&'static str>::from(OpSpecId::JOVIAN), name::JOVIAN);
+    }
+
     #[test]
     fn default_op_spec_id() {
         assert_eq!(OpSpecId::default(), OpSpecId::ISTHMUS);
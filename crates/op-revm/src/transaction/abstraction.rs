@@ -3,7 +3,7 @@ use auto_impl::auto_impl;
 use revm::{
     context::TxEnv,
     context_interface::transaction::Transaction,
-    handler::SystemCallTx,
+    handler::{EstimateGasTx, SystemCallTx},
     primitives::{Address, Bytes, TxKind, B256, U256},
 };
 use std::vec;
@@ -66,6 +66,12 @@ impl<TX: Transaction + SystemCallTx> SystemCallTx for OpTransaction<TX> {
     }
 }
 
+impl<TX: EstimateGasTx> EstimateGasTx for OpTransaction<TX> {
+    fn set_gas_limit(&mut self, gas_limit: u64) {
+        self.base.set_gas_limit(gas_limit);
+    }
+}
+
 impl<T: Transaction> Transaction for OpTransaction<T> {
     type AccessListItem<'a>
         = T::AccessListItem<'a>
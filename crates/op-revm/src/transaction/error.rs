@@ -1,3 +1,4 @@
+use crate::interop::InteropError;
 use core::fmt::Display;
 use revm::context_interface::{
     result::{EVMError, InvalidTransaction},
@@ -39,6 +40,8 @@ pub enum OpTransactionError {
     /// are cause for non-inclusion, so a special [OpHaltReason][crate::OpHaltReason] variant was introduced to handle this
     /// case for failed deposit transactions.
     HaltedDepositPostRegolith,
+    /// Executing message declared in the access list of an interop transaction is invalid.
+    Interop(InteropError),
 }
 
 impl TransactionError for OpTransactionError {}
@@ -59,6 +62,7 @@ impl Display for OpTransactionError {
                     "deposit transaction halted post-regolith; error will be bubbled up to main return handler"
                 )
             }
+            Self::Interop(error) => error.fmt(f),
         }
     }
 }
@@ -71,6 +75,12 @@ impl From<InvalidTransaction> for OpTransactionError {
     }
 }
 
+impl From<InteropError> for OpTransactionError {
+    fn from(value: InteropError) -> Self {
+        Self::Interop(value)
+    }
+}
+
 impl<DBError> From<OpTransactionError> for EVMError<DBError, OpTransactionError> {
     fn from(value: OpTransactionError) -> Self {
         Self::Transaction(value)
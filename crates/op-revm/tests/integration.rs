@@ -14,6 +14,7 @@ use revm::{
     },
     context_interface::result::HaltReason,
     database::{BenchmarkDB, EmptyDB, BENCH_CALLER, BENCH_CALLER_BALANCE, BENCH_TARGET},
+    handler::estimate_gas,
     interpreter::{
         gas::{calculate_initial_tx_gas, InitialAndFloorGas},
         Interpreter, InterpreterTypes,
@@ -773,3 +774,35 @@ fn test_log_inspector() {
 
     compare_or_save_testdata("test_log_inspector.json", &output);
 }
+
+#[test]
+fn test_estimate_gas() {
+    // SSTORE(0, 1)
+    let bytecode = Bytecode::new_legacy(
+        [
+            opcode::PUSH1,
+            1,
+            opcode::PUSH1,
+            0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]
+        .into(),
+    );
+    let ctx = Context::op()
+        .with_db(BenchmarkDB::new_bytecode(bytecode))
+        .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::HOLOCENE);
+    let mut evm = ctx.build_op();
+
+    let tx = OpTransaction {
+        base: TxEnv::builder()
+            .caller(BENCH_CALLER)
+            .kind(TxKind::Call(BENCH_TARGET))
+            .gas_limit(100_000)
+            .build_fill(),
+        ..Default::default()
+    };
+    let estimate = estimate_gas(&mut evm, tx).unwrap();
+    // Intrinsic gas, two pushes, cold SSTORE setting a new value.
+    assert_eq!(estimate.gas_limit(), Some(21_000 + 6 + 22_100));
+}
@@ -0,0 +1,262 @@
+//! Block access list of [EIP-7928](https://eips.ethereum.org/EIPS/eip-7928).
+//!
+//! The list records every account and storage slot accessed in a block together with the
+//! balance, nonce, code and storage changes, indexed by the block access index. Index `0` is
+//! used by the pre-execution system calls, index `i` by the `i`-th transaction (starting at one)
+//! and index `n + 1` by the post-execution changes, like withdrawals.
+use crate::{Account, AccountInfo};
+use core::fmt;
+use primitives::{Address, Bytes, StorageKey, StorageValue, KECCAK_EMPTY, U256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Accesses and changes of an account, the changes are ordered by block access index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountChanges {
+    /// Final values of the changed storage slots per block access index.
+    pub storage_changes: BTreeMap<StorageKey, Vec<(u64, StorageValue)>>,
+    /// Storage slots that were accessed but never changed.
+    pub storage_reads: BTreeSet<StorageKey>,
+    /// Balances after the change per block access index.
+    pub balance_changes: Vec<(u64, U256)>,
+    /// Nonces after the change per block access index.
+    pub nonce_changes: Vec<(u64, u64)>,
+    /// Codes after the change per block access index.
+    pub code_changes: Vec<(u64, Bytes)>,
+}
+
+/// Block access list, see the [module](self) documentation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockAccessList {
+    /// Accessed accounts.
+    pub accounts: BTreeMap<Address, AccountChanges>,
+}
+
+impl BlockAccessList {
+    /// Creates an empty block access list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the access of the account and returns its changes.
+    pub fn account_mut(&mut self, address: Address) -> &mut AccountChanges {
+        self.accounts.entry(address).or_default()
+    }
+
+    /// Records the read of the storage slot, ignored if the slot is changed in the block.
+    pub fn record_storage_read(&mut self, address: Address, key: StorageKey) {
+        let account = self.account_mut(address);
+        if !account.storage_changes.contains_key(&key) {
+            account.storage_reads.insert(key);
+        }
+    }
+
+    /// Records the value of the storage slot after the change.
+    pub fn record_storage_change(
+        &mut self,
+        index: u64,
+        address: Address,
+        key: StorageKey,
+        value: StorageValue,
+    ) {
+        let account = self.account_mut(address);
+        account.storage_reads.remove(&key);
+        push_change(
+            account.storage_changes.entry(key).or_default(),
+            index,
+            value,
+        );
+    }
+
+    /// Records the balance of the account after the change.
+    pub fn record_balance_change(&mut self, index: u64, address: Address, balance: U256) {
+        push_change(
+            &mut self.account_mut(address).balance_changes,
+            index,
+            balance,
+        );
+    }
+
+    /// Records the nonce of the account after the change.
+    pub fn record_nonce_change(&mut self, index: u64, address: Address, nonce: u64) {
+        push_change(&mut self.account_mut(address).nonce_changes, index, nonce);
+    }
+
+    /// Records the code of the account after the change.
+    pub fn record_code_change(&mut self, index: u64, address: Address, code: Bytes) {
+        push_change(&mut self.account_mut(address).code_changes, index, code);
+    }
+
+    /// Records the accesses and changes of an account of the EVM state.
+    ///
+    /// `original` is the account info before the execution at `index`. Only touched accounts
+    /// have their changes recorded, storage slots of other accounts are recorded as reads.
+    pub fn record_account(
+        &mut self,
+        index: u64,
+        address: Address,
+        original: &AccountInfo,
+        account: &Account,
+    ) {
+        self.account_mut(address);
+        if !account.is_touched() {
+            for key in account.storage.keys() {
+                self.record_storage_read(address, *key);
+            }
+            return;
+        }
+
+        let destroyed = AccountInfo::default();
+        let info = if account.is_selfdestructed() {
+            &destroyed
+        } else {
+            &account.info
+        };
+        if info.balance != original.balance {
+            self.record_balance_change(index, address, info.balance);
+        }
+        if info.nonce != original.nonce {
+            self.record_nonce_change(index, address, info.nonce);
+        }
+        if info.code_hash != original.code_hash {
+            let code = match &info.code {
+                Some(code) if info.code_hash != KECCAK_EMPTY => code.original_bytes(),
+                _ => Bytes::new(),
+            };
+            self.record_code_change(index, address, code);
+        }
+        for (key, slot) in &account.storage {
+            if slot.is_changed() {
+                self.record_storage_change(index, address, *key, slot.present_value);
+            } else {
+                self.record_storage_read(address, *key);
+            }
+        }
+    }
+
+    /// Validates the provided block access list against this one, built by the execution.
+    pub fn validate(&self, provided: &BlockAccessList) -> Result<(), BlockAccessListError> {
+        if let Some(address) = provided
+            .accounts
+            .keys()
+            .find(|address| !self.accounts.contains_key(*address))
+        {
+            return Err(BlockAccessListError::UnexpectedAccount(*address));
+        }
+        for (address, changes) in &self.accounts {
+            match provided.accounts.get(address) {
+                None => return Err(BlockAccessListError::MissingAccount(*address)),
+                Some(provided) if provided != changes => {
+                    return Err(BlockAccessListError::AccountMismatch(*address))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the last change if it was made at the same index, otherwise appends it.
+fn push_change<T>(changes: &mut Vec<(u64, T)>, index: u64, value: T) {
+    match changes.last_mut() {
+        Some(last) if last.0 == index => last.1 = value,
+        _ => changes.push((index, value)),
+    }
+}
+
+/// Mismatch between a provided block access list and the execution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockAccessListError {
+    /// Account accessed by the execution is missing from the provided list.
+    MissingAccount(Address),
+    /// Account of the provided list was not accessed by the execution.
+    UnexpectedAccount(Address),
+    /// Accesses or changes of the account differ from the execution.
+    AccountMismatch(Address),
+}
+
+impl fmt::Display for BlockAccessListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => {
+                write!(f, "block access list is missing account {address}")
+            }
+            Self::UnexpectedAccount(address) => {
+                write!(f, "block access list has unaccessed account {address}")
+            }
+            Self::AccountMismatch(address) => {
+                write!(f, "block access list changes of account {address} mismatch")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BlockAccessListError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EvmStorageSlot;
+
+    #[test]
+    fn record_account() {
+        let address = Address::with_last_byte(1);
+        let original = AccountInfo::from_balance(U256::from(10));
+        let mut account = Account::from(original.clone());
+        account.mark_touch();
+        account.info.balance = U256::from(5);
+        account.info.nonce = 1;
+        account.storage.insert(
+            U256::from(1),
+            EvmStorageSlot::new_changed(U256::ZERO, U256::from(2)),
+        );
+        account
+            .storage
+            .insert(U256::from(2), EvmStorageSlot::new(U256::from(3)));
+
+        let mut list = BlockAccessList::new();
+        list.record_account(1, address, &original, &account);
+        account.info.balance = U256::from(4);
+        list.record_account(1, address, &original, &account);
+        // The read slot is changed by a later transaction.
+        list.record_storage_change(2, address, U256::from(2), U256::ZERO);
+
+        let changes = &list.accounts[&address];
+        assert_eq!(changes.balance_changes, [(1, U256::from(4))]);
+        assert_eq!(changes.nonce_changes, [(1, 1)]);
+        assert!(changes.code_changes.is_empty());
+        assert_eq!(
+            changes.storage_changes[&U256::from(1)],
+            [(1, U256::from(2))]
+        );
+        assert_eq!(changes.storage_changes[&U256::from(2)], [(2, U256::ZERO)]);
+        assert!(changes.storage_reads.is_empty());
+    }
+
+    #[test]
+    fn validate() {
+        let address = Address::with_last_byte(1);
+        let mut list = BlockAccessList::new();
+        list.record_storage_read(address, U256::from(1));
+        assert_eq!(list.validate(&list.clone()), Ok(()));
+
+        let mut provided = list.clone();
+        provided.record_balance_change(1, address, U256::from(1));
+        assert_eq!(
+            list.validate(&provided),
+            Err(BlockAccessListError::AccountMismatch(address))
+        );
+        assert_eq!(
+            list.validate(&BlockAccessList::new()),
+            Err(BlockAccessListError::MissingAccount(address))
+        );
+        assert_eq!(
+            BlockAccessList::new().validate(&list),
+            Err(BlockAccessListError::UnexpectedAccount(address))
+        );
+    }
+}
@@ -2,11 +2,16 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc as std;
+
 mod account_info;
+pub mod block_access_list;
 mod types;
 pub use bytecode;
 
 pub use account_info::AccountInfo;
+pub use block_access_list::{AccountChanges, BlockAccessList, BlockAccessListError};
 pub use bytecode::Bytecode;
 pub use primitives;
 pub use types::{EvmState, EvmStorage, TransientStorage};
@@ -0,0 +1,225 @@
+use crate::{deserializer::deserialize_maybe_empty, AccountInfo, SpecName, TestAuthorization};
+use revm::{
+    context_interface::transaction::AccessList,
+    primitives::{Address, Bytes, FixedBytes, HashMap, B256, U256},
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The top level blockchain test suite struct
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTest(pub BTreeMap<String, BlockchainTestCase>);
+
+/// Single blockchain test case.
+///
+/// Blocks are executed in order on top of the `pre` state. After each valid block the
+/// state root has to match the one in its header.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTestCase {
+    /// Test info is optional.
+    #[serde(default, rename = "_info")]
+    pub info: Option<serde_json::Value>,
+
+    /// Fork the blocks are executed in.
+    pub network: SpecName,
+    /// Header of the genesis block.
+    pub genesis_block_header: BlockHeader,
+    /// RLP encoded genesis block.
+    #[serde(default, rename = "genesisRLP")]
+    pub genesis_rlp: Option<Bytes>,
+    /// Genesis state.
+    pub pre: HashMap<Address, AccountInfo>,
+    /// Expected state after all blocks are executed.
+    #[serde(default)]
+    pub post_state: Option<HashMap<Address, AccountInfo>>,
+    /// Expected state root after all blocks are executed.
+    ///
+    /// Large fixtures contain only the hash instead of the full `post_state`.
+    #[serde(default)]
+    pub post_state_hash: Option<B256>,
+    /// Hash of the last valid block.
+    #[serde(rename = "lastblockhash")]
+    pub last_block_hash: B256,
+    /// Blocks in the order they are imported.
+    pub blocks: Vec<Block>,
+    /// Seal engine, `NoProof` for all execution spec tests.
+    #[serde(default)]
+    pub seal_engine: Option<String>,
+}
+
+/// Block of the blockchain test.
+///
+/// Invalid blocks have `expect_exception` set and may contain only the `rlp` field.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    /// RLP encoded block.
+    #[serde(default)]
+    pub rlp: Bytes,
+    /// Decoded block header.
+    #[serde(default)]
+    pub block_header: Option<BlockHeader>,
+    /// Decoded transactions with recovered senders.
+    #[serde(default)]
+    pub transactions: Vec<BlockTransaction>,
+    /// Decoded uncle headers.
+    #[serde(default)]
+    pub uncle_headers: Vec<BlockHeader>,
+    /// Withdrawals of the block, present from Shanghai.
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    /// Exception expected when importing the block.
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+}
+
+/// Block header.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub parent_hash: B256,
+    pub uncle_hash: B256,
+    pub coinbase: Address,
+    pub state_root: B256,
+    pub transactions_trie: B256,
+    pub receipt_trie: B256,
+    pub bloom: FixedBytes<256>,
+    pub difficulty: U256,
+    pub number: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub mix_hash: B256,
+    pub nonce: FixedBytes<8>,
+    pub base_fee_per_gas: Option<U256>,
+    pub withdrawals_root: Option<B256>,
+    pub blob_gas_used: Option<U256>,
+    pub excess_blob_gas: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+    pub requests_hash: Option<B256>,
+    /// Hash of the block.
+    pub hash: B256,
+}
+
+/// Decoded transaction of the block.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransaction {
+    #[serde(rename = "type", default)]
+    pub tx_type: Option<U256>,
+    pub chain_id: Option<U256>,
+    pub nonce: U256,
+    pub gas_price: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub gas_limit: U256,
+    #[serde(default, deserialize_with = "deserialize_maybe_empty")]
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: Option<AccessList>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    pub authorization_list: Option<Vec<TestAuthorization>>,
+    /// Recovered sender of the transaction.
+    pub sender: Address,
+}
+
+/// Validator withdrawal.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: U256,
+    pub validator_index: U256,
+    pub address: Address,
+    /// Amount in gwei.
+    pub amount: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_blockchain_test() {
+        let test = r#"{
+            "withdrawals_to_empty_account": {
+                "network": "Shanghai",
+                "genesisBlockHeader": {
+                    "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "uncleHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "coinbase": "0x0000000000000000000000000000000000000000",
+                    "stateRoot": "0x6b7cd1a09b2e4d23bd6e7a7e2b3ea0f9b37ef2c8b38b5cca4d0b6c2d8b6d53c4",
+                    "transactionsTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "receiptTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                    "difficulty": "0x00",
+                    "number": "0x00",
+                    "gasLimit": "0x016345785d8a0000",
+                    "gasUsed": "0x00",
+                    "timestamp": "0x00",
+                    "extraData": "0x00",
+                    "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "nonce": "0x0000000000000000",
+                    "baseFeePerGas": "0x07",
+                    "withdrawalsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "hash": "0x1c1ea4e6c4ae8a8bd1ba1e8b1c1fd8c34b2eb0c1ad3c0b4b1f7b6d1b1ad1a1a1"
+                },
+                "pre": {
+                    "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {
+                        "nonce": "0x00",
+                        "balance": "0x3635c9adc5dea00000",
+                        "code": "0x",
+                        "storage": {}
+                    }
+                },
+                "postState": {},
+                "lastblockhash": "0x1c1ea4e6c4ae8a8bd1ba1e8b1c1fd8c34b2eb0c1ad3c0b4b1f7b6d1b1ad1a1a1",
+                "blocks": [
+                    {
+                        "rlp": "0xf90100",
+                        "transactions": [
+                            {
+                                "type": "0x02",
+                                "chainId": "0x01",
+                                "nonce": "0x00",
+                                "maxPriorityFeePerGas": "0x00",
+                                "maxFeePerGas": "0x07",
+                                "gasLimit": "0x5208",
+                                "to": "",
+                                "value": "0x01",
+                                "data": "0x",
+                                "accessList": [],
+                                "v": "0x00",
+                                "r": "0x01",
+                                "s": "0x01",
+                                "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+                            }
+                        ],
+                        "uncleHeaders": [],
+                        "withdrawals": [
+                            {
+                                "index": "0x00",
+                                "validatorIndex": "0x00",
+                                "address": "0xc94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+                                "amount": "0x01"
+                            }
+                        ],
+                        "expectException": "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS"
+                    }
+                ],
+                "sealEngine": "NoProof"
+            }
+        }"#;
+
+        let test: BlockchainTest = serde_json::from_str(test).unwrap();
+        let case = &test.0["withdrawals_to_empty_account"];
+        assert_eq!(case.network, SpecName::Shanghai);
+        assert_eq!(case.blocks[0].transactions[0].to, None);
+        assert_eq!(case.blocks[0].withdrawals.as_ref().unwrap().len(), 1);
+        assert!(case.blocks[0].block_header.is_none());
+    }
+}
@@ -7,6 +7,7 @@
 //! test cases, and transaction data used in Ethereum state tests.
 
 mod account_info;
+mod blockchain;
 mod deserializer;
 mod env;
 mod spec;
@@ -17,6 +18,7 @@ mod test_unit;
 mod transaction;
 
 pub use account_info::*;
+pub use blockchain::*;
 pub use deserializer::*;
 pub use env::*;
 pub use spec::*;
@@ -136,6 +136,10 @@ impl JournalTr for Backend {
         self.journaled_state.set_spec_id(spec_id);
     }
 
+    fn set_transfer_logs(&mut self, enabled: bool) {
+        self.journaled_state.set_transfer_logs(enabled);
+    }
+
     fn touch_account(&mut self, address: Address) {
         self.journaled_state.touch_account(address);
     }